@@ -0,0 +1,242 @@
+use super::RetryConfig;
+#[cfg(test)]
+use crate::search::tokenize;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, time::Duration};
+use tokio::time::sleep;
+
+// 本地向量的维数
+#[cfg(test)]
+const LOCAL_DIMENSIONS: usize = 256;
+// 本地向量的模型名，与远程模型区分
+#[cfg(test)]
+const LOCAL_MODEL: &str = "local-hash";
+// 每次请求最多嵌入的文本数
+const BATCH_SIZE: usize = 16;
+
+// 文本向量化，调用七牛云接口；测试时可使用本地的特征哈希
+#[derive(Clone)]
+pub enum Embedder {
+    Remote {
+        http_client: reqwest::Client,
+        model: String,
+        retry_config: RetryConfig,
+    },
+    // 结果确定，不访问网络，只能匹配字面相近的文本，只用于测试
+    #[cfg(test)]
+    Local,
+}
+
+impl Embedder {
+    pub fn new(ai_api_key: &str, model: &str) -> Self {
+        let headers = {
+            let mut headers = reqwest::header::HeaderMap::new();
+            headers.insert(
+                reqwest::header::CONTENT_TYPE,
+                "application/json".parse().unwrap(),
+            );
+            headers.insert(
+                reqwest::header::AUTHORIZATION,
+                format!("Bearer {}", ai_api_key).parse().unwrap(),
+            );
+            headers
+        };
+
+        let http_client = reqwest::Client::builder()
+            .default_headers(headers)
+            .build()
+            .unwrap();
+
+        Self::Remote {
+            http_client,
+            model: model.to_string(),
+            retry_config: RetryConfig::default(),
+        }
+    }
+
+    #[cfg(test)]
+    pub fn local() -> Self {
+        Self::Local
+    }
+
+    // 随向量一起保存，换了模型后重新计算
+    pub fn model(&self) -> &str {
+        match self {
+            Self::Remote { model, .. } => model,
+            #[cfg(test)]
+            Self::Local => LOCAL_MODEL,
+        }
+    }
+
+    pub async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        match self {
+            Self::Remote {
+                http_client,
+                model,
+                retry_config,
+            } => {
+                let mut vectors = Vec::with_capacity(texts.len());
+
+                for chunk in texts.chunks(BATCH_SIZE) {
+                    vectors.extend(embed_remote(http_client, model, retry_config, chunk).await?);
+                }
+
+                Ok(vectors)
+            }
+            #[cfg(test)]
+            Self::Local => Ok(texts.iter().map(|text| embed_local(text)).collect()),
+        }
+    }
+
+    pub async fn embed_one(&self, text: &str) -> Result<Vec<f32>> {
+        let mut vectors = self.embed(&[text.to_string()]).await?;
+
+        vectors
+            .pop()
+            .ok_or_else(|| anyhow::anyhow!("Embedding API returned no vector"))
+    }
+}
+
+async fn embed_remote(
+    http_client: &reqwest::Client,
+    model: &str,
+    retry_config: &RetryConfig,
+    texts: &[String],
+) -> Result<Vec<Vec<f32>>> {
+    const URL: &str = "https://openai.qiniu.com/v1/embeddings";
+
+    let data = RequestParams {
+        model: model.to_string(),
+        input: texts.to_vec(),
+    };
+
+    let mut last_error = None;
+
+    for attempt in 0..retry_config.max_retries {
+        match embed_attempt(http_client, &data, URL).await {
+            Ok(vectors) if vectors.len() == texts.len() => return Ok(vectors),
+            Ok(vectors) => {
+                last_error = Some(anyhow::anyhow!(
+                    "Embedding API returned {} vectors for {} texts",
+                    vectors.len(),
+                    texts.len()
+                ));
+            }
+            Err(e) => last_error = Some(e),
+        }
+
+        if attempt < retry_config.max_retries - 1 {
+            let delay = Duration::from_millis(retry_config.base_delay_ms * (1 << attempt)); // 指数退避
+            tracing::warn!(
+                "Embedding request failed on attempt {}/{}, retrying in {:?}: {}",
+                attempt + 1,
+                retry_config.max_retries,
+                delay,
+                last_error.as_ref().unwrap()
+            );
+            sleep(delay).await;
+        }
+    }
+
+    Err(last_error.unwrap())
+}
+
+async fn embed_attempt(
+    http_client: &reqwest::Client,
+    data: &RequestParams,
+    url: &str,
+) -> Result<Vec<Vec<f32>>> {
+    let res = http_client.post(url).json(data).send().await?;
+
+    if !res.status().is_success() {
+        return Err(anyhow::anyhow!(
+            "Embedding API returned status: {}",
+            res.status()
+        ));
+    }
+
+    let body = res.text().await?;
+    let mut res = serde_json::from_str::<Response>(&body)?;
+    res.data.sort_by_key(|item| item.index);
+
+    Ok(res.data.into_iter().map(|item| item.embedding).collect())
+}
+
+// 把词哈希到固定维数，用第二个哈希决定正负以减少冲突的影响
+#[cfg(test)]
+fn embed_local(text: &str) -> Vec<f32> {
+    let mut vector = vec![0.0; LOCAL_DIMENSIONS];
+
+    for term in tokenize(text) {
+        let hash = fnv1a(term.as_bytes());
+        let index = (hash % LOCAL_DIMENSIONS as u64) as usize;
+        let sign = if (hash >> 32) & 1 == 0 { 1.0 } else { -1.0 };
+
+        vector[index] += sign;
+    }
+
+    vector
+}
+
+#[cfg(test)]
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[derive(Serialize)]
+struct RequestParams {
+    model: String,
+    input: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct Response {
+    data: Vec<ResponseItem>,
+    #[serde(flatten)]
+    _others: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Deserialize)]
+struct ResponseItem {
+    index: usize,
+    embedding: Vec<f32>,
+    #[serde(flatten)]
+    _others: HashMap<String, serde_json::Value>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::VectorIndex;
+
+    #[tokio::test]
+    async fn test_local_embedder() {
+        let embedder = Embedder::local();
+        assert_eq!(embedder.model(), LOCAL_MODEL);
+
+        let texts = [
+            "幽默的物理学家，喜欢用比喻讲解相对论".to_string(),
+            "唐代浪漫主义诗人，嗜酒，写月亮".to_string(),
+            "严谨的物理学家，研究量子力学".to_string(),
+        ];
+        let vectors = embedder.embed(&texts).await.unwrap();
+
+        // 结果确定
+        assert_eq!(vectors, embedder.embed(&texts).await.unwrap());
+        assert!(vectors.iter().all(|v| v.len() == LOCAL_DIMENSIONS));
+
+        let index = VectorIndex::new();
+        for (role_id, vector) in vectors.into_iter().enumerate() {
+            index.insert(role_id as i32, vector);
+        }
+
+        let query = embedder.embed_one("教我物理的幽默老师").await.unwrap();
+        assert_eq!(index.nearest(&query, 1, None)[0].0, 0);
+
+        let similar = index.nearest(&index.get(0).unwrap(), 1, Some(0));
+        assert_eq!(similar[0].0, 2);
+    }
+}
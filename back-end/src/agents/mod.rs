@@ -1,7 +1,13 @@
 mod debater;
+mod embedder;
 pub mod reciter;
+pub mod recommender;
 mod recorder;
+pub mod role_batches;
 pub mod role_builder;
+mod role_embeddings;
+pub mod role_jobs;
+mod structured;
 mod summarizer;
 
 use anyhow::Result;
@@ -11,17 +17,41 @@ use llm_chain::{
     parameters, prompt,
 };
 use llm_chain_openai::chatgpt::Executor;
+use std::sync::{
+    Arc,
+    atomic::{AtomicU64, Ordering},
+};
 
 pub use debater::Debater;
+pub use embedder::Embedder;
 pub use reciter::Reciter;
+pub use recommender::Recommender;
 pub use recorder::Recorder;
+pub use role_batches::RoleBatches;
 pub use role_builder::RoleBuilder;
+pub use role_embeddings::RoleEmbeddings;
+pub use role_jobs::RoleJobs;
 pub use summarizer::Summarizer;
 
 #[derive(Clone)]
 pub struct AI {
     executor: Executor,
     thinking_executor: Executor,
+    calls: CallCounter,
+}
+
+// 进程启动以来的接口调用次数，克隆后共享计数，重启后清零
+#[derive(Clone, Default)]
+pub struct CallCounter(Arc<AtomicU64>);
+
+impl CallCounter {
+    pub fn increment(&self) {
+        self.0.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn get(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
 }
 
 impl AI {
@@ -52,9 +82,25 @@ impl AI {
         Self {
             executor,
             thinking_executor,
+            calls: CallCounter::default(),
         }
     }
 
+    // 每次调用模型前取一次执行器，顺便计数
+    fn executor(&self) -> &Executor {
+        self.calls.increment();
+        &self.executor
+    }
+
+    fn thinking_executor(&self) -> &Executor {
+        self.calls.increment();
+        &self.thinking_executor
+    }
+
+    pub fn llm_calls(&self) -> u64 {
+        self.calls.get()
+    }
+
     pub async fn chat_once(
         &self,
         system: &str,
@@ -70,7 +116,7 @@ impl AI {
         let res = prompt!("{{system}}\n{{history}}", "{{user}}\nAssistant:")
             .run(
                 &parameters!("system" => system, "history" => history, "user" => user),
-                &self.thinking_executor,
+                self.thinking_executor(),
             )
             .await?
             .to_immediate()
@@ -155,4 +201,16 @@ mod tests {
             .unwrap();
         println!("Reply:\n{}", reply);
     }
+
+    #[test]
+    fn test_call_counter() {
+        let counter = CallCounter::default();
+        let cloned = counter.clone();
+
+        counter.increment();
+        cloned.increment();
+
+        assert_eq!(counter.get(), 2);
+        assert_eq!(cloned.get(), 2);
+    }
 }
@@ -1,4 +1,4 @@
-use super::RetryConfig;
+use super::{CallCounter, RetryConfig};
 use crate::{database::models::roles::Gender, storage::StorageClient};
 use anyhow::{Result, anyhow};
 use base64::{Engine, engine::general_purpose};
@@ -11,6 +11,7 @@ pub struct Reciter {
     storage_client: Arc<StorageClient>,
     http_client: reqwest::Client,
     retry_config: RetryConfig,
+    calls: CallCounter,
 }
 
 impl Reciter {
@@ -37,6 +38,7 @@ impl Reciter {
             storage_client,
             http_client,
             retry_config: RetryConfig::default(),
+            calls: CallCounter::default(),
         }
     }
 
@@ -94,7 +96,14 @@ impl Reciter {
         Err(last_error.unwrap())
     }
 
+    // 重试也计入调用次数
+    pub fn tts_calls(&self) -> u64 {
+        self.calls.get()
+    }
+
     async fn tts_attempt(&self, data: &RequestParams, url: &str) -> Result<Vec<u8>> {
+        self.calls.increment();
+
         let res = self.http_client.post(url).json(data).send().await?;
 
         if !res.status().is_success() {
@@ -0,0 +1,412 @@
+use super::RoleEmbeddings;
+use crate::{
+    database::{
+        Database,
+        models::{role_reactions::ReactionKind, roles},
+    },
+    search::RoleFilter,
+    stats::DAY_MS,
+};
+use anyhow::Result;
+use chrono::Utc;
+use serde::Serialize;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+// 参考的最近对话和辩论数
+const RECENT_ACTIVITIES: u64 = 20;
+// 作为推荐依据的角色数
+const MAX_SEEDS: usize = 5;
+// 每个依据角色取的相似角色数
+const NEIGHBOURS: usize = 20;
+// 推荐列表的最大长度
+const MAX_FEED: usize = 100;
+// 互动的权重每14天减半
+const HALF_LIFE_DAYS: f32 = 14.0;
+// 参加一场辩论相当于的对话轮数
+const DEBATE_DIALOGS: i64 = 4;
+// 收藏一个角色相当于的对话轮数
+const FAVOURITE_DIALOGS: i64 = 10;
+
+// 根据用户的对话、辩论和收藏推荐相似的角色，没有互动时推荐热门角色
+pub struct Recommender {
+    database: Arc<Database>,
+    role_embeddings: Arc<RoleEmbeddings>,
+}
+
+pub struct Recommendation {
+    pub role: roles::Model,
+    pub reason: Reason,
+}
+
+// 推荐理由
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Reason {
+    Conversation { role_id: i32, role_name: String },
+    Debate { role_id: i32, role_name: String },
+    Favourite { role_id: i32, role_name: String },
+    Trending,
+}
+
+impl Reason {
+    pub fn explain(&self) -> String {
+        match self {
+            Self::Conversation { role_name, .. } => format!("因为你和{}聊过", role_name),
+            Self::Debate { role_name, .. } => format!("因为你让{}参加过辩论", role_name),
+            Self::Favourite { role_name, .. } => format!("因为你收藏了{}", role_name),
+            Self::Trending => "最近很多人在聊".to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Source {
+    Conversation,
+    Debate,
+    Favourite,
+}
+
+struct Activity {
+    role_id: i32,
+    source: Source,
+    dialogs: i64,
+    timestamp: i64,
+}
+
+#[derive(Debug, PartialEq)]
+struct Seed {
+    role_id: i32,
+    source: Source,
+    weight: f32,
+}
+
+impl Recommender {
+    pub fn new(database: Arc<Database>, role_embeddings: Arc<RoleEmbeddings>) -> Self {
+        Self {
+            database,
+            role_embeddings,
+        }
+    }
+
+    pub async fn feed(&self, user_id: i32) -> Result<Vec<Recommendation>> {
+        let now = Utc::now().timestamp_millis();
+        let activities = self.activities(user_id).await?;
+
+        // 已经互动过的角色不再推荐
+        let mut seen: HashSet<i32> = activities.iter().map(|a| a.role_id).collect();
+
+        let mut neighbours: HashMap<i32, Vec<(i32, f32)>> = HashMap::new();
+        let mut seed_roles: HashMap<i32, roles::Model> = HashMap::new();
+        let seeds = seeds(&activities, now);
+
+        for seed in &seeds {
+            // 角色可能已被删除
+            let Ok(role) = self.database.get_role(seed.role_id).await else {
+                continue;
+            };
+
+            neighbours.insert(
+                seed.role_id,
+                self.role_embeddings.similar(&role, NEIGHBOURS).await?,
+            );
+            seed_roles.insert(seed.role_id, role);
+        }
+
+        let ranked = rank(&seeds, &neighbours, &seen);
+        let reasons: HashMap<i32, i32> = ranked.iter().copied().collect();
+        let sources: HashMap<i32, Source> = seeds
+            .iter()
+            .map(|seed| (seed.role_id, seed.source))
+            .collect();
+        let role_ids: Vec<i32> = ranked.iter().map(|(role_id, _)| *role_id).collect();
+
+        let mut feed: Vec<Recommendation> = Vec::new();
+
+        for role in self
+            .database
+            .find_roles_ranked(&role_ids, &RoleFilter::default())
+            .await?
+        {
+            if role.user_id == user_id {
+                continue;
+            }
+
+            let seed_role = &seed_roles[&reasons[&role.id]];
+            let reason = match sources[&seed_role.id] {
+                Source::Conversation => Reason::Conversation {
+                    role_id: seed_role.id,
+                    role_name: seed_role.name.clone(),
+                },
+                Source::Debate => Reason::Debate {
+                    role_id: seed_role.id,
+                    role_name: seed_role.name.clone(),
+                },
+                Source::Favourite => Reason::Favourite {
+                    role_id: seed_role.id,
+                    role_name: seed_role.name.clone(),
+                },
+            };
+
+            seen.insert(role.id);
+            feed.push(Recommendation { role, reason });
+        }
+
+        // 不足时用热门角色补齐，新用户只有热门角色
+        if feed.len() < MAX_FEED {
+            let trending: Vec<i32> = self
+                .database
+                .trending_roles()
+                .await?
+                .into_iter()
+                .map(|(role_id, _)| role_id)
+                .filter(|role_id| !seen.contains(role_id))
+                .take(MAX_FEED * 2)
+                .collect();
+
+            for role in self
+                .database
+                .find_roles_ranked(&trending, &RoleFilter::default())
+                .await?
+            {
+                if role.user_id != user_id {
+                    feed.push(Recommendation {
+                        role,
+                        reason: Reason::Trending,
+                    });
+                }
+            }
+        }
+
+        feed.truncate(MAX_FEED);
+
+        Ok(feed)
+    }
+
+    async fn activities(&self, user_id: i32) -> Result<Vec<Activity>> {
+        let mut activities = Vec::new();
+
+        for conversation in self
+            .database
+            .list_recent_conversations(user_id, RECENT_ACTIVITIES)
+            .await?
+        {
+            let dialogs = self
+                .database
+                .get_dialog_count_by_id(conversation.id)
+                .await?;
+
+            activities.push(Activity {
+                role_id: conversation.role_id,
+                source: Source::Conversation,
+                dialogs,
+                timestamp: conversation.last_dialog_timestamp,
+            });
+        }
+
+        for debate in self
+            .database
+            .list_recent_debates(user_id, RECENT_ACTIVITIES)
+            .await?
+        {
+            for role_id in [debate.role1_id, debate.role2_id] {
+                activities.push(Activity {
+                    role_id,
+                    source: Source::Debate,
+                    dialogs: DEBATE_DIALOGS,
+                    timestamp: debate.last_dialog_timestamp,
+                });
+            }
+        }
+
+        for reaction in self
+            .database
+            .list_user_reactions(user_id, ReactionKind::Favourite)
+            .await?
+        {
+            activities.push(Activity {
+                role_id: reaction.role_id,
+                source: Source::Favourite,
+                dialogs: FAVOURITE_DIALOGS,
+                timestamp: reaction.created_at,
+            });
+        }
+
+        Ok(activities)
+    }
+}
+
+// 按对话轮数和时间衰减计算每个角色的权重，取权重最高的几个，来源取贡献最大的一项
+fn seeds(activities: &[Activity], now: i64) -> Vec<Seed> {
+    let mut weights: HashMap<i32, (f32, Source, f32)> = HashMap::new();
+
+    for activity in activities {
+        let age_days = (now - activity.timestamp).max(0) as f32 / DAY_MS as f32;
+        let decay = 0.5f32.powf(age_days / HALF_LIFE_DAYS);
+        let weight = (1.0 + activity.dialogs.max(0) as f32).ln() * decay;
+
+        let entry = weights
+            .entry(activity.role_id)
+            .or_insert((0.0, activity.source, 0.0));
+        entry.0 += weight;
+
+        if weight > entry.2 {
+            entry.1 = activity.source;
+            entry.2 = weight;
+        }
+    }
+
+    let mut seeds: Vec<Seed> = weights
+        .into_iter()
+        .filter(|(_, (weight, _, _))| *weight > 0.0)
+        .map(|(role_id, (weight, source, _))| Seed {
+            role_id,
+            source,
+            weight,
+        })
+        .collect();
+
+    seeds.sort_by(|a, b| {
+        b.weight
+            .total_cmp(&a.weight)
+            .then_with(|| a.role_id.cmp(&b.role_id))
+    });
+    seeds.truncate(MAX_SEEDS);
+
+    seeds
+}
+
+// 候选角色的得分为各依据角色的权重乘以相似度之和，返回(角色ID, 贡献最大的依据角色ID)
+fn rank(
+    seeds: &[Seed],
+    neighbours: &HashMap<i32, Vec<(i32, f32)>>,
+    exclude: &HashSet<i32>,
+) -> Vec<(i32, i32)> {
+    // 角色ID -> (得分, 依据角色ID, 最大贡献)
+    let mut scores: HashMap<i32, (f32, i32, f32)> = HashMap::new();
+
+    for seed in seeds {
+        for (role_id, similarity) in neighbours.get(&seed.role_id).into_iter().flatten() {
+            if exclude.contains(role_id) || *similarity <= 0.0 {
+                continue;
+            }
+
+            let contribution = seed.weight * similarity;
+            let entry = scores.entry(*role_id).or_insert((0.0, seed.role_id, 0.0));
+            entry.0 += contribution;
+
+            if contribution > entry.2 {
+                entry.1 = seed.role_id;
+                entry.2 = contribution;
+            }
+        }
+    }
+
+    let mut ranked: Vec<(i32, f32, i32)> = scores
+        .into_iter()
+        .map(|(role_id, (score, seed_id, _))| (role_id, score, seed_id))
+        .collect();
+    ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+    ranked
+        .into_iter()
+        .map(|(role_id, _, seed_id)| (role_id, seed_id))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seeds() {
+        let now = 100 * DAY_MS;
+        let activities = [
+            // 很久以前的长对话不如最近的对话
+            Activity {
+                role_id: 1,
+                source: Source::Conversation,
+                dialogs: 50,
+                timestamp: now - 90 * DAY_MS,
+            },
+            Activity {
+                role_id: 2,
+                source: Source::Conversation,
+                dialogs: 10,
+                timestamp: now - DAY_MS,
+            },
+            Activity {
+                role_id: 2,
+                source: Source::Debate,
+                dialogs: DEBATE_DIALOGS,
+                timestamp: now,
+            },
+            Activity {
+                role_id: 3,
+                source: Source::Debate,
+                dialogs: DEBATE_DIALOGS,
+                timestamp: now,
+            },
+            // 没有对话记录
+            Activity {
+                role_id: 4,
+                source: Source::Conversation,
+                dialogs: 0,
+                timestamp: now,
+            },
+        ];
+
+        let seeds = seeds(&activities, now);
+        let ids: Vec<i32> = seeds.iter().map(|seed| seed.role_id).collect();
+
+        assert_eq!(ids, vec![2, 3, 1]);
+        assert_eq!(seeds[0].source, Source::Conversation);
+        assert_eq!(seeds[1].source, Source::Debate);
+    }
+
+    #[test]
+    fn test_rank() {
+        let seeds = [
+            Seed {
+                role_id: 1,
+                source: Source::Conversation,
+                weight: 2.0,
+            },
+            Seed {
+                role_id: 2,
+                source: Source::Debate,
+                weight: 1.0,
+            },
+        ];
+        let neighbours = HashMap::from([
+            (1, vec![(10, 0.9), (11, 0.2), (2, 0.8)]),
+            (2, vec![(11, 0.9), (12, 0.5), (13, -0.1)]),
+        ]);
+
+        let ranked = rank(&seeds, &neighbours, &HashSet::from([1, 2]));
+
+        // 11: 2.0*0.2 + 1.0*0.9 = 1.3，理由取贡献更大的2
+        assert_eq!(ranked, vec![(10, 1), (11, 2), (12, 2)]);
+        assert!(rank(&[], &neighbours, &HashSet::new()).is_empty());
+    }
+
+    #[test]
+    fn test_explain() {
+        let reason = Reason::Conversation {
+            role_id: 1,
+            role_name: "爱因斯坦".to_string(),
+        };
+
+        assert_eq!(reason.explain(), "因为你和爱因斯坦聊过");
+
+        let reason = Reason::Favourite {
+            role_id: 1,
+            role_name: "爱因斯坦".to_string(),
+        };
+
+        assert_eq!(reason.explain(), "因为你收藏了爱因斯坦");
+        assert_eq!(Reason::Trending.explain(), "最近很多人在聊");
+    }
+}
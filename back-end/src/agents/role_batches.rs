@@ -0,0 +1,394 @@
+use super::{RoleBuilder, role_builder::RoleBuilt, role_jobs::user_room};
+use crate::database::{
+    Database,
+    models::{
+        role_batch_items::{ItemStatus, Model as Item},
+        role_batches::Model as Batch,
+        role_jobs::Status,
+        roles::{AgeGroup, Gender, MAX_TAG_CHARS, MAX_TAGS, Visibility, normalize_tags},
+    },
+};
+use anyhow::Result;
+use futures::StreamExt;
+use serde::Serialize;
+use socketioxide::SocketIo;
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex},
+};
+
+pub const PROGRESS_EVENT: &str = "role_batch_progress";
+
+// 同时生成的角色数，避免占满模型接口的并发额度
+const CONCURRENCY: usize = 3;
+
+#[derive(Clone)]
+pub struct RoleBatches {
+    database: Arc<Database>,
+    role_builder: Arc<RoleBuilder>,
+    socket: Option<SocketIo>,
+    running: Arc<Mutex<HashSet<i32>>>,
+}
+
+impl RoleBatches {
+    pub fn new(
+        database: Arc<Database>,
+        role_builder: Arc<RoleBuilder>,
+        socket: Option<SocketIo>,
+    ) -> Self {
+        Self {
+            database,
+            role_builder,
+            socket,
+            running: Arc::new(Mutex::new(HashSet::new())),
+        }
+    }
+
+    // 启动或恢复批量任务，已处理的条目不会重复生成
+    pub async fn start(&self, batch_id: i32) -> Result<()> {
+        if !self.running.lock().unwrap().insert(batch_id) {
+            return Ok(());
+        }
+
+        let mut batch = match self.database.get_role_batch(batch_id).await {
+            Ok(batch) => batch,
+            Err(e) => {
+                self.running.lock().unwrap().remove(&batch_id);
+                return Err(e);
+            }
+        };
+
+        batch.status = Status::Running;
+        batch.error = None;
+        self.database.save_role_batch(&batch).await?;
+
+        let this = self.clone();
+
+        tokio::spawn(async move {
+            if let Err(e) = this.run(&mut batch).await {
+                tracing::error!("Role batch {} failed: {}", batch_id, e);
+
+                batch.status = Status::Failed;
+                batch.error = Some(e.to_string());
+            }
+
+            if let Err(e) = this.finish(&batch).await {
+                tracing::error!("Failed to save role batch {}: {}", batch_id, e);
+            }
+
+            this.running.lock().unwrap().remove(&batch_id);
+        });
+
+        Ok(())
+    }
+
+    // 服务重启后继续执行未完成的批量任务
+    pub async fn resume_unfinished(&self) -> Result<()> {
+        for batch in self.database.list_unfinished_role_batches().await? {
+            self.start(batch.id).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn run(&self, batch: &mut Batch) -> Result<()> {
+        let items = self.database.list_role_batch_items(batch.id).await?;
+
+        // 与用户已有角色或名单中靠前的条目重名的视为重复
+        let mut names: HashSet<String> = self
+            .database
+            .get_user_roles(batch.user_id)
+            .await?
+            .iter()
+            .map(|role| normalize_name(&role.name))
+            .collect();
+        let mut pending = Vec::new();
+
+        for mut item in items {
+            if item.status != ItemStatus::Pending {
+                continue;
+            }
+
+            if !names.insert(normalize_name(&item.name)) {
+                item.status = ItemStatus::Duplicate;
+                item.error = Some("与已有角色重名".to_string());
+                self.save_item(batch, &item).await?;
+                continue;
+            }
+
+            pending.push(item);
+        }
+
+        let shared: &Batch = batch;
+        // 本批中已生成的人物，名字不同但指向同一条目的也视为重复
+        let claimed = &Mutex::new(HashSet::new());
+
+        futures::stream::iter(pending)
+            .for_each_concurrent(CONCURRENCY, |mut item| async move {
+                match self.create_role(shared.user_id, &item, claimed).await {
+                    Ok(Created::Role(role_id)) => {
+                        item.status = ItemStatus::Created;
+                        item.role_id = Some(role_id);
+                    }
+                    Ok(Created::Duplicate(reason)) => {
+                        item.status = ItemStatus::Duplicate;
+                        item.error = Some(reason);
+                    }
+                    Err(e) => {
+                        tracing::warn!("Failed to create role {}: {}", item.name, e);
+                        item.status = ItemStatus::Failed;
+                        item.error = Some(e.to_string());
+                    }
+                }
+
+                if let Err(e) = self.save_item(shared, &item).await {
+                    tracing::error!("Failed to save role batch item {}: {}", item.id, e);
+                }
+            })
+            .await;
+
+        batch.status = Status::Completed;
+
+        Ok(())
+    }
+
+    async fn create_role(
+        &self,
+        user_id: i32,
+        item: &Item,
+        claimed: &Mutex<HashSet<String>>,
+    ) -> Result<Created> {
+        let gender = item
+            .gender
+            .as_ref()
+            .map(Gender::to_string)
+            .unwrap_or_default();
+        let age_group = item
+            .age_group
+            .as_ref()
+            .map(AgeGroup::to_string)
+            .unwrap_or_default();
+
+        // 先确定人物身份，重复时不再调用模型生成
+        let (name, knowledge) = self.role_builder.research(&item.name, None).await?;
+        let identity = knowledge.identity(&name);
+
+        if let Some(role) = self
+            .database
+            .find_roles_by_identity(&identity)
+            .await?
+            .into_iter()
+            .find(|role| role.user_id == user_id)
+        {
+            return Ok(Created::Duplicate(format!(
+                "与已有角色“{}”是同一人物",
+                role.name
+            )));
+        }
+
+        if let Some(key) = identity.key()
+            && !claimed.lock().unwrap().insert(key)
+        {
+            return Ok(Created::Duplicate(
+                "与名单中的其他名字是同一人物".to_string(),
+            ));
+        }
+
+        let RoleBuilt {
+            description,
+            traits,
+            gender,
+            age_group,
+            voice_type,
+            avatar,
+            example_dialogues,
+            greetings,
+            identity,
+            category,
+            tags,
+            ..
+        } = match self
+            .role_builder
+            .build_from_knowledge(&name, knowledge, "", "", &gender, &age_group, None)
+            .await
+        {
+            Ok(built) => built,
+            // 生成失败时释放占用的身份，名单中同一人物的其他名字还可以生成
+            Err(e) => {
+                if let Some(key) = identity.key() {
+                    claimed.lock().unwrap().remove(&key);
+                }
+                return Err(e);
+            }
+        };
+
+        let image = avatar
+            .as_ref()
+            .map(|avatar| avatar.url.clone())
+            .unwrap_or_default();
+
+        let role_id = self
+            .database
+            .add_role(
+                user_id,
+                &item.name,
+                &description,
+                &traits,
+                &image,
+                item.gender.clone().unwrap_or(gender),
+                item.age_group.clone().unwrap_or(age_group),
+                &voice_type,
+                &example_dialogues,
+                &greetings,
+                Visibility::Public,
+            )
+            .await?;
+
+        if let Some(avatar) = avatar {
+            self.database
+                .set_role_image_attribution(
+                    role_id,
+                    Some(&serde_json::to_string(&avatar.attribution)?),
+                )
+                .await?;
+        }
+
+        self.database.set_role_identity(role_id, &identity).await?;
+        self.database.set_role_category(role_id, category).await?;
+
+        // 名单中的标签在前，其余用建议的标签补足
+        let tags: Vec<String> = normalize_tags(&[item.tags(), tags].concat())
+            .into_iter()
+            .filter(|tag| tag.chars().count() <= MAX_TAG_CHARS)
+            .take(MAX_TAGS)
+            .collect();
+        self.database.set_role_tags(role_id, &tags).await?;
+
+        Ok(Created::Role(role_id))
+    }
+
+    async fn save_item(&self, batch: &Batch, item: &Item) -> Result<()> {
+        self.database.save_role_batch_item(item).await?;
+
+        self.emit(
+            batch.user_id,
+            &ProgressData {
+                batch_id: batch.id,
+                item: Some(ItemData::from(item)),
+                batch: None,
+            },
+        )
+        .await;
+
+        Ok(())
+    }
+
+    async fn finish(&self, batch: &Batch) -> Result<()> {
+        self.database.save_role_batch(batch).await?;
+
+        let items = self.database.list_role_batch_items(batch.id).await?;
+
+        self.emit(
+            batch.user_id,
+            &ProgressData {
+                batch_id: batch.id,
+                item: None,
+                batch: Some(BatchData::new(batch, &items)),
+            },
+        )
+        .await;
+
+        Ok(())
+    }
+
+    async fn emit(&self, user_id: i32, event: &ProgressData) {
+        if let Some(socket) = &self.socket
+            && let Err(e) = socket
+                .to(user_room(user_id))
+                .emit(PROGRESS_EVENT, event)
+                .await
+        {
+            tracing::warn!("Failed to emit role batch progress: {}", e);
+        }
+    }
+}
+
+enum Created {
+    Role(i32),
+    // 生成后发现是已有的人物，附带原因
+    Duplicate(String),
+}
+
+fn normalize_name(name: &str) -> String {
+    name.trim().to_lowercase()
+}
+
+// 每处理完一个条目推送item，整个任务结束时推送batch
+#[derive(Serialize)]
+pub struct ProgressData {
+    pub batch_id: i32,
+    pub item: Option<ItemData>,
+    pub batch: Option<BatchData>,
+}
+
+#[derive(Serialize)]
+pub struct BatchData {
+    pub batch_id: i32,
+    pub status: Status,
+    pub total: i32,
+    pub created: usize,
+    pub failed: usize,
+    pub duplicate: usize,
+    pub pending: usize,
+    pub items: Vec<ItemData>,
+    pub error: Option<String>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+impl BatchData {
+    pub fn new(batch: &Batch, items: &[Item]) -> Self {
+        let count = |status: ItemStatus| items.iter().filter(|i| i.status == status).count();
+
+        Self {
+            batch_id: batch.id,
+            status: batch.status.clone(),
+            total: batch.total,
+            created: count(ItemStatus::Created),
+            failed: count(ItemStatus::Failed),
+            duplicate: count(ItemStatus::Duplicate),
+            pending: count(ItemStatus::Pending),
+            items: items.iter().map(ItemData::from).collect(),
+            error: batch.error.clone(),
+            created_at: batch.created_at,
+            updated_at: batch.updated_at,
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct ItemData {
+    pub position: i32,
+    pub name: String,
+    pub gender: Option<Gender>,
+    pub age_group: Option<AgeGroup>,
+    pub tags: Vec<String>,
+    pub status: ItemStatus,
+    pub role_id: Option<i32>,
+    pub error: Option<String>,
+}
+
+impl From<&Item> for ItemData {
+    fn from(item: &Item) -> Self {
+        Self {
+            position: item.position,
+            name: item.name.clone(),
+            gender: item.gender.clone(),
+            age_group: item.age_group.clone(),
+            tags: item.tags(),
+            status: item.status.clone(),
+            role_id: item.role_id,
+            error: item.error.clone(),
+        }
+    }
+}
@@ -1,11 +1,21 @@
-use super::{AI, remove_prefix_assistant};
+use super::{
+    AI, remove_prefix_assistant,
+    structured::{Schema, Structured},
+};
 use crate::{
     agents::Reciter,
-    database::models::roles::{AgeGroup, Gender},
+    database::models::roles::{
+        AgeGroup, Category, ExampleDialogue, Gender, MAX_EXAMPLE_DIALOGUES, MAX_TAG_CHARS,
+        RoleIdentity, normalize_tags,
+    },
+    knowledge::{Article, ImageAttribution, Knowledge},
+    storage::{ObjectInfo, StorageClient},
 };
 use anyhow::{Result, anyhow};
 use llm_chain::{parameters, prompt};
+use serde::Deserialize;
 use socketioxide::SocketIo;
+use std::{sync::Arc, time::Duration};
 
 pub struct RoleBuilt {
     pub description: String,
@@ -13,20 +23,122 @@ pub struct RoleBuilt {
     pub gender: Gender,
     pub age_group: AgeGroup,
     pub voice_type: String,
+    pub quotes: Vec<String>,
+    pub avatar: Option<Avatar>,
+    pub example_dialogues: Vec<ExampleDialogue>,
+    pub greetings: Vec<String>,
+    pub identity: RoleIdentity,
+    pub category: Option<Category>,
+    pub tags: Vec<String>,
+}
+
+// 原创角色的设定，各项均可为空
+#[derive(Default, Deserialize)]
+#[serde(default)]
+pub struct OriginalCharacter {
+    pub backstory: String,
+    pub world: String,
+    pub relationships: String,
+    pub goals: String,
+    pub secrets: String,
+    pub speech_quirks: String,
 }
 
+// 上传到对象存储的条目首图
+pub struct Avatar {
+    pub url: String,
+    pub attribution: ImageAttribution,
+}
+
+// 从维基获取的资料，查不到时各项为空
+#[derive(Default)]
+pub struct RoleKnowledge {
+    pub extract: String,
+    pub quotes: Vec<String>,
+    pub avatar: Option<Avatar>,
+    // 条目所在语言和页面ID
+    pub page: Option<(String, i64)>,
+}
+
+impl RoleKnowledge {
+    // 规范标题与查到的维基条目共同确定人物身份
+    pub fn identity(&self, canonical_title: &str) -> RoleIdentity {
+        let (wiki_lang, wiki_page_id) = self.page.clone().unzip();
+
+        RoleIdentity {
+            canonical_title: Some(canonical_title.to_string()),
+            wiki_lang,
+            wiki_page_id,
+        }
+    }
+}
+
+// 生成过程的中间结果，已有的步骤不再重复执行，后台任务据此断点续做
+#[derive(Default)]
+pub struct RoleDraft {
+    pub canonical_name: Option<String>,
+    pub knowledge: Option<RoleKnowledge>,
+    pub traits: Option<String>,
+    pub voice_type: Option<String>,
+}
+
+// 每完成一步后调用，用于保存中间结果和推送进度
+pub trait Checkpoint {
+    fn save(&mut self, step: &str, draft: &RoleDraft) -> impl Future<Output = Result<()>> + Send;
+}
+
+// 不需要保存中间结果
+impl Checkpoint for () {
+    async fn save(&mut self, _step: &str, _draft: &RoleDraft) -> Result<()> {
+        Ok(())
+    }
+}
+
+// 提供给模型的百科内容上限（字符）
+const KNOWLEDGE_MAX_CHARS: usize = 4000;
+
+// 下载条目首图的超时时间和大小上限
+const AVATAR_FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+const MAX_AVATAR_BYTES: usize = 10 * 1024 * 1024;
+
+// 生成示例对话的最少组数
+const MIN_EXAMPLE_DIALOGUES: usize = 3;
+// 生成开场白的条数上限
+const GENERATED_GREETINGS: usize = 3;
+// 建议标签的个数上限
+const SUGGESTED_TAGS: usize = 5;
+
 pub struct RoleBuilder {
     ai: AI,
     socket: Option<SocketIo>,
     reciter: Reciter,
+    knowledge: Knowledge,
+    storage_client: Arc<StorageClient>,
+    http_client: reqwest::Client,
 }
 
 impl RoleBuilder {
-    pub fn new(ai: AI, socket: Option<SocketIo>, reciter: Reciter) -> Self {
+    pub fn new(
+        ai: AI,
+        socket: Option<SocketIo>,
+        reciter: Reciter,
+        knowledge: Knowledge,
+        storage_client: Arc<StorageClient>,
+    ) -> Self {
+        // 维基媒体要求请求带有User-Agent
+        let http_client = reqwest::Client::builder()
+            .user_agent("role-play-ai/0.1")
+            .timeout(AVATAR_FETCH_TIMEOUT)
+            .build()
+            .unwrap();
+
         Self {
             ai,
             socket,
             reciter,
+            knowledge,
+            storage_client,
+            http_client,
         }
     }
 
@@ -39,60 +151,191 @@ impl RoleBuilder {
         age_group: &str,
         sid: Option<String>,
     ) -> Result<RoleBuilt> {
-        let name = self.to_precise_en_title(name, sid.clone()).await?;
-        let extract = self.wiki_extract(&name, sid.clone()).await?;
+        let (name, knowledge) = self.research(name, sid.clone()).await?;
 
-        let traits = if extract.is_empty() {
-            self.traits_from_prior(&name, description, traits, gender, age_group, sid.clone())
-                .await?
-        } else {
-            self.traits_from_extract(
-                &extract,
-                description,
-                traits,
-                gender,
-                age_group,
-                sid.clone(),
-            )
-            .await?
+        self.build_from_knowledge(
+            &name,
+            knowledge,
+            description,
+            traits,
+            gender,
+            age_group,
+            sid,
+        )
+        .await
+    }
+
+    // 确定规范标题并查询百科，调用方可以先据此判断人物是否重复，再完成生成
+    pub async fn research(
+        &self,
+        raw_name: &str,
+        sid: Option<String>,
+    ) -> Result<(String, RoleKnowledge)> {
+        let mut draft = RoleDraft::default();
+        self.research_draft(raw_name, &mut draft, sid, &mut ())
+            .await?;
+
+        Ok((
+            draft.canonical_name.unwrap_or_default(),
+            draft.knowledge.unwrap_or_default(),
+        ))
+    }
+
+    pub async fn build_from_knowledge(
+        &self,
+        name: &str,
+        knowledge: RoleKnowledge,
+        description: &str,
+        traits: &str,
+        gender: &str,
+        age_group: &str,
+        sid: Option<String>,
+    ) -> Result<RoleBuilt> {
+        let draft = RoleDraft {
+            canonical_name: Some(name.to_string()),
+            knowledge: Some(knowledge),
+            ..Default::default()
         };
 
-        let voice_type = self.select_voice_type(&traits, sid.clone()).await?;
+        self.build_draft(draft, description, traits, gender, age_group, sid, &mut ())
+            .await
+    }
 
-        let prompt = self.build_cn_rp_system_prompt(&name, &traits, sid).await?;
+    // 补齐规范标题和百科资料，草稿中已有的步骤跳过
+    pub async fn research_draft<C: Checkpoint>(
+        &self,
+        raw_name: &str,
+        draft: &mut RoleDraft,
+        sid: Option<String>,
+        checkpoint: &mut C,
+    ) -> Result<()> {
+        if draft.canonical_name.is_none() {
+            let name = self.to_precise_en_title(raw_name, sid.clone()).await?;
 
-        let description = extract_content_from_xml(&prompt, "description").unwrap_or_default();
+            draft.canonical_name = Some(name);
+            checkpoint.save("canonical_name", draft).await?;
+        }
 
-        let traits = extract_content_from_xml(&prompt, "traits").unwrap_or_default();
+        if draft.knowledge.is_none() {
+            let name = draft.canonical_name.clone().unwrap_or_default();
+            let knowledge = self.lookup_knowledge(&[raw_name, &name], sid).await?;
 
-        let gender = match extract_content_from_xml(&prompt, "gender")
-            .unwrap_or_default()
-            .as_str()
-        {
-            "male" => Gender::Male,
-            "female" => Gender::Female,
-            _ => return Err(anyhow::anyhow!("Unknown gender")),
-        };
+            draft.knowledge = Some(knowledge);
+            checkpoint.save("extract", draft).await?;
+        }
 
-        let age_group = match extract_content_from_xml(&prompt, "age-group")
-            .unwrap_or_default()
-            .as_str()
-        {
-            "mature" => AgeGroup::Mature,
-            "young" => AgeGroup::Young,
-            _ => return Err(anyhow::anyhow!("Unknown age group")),
-        };
+        Ok(())
+    }
 
-        Ok(RoleBuilt {
-            description,
-            traits,
-            gender,
-            age_group,
-            voice_type,
-        })
+    // 由百科资料完成生成，草稿中已有的角色特点和语音类型跳过
+    pub async fn build_draft<C: Checkpoint>(
+        &self,
+        mut draft: RoleDraft,
+        description: &str,
+        traits: &str,
+        gender: &str,
+        age_group: &str,
+        sid: Option<String>,
+        checkpoint: &mut C,
+    ) -> Result<RoleBuilt> {
+        let name = draft.canonical_name.clone().unwrap_or_default();
+
+        if draft.traits.is_none() {
+            let extract = draft
+                .knowledge
+                .as_ref()
+                .map(|knowledge| knowledge.extract.as_str())
+                .unwrap_or_default();
+
+            let traits = if extract.is_empty() {
+                self.traits_from_prior(&name, description, traits, gender, age_group, sid.clone())
+                    .await?
+            } else {
+                self.traits_from_extract(
+                    extract,
+                    description,
+                    traits,
+                    gender,
+                    age_group,
+                    sid.clone(),
+                )
+                .await?
+            };
+
+            draft.traits = Some(traits);
+            checkpoint.save("traits", &draft).await?;
+        }
+
+        let traits = draft.traits.clone().unwrap_or_default();
+
+        if draft.voice_type.is_none() {
+            let voice_type = self.select_voice_type(&traits, sid.clone()).await?;
+
+            draft.voice_type = Some(voice_type);
+            checkpoint.save("voice_type", &draft).await?;
+        }
+
+        let voice_type = draft.voice_type.unwrap_or_default();
+        let knowledge = draft.knowledge.unwrap_or_default();
+        let identity = knowledge.identity(&name);
+
+        let prompt = self
+            .build_cn_rp_system_prompt(&name, &traits, &knowledge.quotes, sid.clone())
+            .await?;
+
+        let mut built = parse_role_prompt(&prompt, voice_type, knowledge.quotes, knowledge.avatar)?;
+        built.identity = identity;
+        built.example_dialogues = self
+            .generate_example_dialogues(&name, &built.description, &built.traits, sid.clone())
+            .await?;
+        built.greetings = self
+            .generate_greetings(&name, &built.description, &built.traits, sid.clone())
+            .await?;
+        let (category, tags) = self
+            .suggest_tags(&name, &built.description, &built.traits, sid)
+            .await?;
+        built.category = Some(category);
+        built.tags = tags;
+
+        Ok(built)
+    }
+
+    // 原创角色不查询百科，直接由设定展开
+    pub async fn build_original(
+        &self,
+        name: &str,
+        original: &OriginalCharacter,
+        gender: &str,
+        age_group: &str,
+        sid: Option<String>,
+    ) -> Result<RoleBuilt> {
+        let traits = self
+            .traits_from_original(name, original, gender, age_group, sid.clone())
+            .await?;
+
+        let voice_type = self.select_voice_type(&traits, sid.clone()).await?;
+
+        let prompt = self
+            .build_cn_rp_system_prompt(name, &traits, &[], sid.clone())
+            .await?;
+
+        let mut built = parse_role_prompt(&prompt, voice_type, Vec::new(), None)?;
+        built.example_dialogues = self
+            .generate_example_dialogues(name, &built.description, &built.traits, sid.clone())
+            .await?;
+        built.greetings = self
+            .generate_greetings(name, &built.description, &built.traits, sid.clone())
+            .await?;
+        let (category, tags) = self
+            .suggest_tags(name, &built.description, &built.traits, sid)
+            .await?;
+        built.category = Some(category);
+        built.tags = tags;
+
+        Ok(built)
     }
 
-    async fn to_precise_en_title(&self, raw_name: &str, sid: Option<String>) -> Result<String> {
+    pub async fn to_precise_en_title(&self, raw_name: &str, sid: Option<String>) -> Result<String> {
         self.emit_status(sid.clone(), "正在规范角色名称...").await?;
 
         let sys = r#"
@@ -101,35 +344,26 @@ impl RoleBuilder {
 - 返回单行纯文本，不要解释、不要引号。把人名包裹在`<name></name>`标签中。
 - 如遇多义/同名，返回最常见且与“人物”对应的英文标题（不含括号 disambiguation）。
 - 若用户已是英文名，规范大小写与常见写法（如 'Jay Chou'）。
-- 你的回复必须是xml格式，且只能包含上述四个标签，且每个标签只能出现一次，且标签内不能嵌套其他标签。
+- 你的回复必须是xml格式，且只能包含上述一个标签，且标签内不能嵌套其他标签。
 "#;
 
-        let res = prompt!(
-            sys,
-            "用户给出的人名：{{name}}\n仅输出英文标题。\nAssistant:"
-        )
-        .run(&parameters!("name" => raw_name), &self.ai.executor)
-        .await?
-        .to_immediate()
-        .await?
-        .as_content()
-        .to_text()
-        .trim()
-        .to_string();
-
-        let res = remove_prefix_assistant(&res);
+        let res = self
+            .ai
+            .structured(
+                sys,
+                &format!("用户给出的人名：{}\n仅输出英文标题。", raw_name),
+                &Schema::new().field("name"),
+            )
+            .await?;
 
-        let name = extract_content_from_xml(res, "name").ok_or(anyhow::anyhow!(
-            "Failed to extract name from response: {}",
-            res
-        ))?;
+        let name = res.get("name").to_string();
 
         Ok(name)
     }
 
-    async fn traits_from_extract(
+    pub async fn traits_from_extract(
         &self,
-        extract_en: &str,
+        extract: &str,
         description: &str,
         traits: &str,
         gender: &str,
@@ -139,7 +373,7 @@ impl RoleBuilder {
         self.emit_status(sid, "正在生成角色特征...").await?;
 
         let sys = r#"
-你是角色设定提炼助手。输入是英文维基导言 extract。用户可能会提供一些描述、特征、性别、年龄段。
+你是角色设定提炼助手。输入是维基百科条目内容 extract，可能是中文、英文或日文。用户可能会提供一些描述、特征、性别、年龄段。
 角色的性别值为“male”或“female”，年龄段为“mature”或“young”。
 如果用户提供性别、年龄段，则必须使用这个设定，无论是否与常识冲突，因为这可能是用户的喜好。
 如果用户提供描述、特征，则结合extract一并考虑。
@@ -154,11 +388,11 @@ impl RoleBuilder {
 
         let res = prompt!(
             sys,
-            "英文 extract：\n{{extract}}\n描述：{{description}}\n特征：{{traits}}\n性别：{{gender}}\n年龄段：{{age_group}}\n——\n请中文列要点。\nAssistant:"
+            "extract：\n{{extract}}\n描述：{{description}}\n特征：{{traits}}\n性别：{{gender}}\n年龄段：{{age_group}}\n——\n请中文列要点。\nAssistant:"
         )
         .run(
-            &parameters!("extract" => extract_en, "description" => description, "traits" => traits, "gender" => gender, "age_group" => age_group),
-            &self.ai.executor,
+            &parameters!("extract" => extract, "description" => description, "traits" => traits, "gender" => gender, "age_group" => age_group),
+            self.ai.executor(),
         )
         .await?
         .to_immediate()
@@ -173,7 +407,7 @@ impl RoleBuilder {
         Ok(res.to_string())
     }
 
-    async fn traits_from_prior(
+    pub async fn traits_from_prior(
         &self,
         person_en: &str,
         description: &str,
@@ -203,7 +437,7 @@ impl RoleBuilder {
         )
         .run(
             &parameters!("name" => person_en, "description" => description, "traits" => traits, "gender" => gender, "age_group" => age_group),
-            &self.ai.executor,
+            self.ai.executor(),
         )
         .await?.to_immediate()
             .await?
@@ -217,7 +451,182 @@ impl RoleBuilder {
         Ok(res.to_string())
     }
 
-    async fn select_voice_type(&self, traits: &str, sid: Option<String>) -> Result<String> {
+    pub async fn traits_from_original(
+        &self,
+        name: &str,
+        original: &OriginalCharacter,
+        gender: &str,
+        age_group: &str,
+        sid: Option<String>,
+    ) -> Result<String> {
+        self.emit_status(sid, "正在生成角色特征...").await?;
+
+        let sys = r#"
+你是原创角色设定助手。用户创作了一个原创角色，给出了背景故事、世界观、人物关系、目标、秘密、说话习惯，其中一些可能为空。
+角色的性别值为“male”或“female”，年龄段为“mature”或“young”。
+如果用户提供性别、年龄段，则必须使用这个设定。
+请把这些设定整合成一个前后一致的人物，用中文输出适用于“角色扮演”的要点：
+- 人物的性格特质（3-6条），应能解释其背景故事和目标
+- 所处世界与身份（1-3条）
+- 重要的人际关系及对待方式（1-3条）
+- 说话风格/口吻（2-4条），必须体现用户给出的说话习惯
+- 典型话题或偏好（2-4条）
+- 秘密：角色不会主动透露，只在被追问或情境合适时有所暗示
+- 注意事项（禁用话题/需回避的领域）
+为空的设定可以合理补全，但不能与其他设定矛盾。这是虚构人物，不要引用任何真实人物的生平。
+输出为简洁条目列表。必须写明角色的性别和年龄段。
+"#;
+
+        let res = prompt!(
+            sys,
+            "角色名：{{name}}\n背景故事：{{backstory}}\n世界观：{{world}}\n人物关系：{{relationships}}\n目标：{{goals}}\n秘密：{{secrets}}\n说话习惯：{{speech_quirks}}\n性别：{{gender}}\n年龄段：{{age_group}}\n——\n请中文列要点。\nAssistant:"
+        )
+        .run(
+            &parameters!(
+                "name" => name,
+                "backstory" => original.backstory.as_str(),
+                "world" => original.world.as_str(),
+                "relationships" => original.relationships.as_str(),
+                "goals" => original.goals.as_str(),
+                "secrets" => original.secrets.as_str(),
+                "speech_quirks" => original.speech_quirks.as_str(),
+                "gender" => gender,
+                "age_group" => age_group
+            ),
+            self.ai.executor(),
+        )
+        .await?
+        .to_immediate()
+        .await?
+        .as_content()
+        .to_text()
+        .trim()
+        .to_string();
+
+        let res = remove_prefix_assistant(&res);
+
+        Ok(res.to_string())
+    }
+
+    // 生成几组示例对话，展示角色的口吻
+    pub async fn generate_example_dialogues(
+        &self,
+        name: &str,
+        description: &str,
+        traits: &str,
+        sid: Option<String>,
+    ) -> Result<Vec<ExampleDialogue>> {
+        self.emit_status(sid, "正在生成示例对话...").await?;
+
+        let sys = format!(
+            r#"
+你是角色扮演对话编剧。根据给出的角色描述和特点，写{}到{}组用户与角色之间的简短对话，展示角色的语气、口癖和互动方式。
+要求：
+- 每组对话为一行“用户：”加一行“角色：”，组与组之间空一行。
+- 角色的回复不超过60个汉字，话题各不相同。
+- 全部对话用`<dialogues></dialogues>`包裹，不要解释。
+"#,
+            MIN_EXAMPLE_DIALOGUES, MAX_EXAMPLE_DIALOGUES
+        );
+
+        let res = self
+            .ai
+            .structured(
+                &sys,
+                &format!(
+                    "角色：{}\n角色描述：\n{}\n角色特点：\n{}\n——\n请输出示例对话。",
+                    name, description, traits
+                ),
+                &Schema::new().checked("dialogues", check_example_dialogues),
+            )
+            .await?;
+
+        Ok(parse_example_dialogues(res.get("dialogues")))
+    }
+
+    // 生成几条不同的开场白，新对话由角色先开口
+    pub async fn generate_greetings(
+        &self,
+        name: &str,
+        description: &str,
+        traits: &str,
+        sid: Option<String>,
+    ) -> Result<Vec<String>> {
+        self.emit_status(sid, "正在生成开场白...").await?;
+
+        let sys = format!(
+            r#"
+你是角色扮演对话编剧。根据给出的角色描述和特点，写1到{}条角色主动向用户打招呼的开场白，作为新对话的第一句话。
+要求：
+- 符合角色的身份、语气和口癖，可以带出角色所处的情境，引导用户接话。
+- 每条不超过60个汉字，每条一行，不要编号。
+- 全部开场白用`<greetings></greetings>`包裹，不要解释。
+"#,
+            GENERATED_GREETINGS
+        );
+
+        let res = self
+            .ai
+            .structured(
+                &sys,
+                &format!(
+                    "角色：{}\n角色描述：\n{}\n角色特点：\n{}\n——\n请输出开场白。",
+                    name, description, traits
+                ),
+                &Schema::new().field("greetings"),
+            )
+            .await?;
+
+        Ok(parse_greetings(res.get("greetings")))
+    }
+
+    // 从固定分类中选一个，并建议几个标签
+    pub async fn suggest_tags(
+        &self,
+        name: &str,
+        description: &str,
+        traits: &str,
+        sid: Option<String>,
+    ) -> Result<(Category, Vec<String>)> {
+        self.emit_status(sid, "正在生成分类和标签...").await?;
+
+        let categories = Category::ALL
+            .iter()
+            .map(|category| format!("{}（{}）", category.as_str(), category.label()))
+            .collect::<Vec<_>>()
+            .join("、");
+
+        let sys = format!(
+            r#"
+你是角色分类助手。根据给出的角色描述和特点，给角色分类并打标签。
+要求：
+- 从以下分类中选择最合适的一个，输出括号前的英文键名，用`<category></category>`包裹：{}
+- 给出1到{}个中文标签，如“历史人物”“科学家”“动漫”“唐朝”，每个不超过{}个字，用顿号分隔，用`<tags></tags>`包裹。
+- 不要解释。
+"#,
+            categories, SUGGESTED_TAGS, MAX_TAG_CHARS
+        );
+
+        let res = self
+            .ai
+            .structured(
+                &sys,
+                &format!(
+                    "角色：{}\n角色描述：\n{}\n角色特点：\n{}\n——\n请输出分类和标签。",
+                    name, description, traits
+                ),
+                &Schema::new()
+                    .one_of("category", Category::ALL.iter().map(Category::as_str))
+                    .field("tags"),
+            )
+            .await?;
+
+        let category = Category::parse(res.get("category")).unwrap_or(Category::Other);
+
+        Ok((category, parse_tags(res.get("tags"))))
+    }
+
+    pub async fn select_voice_type(&self, traits: &str, sid: Option<String>) -> Result<String> {
         self.emit_status(sid, "正在选择角色声音类型...").await?;
 
         let voice_map = self.reciter.fetch_voice_map().await?;
@@ -228,36 +637,60 @@ impl RoleBuilder {
 你是角色声音选择助手。根据给出的角色特征，选择合适的声音类型：
 可用声音json：{}
 要求：
-- 仅输出选取的声音的键名（字符串），不要解释。
+- 仅输出选取的声音的键名，用`<voice-type></voice-type>`包裹，不要解释。
 - 请通过角色的性格、年龄、说话风格等综合判断。
 "#,
             voice_map_json
         );
 
-        let res = prompt!(
-            &sys,
-            "角色特征：\n{{traits}}\n——\n请输出声音类型键名。\nAssistant:"
-        )
-        .run(&parameters!("traits" => traits), &self.ai.executor)
-        .await?
-        .to_immediate()
-        .await?
-        .as_content()
-        .to_text()
-        .trim()
-        .to_string();
+        let res = self
+            .ai
+            .structured(
+                &sys,
+                &format!("角色特征：\n{}\n——\n请输出声音类型键名。", traits),
+                &Schema::new().one_of("voice-type", voice_map.keys().cloned()),
+            )
+            .await?;
 
-        let res = remove_prefix_assistant(&res);
+        Ok(res.get("voice-type").to_string())
+    }
 
-        Ok(res.to_string())
+    // 用于导入的角色卡片，卡片中没有性别和年龄段
+    pub async fn infer_gender_and_age_group(
+        &self,
+        description: &str,
+        traits: &str,
+    ) -> Result<(Gender, AgeGroup)> {
+        let sys = r#"
+你是角色分类助手。根据给出的角色描述和特征，判断角色的性别和年龄段。
+要求：
+- 给出角色的性别，值为“male”和“female”。用`<gender></gender>`包裹。
+- 给出角色的年龄段，值为“mature”和“young”。用`<age-group></age-group>`包裹。
+- 你的回复必须是xml格式，且只能包含上述两个标签，且每个标签只能出现一次，且标签内不能嵌套其他标签。
+"#;
+
+        let res = self
+            .ai
+            .structured(
+                sys,
+                &format!(
+                    "角色描述：\n{}\n角色特征：\n{}\n——\n请输出性别和年龄段。",
+                    description, traits
+                ),
+                &gender_and_age_group_schema(),
+            )
+            .await?;
+
+        parse_gender_and_age_group(&res)
     }
 
-    async fn build_cn_rp_system_prompt(
+    pub async fn build_cn_rp_system_prompt(
         &self,
         person_en: &str,
         traits_cn: &str,
+        quotes: &[String],
         sid: Option<String>,
-    ) -> Result<String> {
+    ) -> Result<Structured> {
         self.emit_status(sid, "正在生成角色描述和特点...").await?;
 
         let sys = r#"
@@ -267,62 +700,123 @@ impl RoleBuilder {
 - 给出角色设定的年龄段，值为“mature”和“young”。用`<age-group></age-group>`包裹。
 - 用中文概述人物设定，附上英文对照，需要包含人物性别和年纪大小。用`<description></description>`包裹。
 - 列出“说话风格/互动规则/可谈话题/回避事项”，并给出 3 条示例口癖或措辞模板（中文）。用`<traits></traits>`包裹。
+- 如果提供了人物的真实语录，示例口癖应模仿语录的用词和语气，可以直接引用（非中文的语录请翻译）。
 - 控制在 250~400 字，适合作为 system。
 - 你的回复必须是xml格式，且只能包含上述四个标签，且每个标签只能出现一次，且标签内不能嵌套其他标签。
 "#;
 
-        let res = prompt!(
-            sys,
-            "人物（英文名）：{{name}}\n特征要点：\n{{traits}}\n——\n请生成中文\nAssistant:"
-        )
-        .run(
-            &parameters!("name" => person_en, "traits" => traits_cn),
-            &self.ai.executor,
-        )
-        .await?
-        .to_immediate()
-        .await?
-        .as_content()
-        .to_text()
-        .trim()
-        .to_string();
-
-        let res = remove_prefix_assistant(&res);
+        let quotes = if quotes.is_empty() {
+            "无".to_string()
+        } else {
+            quotes
+                .iter()
+                .map(|quote| format!("- {}", quote))
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
 
-        Ok(res.to_string())
+        self.ai
+            .structured(
+                sys,
+                &format!(
+                    "人物（英文名）：{}\n特征要点：\n{}\n真实语录：\n{}\n——\n请生成中文",
+                    person_en, traits_cn, quotes
+                ),
+                &gender_and_age_group_schema()
+                    .field("description")
+                    .field("traits"),
+            )
+            .await
     }
 
-    pub async fn wiki_extract(&self, title: &str, sid: Option<String>) -> Result<String> {
-        self.emit_status(sid, "正在尝试从维基百科获取角色简介...")
+    // 按配置的语言顺序查询百科，返回导言和正文章节，以及语录和头像
+    pub async fn lookup_knowledge(
+        &self,
+        titles: &[&str],
+        sid: Option<String>,
+    ) -> Result<RoleKnowledge> {
+        self.emit_status(sid.clone(), "正在尝试从维基百科获取角色简介...")
             .await?;
 
-        let api = mediawiki::api::Api::new("https://en.wikipedia.org/w/api.php").await?;
+        let Some(article) = self.knowledge.lookup(titles).await? else {
+            return Ok(RoleKnowledge::default());
+        };
 
-        let params = api.params_into(&[
-            ("action", "query"),
-            ("prop", "extracts"),
-            ("exintro", ""),
-            ("explaintext", ""),
-            ("titles", title),
-            ("format", "json"),
-        ]);
+        tracing::info!("Found {} wiki article: {}", article.lang, article.title);
+
+        self.emit_status(sid, "正在获取角色语录和头像...").await?;
+
+        // 语录和头像只是锦上添花，获取失败不影响生成角色
+        let quotes = self.knowledge.quotes(&article).await.unwrap_or_else(|e| {
+            tracing::warn!("Failed to fetch quotes for {}: {}", article.title, e);
+            Vec::new()
+        });
+
+        let avatar = self.fetch_avatar(&article).await.unwrap_or_else(|e| {
+            tracing::warn!("Failed to fetch avatar for {}: {}", article.title, e);
+            None
+        });
+
+        Ok(RoleKnowledge {
+            extract: article.to_context(KNOWLEDGE_MAX_CHARS),
+            quotes,
+            avatar,
+            page: Some((article.lang, article.page_id)),
+        })
+    }
+
+    // 下载条目首图并转存到对象存储
+    async fn fetch_avatar(&self, article: &Article) -> Result<Option<Avatar>> {
+        let Some(image) = self.knowledge.page_image(article).await? else {
+            return Ok(None);
+        };
+
+        let mut res = self
+            .http_client
+            .get(&image.url)
+            .send()
+            .await?
+            .error_for_status()?;
+
+        let is_image = res
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value.starts_with("image/"));
+
+        if !is_image {
+            return Err(anyhow!("Not an image: {}", image.url));
+        }
+
+        if res
+            .content_length()
+            .is_some_and(|length| length > MAX_AVATAR_BYTES as u64)
+        {
+            return Err(anyhow!("Image too large: {}", image.url));
+        }
 
-        let res = api.get_query_api_json_all(&params).await?;
+        let mut data = Vec::new();
 
-        let pages = &res["query"]["pages"];
+        while let Some(chunk) = res.chunk().await? {
+            if data.len() + chunk.len() > MAX_AVATAR_BYTES {
+                return Err(anyhow!("Image too large: {}", image.url));
+            }
 
-        // page编号为-1则没有找到，没有extract
-        if pages.as_object().unwrap().contains_key("-1") {
-            return Ok(String::new());
+            data.extend_from_slice(&chunk);
         }
 
-        // 取第一个page
-        let page = pages.as_object().unwrap().values().next().unwrap();
-        let extract = page["extract"].as_str().unwrap_or("").to_string();
+        let suffix = image.url.rsplit('.').next().unwrap_or("jpg");
+        let name = format!("avatar-{}.{}", uuid::Uuid::new_v4(), suffix);
+
+        let ObjectInfo { key, .. } = self.storage_client.upload_object(&name, data).await?;
 
-        Ok(extract)
+        Ok(Some(Avatar {
+            url: self.storage_client.get_object_url(&key),
+            attribution: image.attribution,
+        }))
     }
 
+    #[allow(clippy::collapsible_if)]
     async fn emit_status(&self, sid: Option<String>, status: &str) -> Result<()> {
         if let Some(socket) = &self.socket {
             if let Some(sid) = sid {
@@ -338,11 +832,133 @@ impl RoleBuilder {
     }
 }
 
-pub fn extract_content_from_xml(xml: &str, tag: &str) -> Option<String> {
-    let xml = format!("<root>{}</root>", xml);
-    let doc = roxmltree::Document::parse(&xml).ok()?;
-    let node = doc.descendants().find(|n| n.has_tag_name(tag))?;
-    Some(node.text()?.trim().to_string())
+fn gender_and_age_group_schema() -> Schema {
+    Schema::new()
+        .one_of("gender", ["male", "female"])
+        .one_of("age-group", ["mature", "young"])
+}
+
+// 解析build_cn_rp_system_prompt的结果
+pub fn parse_role_prompt(
+    prompt: &Structured,
+    voice_type: String,
+    quotes: Vec<String>,
+    avatar: Option<Avatar>,
+) -> Result<RoleBuilt> {
+    let (gender, age_group) = parse_gender_and_age_group(prompt)?;
+
+    Ok(RoleBuilt {
+        description: prompt.get("description").to_string(),
+        traits: prompt.get("traits").to_string(),
+        gender,
+        age_group,
+        voice_type,
+        quotes,
+        avatar,
+        example_dialogues: Vec::new(),
+        greetings: Vec::new(),
+        identity: RoleIdentity::default(),
+        category: None,
+        tags: Vec::new(),
+    })
+}
+
+// 标签以顿号、逗号或换行分隔，过长的标签丢弃
+pub fn parse_tags(text: &str) -> Vec<String> {
+    let tags: Vec<&str> = text
+        .split(['、', '，', ',', '\n'])
+        .map(|tag| tag.trim().trim_matches(['“', '”', '"']))
+        .filter(|tag| tag.chars().count() <= MAX_TAG_CHARS)
+        .collect();
+
+    normalize_tags(&tags)
+        .into_iter()
+        .take(SUGGESTED_TAGS)
+        .collect()
+}
+
+// 每行一条开场白，去掉模型可能加上的列表符号
+pub fn parse_greetings(text: &str) -> Vec<String> {
+    text.lines()
+        .map(strip_list_marker)
+        .filter(|line| !line.is_empty())
+        .take(GENERATED_GREETINGS)
+        .map(str::to_string)
+        .collect()
+}
+
+// 只去掉“1.”“2、”“3)”“-”“*”这样的列表符号，以数字开头的正文保持不变
+fn strip_list_marker(line: &str) -> &str {
+    let line = line.trim();
+    let number = line.trim_start_matches(|c: char| c.is_ascii_digit());
+
+    let rest = if number.len() < line.len() {
+        number.strip_prefix(['.', '、', ')', '）'])
+    } else {
+        line.strip_prefix(['-', '*'])
+    };
+
+    rest.unwrap_or(line).trim()
+}
+
+// 每组对话为一行“用户：”加一行“角色：”，不成对的行忽略
+pub fn parse_example_dialogues(text: &str) -> Vec<ExampleDialogue> {
+    let mut dialogues = Vec::new();
+    let mut user = None;
+
+    for line in text.lines().map(str::trim) {
+        if let Some(content) = strip_speaker(line, "用户") {
+            user = Some(content);
+        } else if let Some(content) = strip_speaker(line, "角色")
+            && let Some(user) = user.take()
+        {
+            dialogues.push(ExampleDialogue {
+                user,
+                role: content,
+            });
+        }
+    }
+
+    dialogues
+}
+
+fn strip_speaker(line: &str, speaker: &str) -> Option<String> {
+    let content = line
+        .strip_prefix(speaker)?
+        .trim_start()
+        .strip_prefix(['：', ':'])?
+        .trim();
+
+    (!content.is_empty()).then(|| content.to_string())
+}
+
+fn check_example_dialogues(text: &str) -> std::result::Result<(), String> {
+    let count = parse_example_dialogues(text).len();
+
+    if (MIN_EXAMPLE_DIALOGUES..=MAX_EXAMPLE_DIALOGUES).contains(&count) {
+        Ok(())
+    } else {
+        Err(format!(
+            "需要{}到{}组对话，实际为{}组，每组为一行“用户：”和一行“角色：”",
+            MIN_EXAMPLE_DIALOGUES, MAX_EXAMPLE_DIALOGUES, count
+        ))
+    }
+}
+
+fn parse_gender_and_age_group(res: &Structured) -> Result<(Gender, AgeGroup)> {
+    let gender = match res.get("gender") {
+        "male" => Gender::Male,
+        "female" => Gender::Female,
+        _ => return Err(anyhow::anyhow!("Unknown gender")),
+    };
+
+    let age_group = match res.get("age-group") {
+        "mature" => AgeGroup::Mature,
+        "young" => AgeGroup::Young,
+        _ => return Err(anyhow::anyhow!("Unknown age group")),
+    };
+
+    Ok((gender, age_group))
 }
 
 #[cfg(test)]
@@ -350,7 +966,53 @@ mod tests {
     use std::sync::Arc;
 
     use super::*;
-    use crate::{env::tests::get_env, storage::StorageClient};
+    use crate::{env::tests::get_env, knowledge::Source, storage::StorageClient};
+
+    #[test]
+    fn test_parse_example_dialogues() {
+        let dialogues = parse_example_dialogues(
+            "用户：你好\n角色：幸会。\n\n用户: 今天天气如何？\n旁白：窗外下雨\n角色 : 雨声正好。\n用户：再见",
+        );
+
+        assert_eq!(
+            dialogues,
+            vec![
+                ExampleDialogue {
+                    user: "你好".to_string(),
+                    role: "幸会。".to_string(),
+                },
+                ExampleDialogue {
+                    user: "今天天气如何？".to_string(),
+                    role: "雨声正好。".to_string(),
+                },
+            ]
+        );
+        assert!(check_example_dialogues("用户：你好\n角色：幸会。").is_err());
+    }
+
+    #[test]
+    fn test_parse_greetings() {
+        assert_eq!(
+            parse_greetings("1. 幸会，且饮一杯。\n\n- 今夜月色正好。\n2、来者何人？\n还有一条"),
+            vec!["幸会，且饮一杯。", "今夜月色正好。", "来者何人？"]
+        );
+        assert_eq!(
+            parse_greetings("3天没见了，想我没有？\n1900年的巴黎，比现在热闹。\n3) 坐吧。"),
+            vec![
+                "3天没见了，想我没有？",
+                "1900年的巴黎，比现在热闹。",
+                "坐吧。"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_tags() {
+        assert_eq!(
+            parse_tags("“历史人物”、诗人，唐朝\n#诗人, 这是一个特别特别特别长的不合格标签"),
+            vec!["历史人物", "诗人", "唐朝"]
+        );
+    }
 
     #[tokio::test]
     async fn test_role_builder() {
@@ -364,15 +1026,17 @@ mod tests {
             &env.qiniu_access_key,
             &env.qiniu_secret_key,
         ));
-        let reciter = Reciter::new(storage_client, &env.qiniu_ai_api_key);
+        let reciter = Reciter::new(storage_client.clone(), &env.qiniu_ai_api_key);
 
-        let role_builder = RoleBuilder::new(ai, None, reciter);
+        let role_builder =
+            RoleBuilder::new(ai, None, reciter, Knowledge::default(), storage_client);
         let RoleBuilt {
             description,
             traits,
             gender,
             age_group,
             voice_type,
+            ..
         } = role_builder
             .build("爱因斯坦", "", "", "", "", None)
             .await
@@ -384,23 +1048,41 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_wiki_extract() {
-        let env = get_env();
-        let ai = AI::new(
-            &env.qiniu_ai_api_key,
-            &env.qiniu_llm_model,
-            &env.qiniu_llm_thinking_model,
+    async fn test_lookup_knowledge() {
+        // 使用本地保存的维基响应，不访问网络
+        let storage_client = Arc::new(StorageClient::new("", ""));
+        let knowledge = Knowledge::new(vec![
+            Source::fixture("zh", include_str!("../knowledge/fixtures/zh.json")),
+            Source::fixture("en", include_str!("../knowledge/fixtures/en.json")),
+        ]);
+        let role_builder = RoleBuilder::new(
+            AI::new("", "", ""),
+            None,
+            Reciter::new(storage_client.clone(), ""),
+            knowledge,
+            storage_client,
         );
-        let storage_client = Arc::new(StorageClient::new(
-            &env.qiniu_access_key,
-            &env.qiniu_secret_key,
-        ));
-        let reciter = Reciter::new(storage_client, &env.qiniu_ai_api_key);
 
-        let role_builder = RoleBuilder::new(ai, None, reciter);
+        // 中文名查不到，由英文条目的语言链接找到中文条目
+        let knowledge = role_builder
+            .lookup_knowledge(&["鸣人", "Naruto Uzumaki"], None)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            knowledge.extract,
+            "漩涡鸣人是日本漫画《火影忍者》的主人公。"
+        );
+        assert_eq!(knowledge.page, Some(("zh".to_string(), 126087)));
+        assert!(knowledge.quotes.is_empty());
+        assert!(knowledge.avatar.is_none());
+
+        let knowledge = role_builder
+            .lookup_knowledge(&["Nobody"], None)
+            .await
+            .unwrap();
 
-        let title = "Harry Potter";
-        let extract = role_builder.wiki_extract(title, None).await.unwrap();
-        println!("Extract for {}:\n{}", title, extract);
+        assert!(knowledge.extract.is_empty());
+        assert!(knowledge.page.is_none());
     }
 }
@@ -0,0 +1,174 @@
+use super::Embedder;
+use crate::{
+    database::{Database, RoleEvent, models::roles},
+    search::VectorIndex,
+};
+use anyhow::Result;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+use tokio::sync::broadcast::error::RecvError;
+
+// 维护角色向量：计算新建和修改过的角色，删除已删除角色的向量；没有配置向量模型时不可用
+pub struct RoleEmbeddings {
+    database: Arc<Database>,
+    embedder: Option<Embedder>,
+    index: VectorIndex,
+}
+
+impl RoleEmbeddings {
+    pub fn new(database: Arc<Database>, embedder: Option<Embedder>) -> Self {
+        Self {
+            database,
+            embedder,
+            index: VectorIndex::new(),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.embedder.is_some()
+    }
+
+    fn embedder(&self) -> Result<&Embedder> {
+        self.embedder
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("未配置向量模型，语义搜索不可用"))
+    }
+
+    // 启动时全量同步一次，之后随角色的创建、修改和删除同步
+    pub fn start(self: &Arc<Self>) {
+        if !self.is_enabled() {
+            return;
+        }
+
+        let this = self.clone();
+        let mut events = self.database.subscribe_role_events();
+
+        tokio::spawn(async move {
+            if let Err(e) = this.sync().await {
+                tracing::error!("Failed to sync role embeddings: {}", e);
+            }
+
+            loop {
+                let result = match events.recv().await {
+                    Ok(RoleEvent::Saved(role_id)) => this.sync_role(role_id).await,
+                    Ok(RoleEvent::Deleted(role_id)) => {
+                        this.index.remove(role_id);
+                        Ok(())
+                    }
+                    // 事件积压丢失时全量同步一次
+                    Err(RecvError::Lagged(_)) => this.sync().await,
+                    Err(RecvError::Closed) => break,
+                };
+
+                if let Err(e) = result {
+                    tracing::error!("Failed to sync role embeddings: {}", e);
+                }
+            }
+        });
+    }
+
+    pub async fn sync(&self) -> Result<()> {
+        let embedder = self.embedder()?;
+        let roles = self.database.list_all_roles().await?;
+        let stored: HashMap<i32, _> = self
+            .database
+            .list_role_embeddings()
+            .await?
+            .into_iter()
+            .map(|embedding| (embedding.role_id, embedding))
+            .collect();
+
+        let mut stale = Vec::new();
+
+        for role in &roles {
+            match stored.get(&role.id) {
+                Some(embedding)
+                    if embedding.model == embedder.model() && embedding.version == role.version =>
+                {
+                    if self.index.get(role.id).is_none() {
+                        self.index.insert(role.id, embedding.vector());
+                    }
+                }
+                _ => stale.push(role),
+            }
+        }
+
+        if !stale.is_empty() {
+            let texts: Vec<String> = stale.iter().map(|role| embedding_text(role)).collect();
+            let vectors = embedder.embed(&texts).await?;
+
+            for (role, vector) in stale.into_iter().zip(vectors) {
+                self.database
+                    .save_role_embedding(role, embedder.model(), &vector)
+                    .await?;
+                self.index.insert(role.id, vector);
+            }
+        }
+
+        let role_ids: HashSet<i32> = roles.iter().map(|role| role.id).collect();
+        self.index.retain(&role_ids);
+
+        for role_id in stored.keys().filter(|role_id| !role_ids.contains(role_id)) {
+            self.database.delete_role_embedding(*role_id).await?;
+        }
+
+        Ok(())
+    }
+
+    // 角色创建或修改后计算向量，只改了标签等、版本没变时跳过
+    async fn sync_role(&self, role_id: i32) -> Result<()> {
+        let embedder = self.embedder()?;
+        let role = self.database.get_role(role_id).await?;
+
+        if let Some(embedding) = self.database.get_role_embedding(role_id).await?
+            && embedding.model == embedder.model()
+            && embedding.version == role.version
+            && self.index.get(role_id).is_some()
+        {
+            return Ok(());
+        }
+
+        self.embed_role(&role).await?;
+
+        Ok(())
+    }
+
+    // 语义搜索，相似度从高到低返回最多limit个角色
+    pub async fn search(&self, query: &str, limit: usize) -> Result<Vec<(i32, f32)>> {
+        let vector = self.embedder()?.embed_one(query).await?;
+
+        Ok(self.index.nearest(&vector, limit, None))
+    }
+
+    // 与角色最相似的其他角色，角色还没有向量时立即计算；没有配置向量模型时为空，推荐只用热门角色补齐
+    pub async fn similar(&self, role: &roles::Model, limit: usize) -> Result<Vec<(i32, f32)>> {
+        if !self.is_enabled() {
+            return Ok(Vec::new());
+        }
+
+        let vector = match self.index.get(role.id) {
+            Some(vector) => vector,
+            None => self.embed_role(role).await?,
+        };
+
+        Ok(self.index.nearest(&vector, limit, Some(role.id)))
+    }
+
+    async fn embed_role(&self, role: &roles::Model) -> Result<Vec<f32>> {
+        let embedder = self.embedder()?;
+        let vector = embedder.embed_one(&embedding_text(role)).await?;
+
+        self.database
+            .save_role_embedding(role, embedder.model(), &vector)
+            .await?;
+        self.index.insert(role.id, vector.clone());
+
+        Ok(vector)
+    }
+}
+
+fn embedding_text(role: &roles::Model) -> String {
+    format!("{}\n{}", role.description, role.traits)
+}
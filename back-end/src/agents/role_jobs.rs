@@ -0,0 +1,425 @@
+use super::{
+    RoleBuilder,
+    role_builder::{Avatar, Checkpoint, RoleDraft, RoleKnowledge},
+};
+use crate::{
+    database::{
+        Database,
+        models::{
+            role_jobs::{Model as Job, Status},
+            roles::{
+                AgeGroup, Category, ExampleDialogue, Gender, RoleIdentity, greetings_from_json,
+            },
+        },
+    },
+    knowledge::ImageAttribution,
+};
+use anyhow::{Result, anyhow};
+use serde::Serialize;
+use socketioxide::SocketIo;
+use std::{
+    collections::{HashMap, hash_map::Entry},
+    sync::{Arc, Mutex},
+};
+use tokio::task::AbortHandle;
+
+pub const PROGRESS_EVENT: &str = "role_job_progress";
+
+// 用户的socket房间，用户登录后通过join_user事件加入
+pub fn user_room(user_id: i32) -> String {
+    format!("user_{}", user_id)
+}
+
+#[derive(Clone)]
+pub struct RoleJobs {
+    database: Arc<Database>,
+    role_builder: Arc<RoleBuilder>,
+    socket: Option<SocketIo>,
+    // 正在启动的任务句柄为空
+    running: Arc<Mutex<HashMap<i32, Option<AbortHandle>>>>,
+}
+
+impl RoleJobs {
+    pub fn new(
+        database: Arc<Database>,
+        role_builder: Arc<RoleBuilder>,
+        socket: Option<SocketIo>,
+    ) -> Self {
+        Self {
+            database,
+            role_builder,
+            socket,
+            running: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    // 启动或恢复任务，已完成的步骤不会重复执行
+    pub async fn start(&self, job_id: i32) -> Result<()> {
+        // 先占位，避免并发启动同一任务；任务开始运行后再填入句柄
+        match self.running.lock().unwrap().entry(job_id) {
+            Entry::Occupied(_) => return Ok(()),
+            Entry::Vacant(entry) => {
+                entry.insert(None);
+            }
+        }
+
+        let job = match self.mark_running(job_id).await {
+            Ok(job) => job,
+            Err(e) => {
+                self.running.lock().unwrap().remove(&job_id);
+                return Err(e);
+            }
+        };
+
+        let this = self.clone();
+        let spawned = match self.running.lock().unwrap().get_mut(&job_id) {
+            Some(slot) => {
+                let handle = tokio::spawn(async move {
+                    if let Err(e) = this.run(job).await {
+                        tracing::error!("Role job {} failed: {}", job_id, e);
+
+                        if let Err(e) = this
+                            .finish(
+                                job_id,
+                                Status::Failed,
+                                Some(e.to_string()),
+                                &[Status::Running],
+                            )
+                            .await
+                        {
+                            tracing::error!("Failed to save role job {}: {}", job_id, e);
+                        }
+                    }
+
+                    this.running.lock().unwrap().remove(&job_id);
+                });
+
+                *slot = Some(handle.abort_handle());
+                true
+            }
+            None => false,
+        };
+
+        // 启动过程中被取消，覆盖刚写入的运行状态
+        if !spawned {
+            self.finish(job_id, Status::Cancelled, None, &[Status::Running])
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn mark_running(&self, job_id: i32) -> Result<Job> {
+        let mut job = self.database.get_role_job(job_id).await?;
+
+        job.status = Status::Running;
+        job.error = None;
+
+        // 已完成的任务不再启动
+        if !self
+            .checkpoint(
+                &job,
+                "started",
+                &[
+                    Status::Pending,
+                    Status::Running,
+                    Status::Failed,
+                    Status::Cancelled,
+                ],
+            )
+            .await?
+        {
+            return Err(anyhow!("Role job already completed"));
+        }
+
+        Ok(job)
+    }
+
+    pub async fn cancel(&self, job_id: i32) -> Result<()> {
+        if let Some(Some(handle)) = self.running.lock().unwrap().remove(&job_id) {
+            handle.abort();
+        }
+
+        self.finish(
+            job_id,
+            Status::Cancelled,
+            None,
+            &[Status::Pending, Status::Running],
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    // 服务重启后继续执行未完成的任务，单个任务失败不影响其他任务
+    pub async fn resume_unfinished(&self) -> Result<()> {
+        for job in self.database.list_unfinished_role_jobs().await? {
+            if let Err(e) = self.start(job.id).await {
+                tracing::error!("Failed to resume role job {}: {}", job.id, e);
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn run(&self, job: Job) -> Result<()> {
+        let gender = job
+            .gender
+            .as_ref()
+            .map(Gender::to_string)
+            .unwrap_or_default();
+        let age_group = job
+            .age_group
+            .as_ref()
+            .map(AgeGroup::to_string)
+            .unwrap_or_default();
+        let name = job.name.clone();
+        let description = job.description.clone();
+        let traits = job.traits.clone();
+
+        let mut draft = draft(&job);
+        let mut checkpoint = JobCheckpoint { jobs: self, job };
+
+        self.role_builder
+            .research_draft(&name, &mut draft, None, &mut checkpoint)
+            .await?;
+        let built = self
+            .role_builder
+            .build_draft(
+                draft,
+                &description,
+                &traits,
+                &gender,
+                &age_group,
+                None,
+                &mut checkpoint,
+            )
+            .await?;
+
+        // 语录和头像已经保存在任务中
+        let mut job = checkpoint.job;
+        job.result_example_dialogues = Some(ExampleDialogue::to_json(&built.example_dialogues));
+        job.result_category = built.category;
+        job.result_tags = Some(built.tags.join("\n"));
+        job.result_greetings = Some(serde_json::to_string(&built.greetings)?);
+        job.result_description = Some(built.description);
+        job.result_traits = Some(built.traits);
+        job.result_gender = Some(built.gender);
+        job.result_age_group = Some(built.age_group);
+        job.status = Status::Completed;
+        self.save_running(&job, "completed").await
+    }
+
+    // 任务仍在运行时保存，已被取消时停止执行
+    async fn save_running(&self, job: &Job, step: &str) -> Result<()> {
+        if !self.checkpoint(job, step, &[Status::Running]).await? {
+            return Err(anyhow!("Role job is no longer running"));
+        }
+
+        Ok(())
+    }
+
+    // 只修改状态，任务当前状态不在expected中时不修改
+    async fn finish(
+        &self,
+        job_id: i32,
+        status: Status,
+        error: Option<String>,
+        expected: &[Status],
+    ) -> Result<()> {
+        let step = status.to_string();
+
+        if let Some(job) = self
+            .database
+            .set_role_job_status(job_id, status, error, expected)
+            .await?
+        {
+            self.emit(&job, &step).await;
+        }
+
+        Ok(())
+    }
+
+    // 保存中间结果并推送给任务所属用户，任务当前状态不在expected中时不保存，返回false
+    async fn checkpoint(&self, job: &Job, step: &str, expected: &[Status]) -> Result<bool> {
+        if !self.database.save_role_job(job, expected).await? {
+            return Ok(false);
+        }
+
+        self.emit(job, step).await;
+
+        Ok(true)
+    }
+
+    async fn emit(&self, job: &Job, step: &str) {
+        if let Some(socket) = &self.socket {
+            let event = ProgressData {
+                step: step.to_string(),
+                job: JobData::from(job),
+            };
+
+            if let Err(e) = socket
+                .to(user_room(job.user_id))
+                .emit(PROGRESS_EVENT, &event)
+                .await
+            {
+                tracing::warn!("Failed to emit role job progress: {}", e);
+            }
+        }
+    }
+}
+
+// 生成过程每完成一步，把中间结果写回任务并保存
+struct JobCheckpoint<'a> {
+    jobs: &'a RoleJobs,
+    job: Job,
+}
+
+impl Checkpoint for JobCheckpoint<'_> {
+    async fn save(&mut self, step: &str, draft: &RoleDraft) -> Result<()> {
+        let job = &mut self.job;
+
+        job.canonical_name = draft.canonical_name.clone();
+        if let Some(knowledge) = &draft.knowledge {
+            job.extract = Some(knowledge.extract.clone());
+            (job.wiki_lang, job.wiki_page_id) = knowledge.page.clone().unzip();
+            job.quotes = Some(knowledge.quotes.join("\n"));
+            if let Some(avatar) = &knowledge.avatar {
+                job.image = Some(avatar.url.clone());
+                job.image_attribution = Some(serde_json::to_string(&avatar.attribution)?);
+            }
+        }
+        job.traits_draft = draft.traits.clone();
+        job.voice_type = draft.voice_type.clone();
+
+        self.jobs.save_running(job, step).await
+    }
+}
+
+// 由任务中保存的中间结果恢复草稿
+fn draft(job: &Job) -> RoleDraft {
+    let knowledge = job.extract.as_ref().map(|extract| RoleKnowledge {
+        extract: extract.clone(),
+        quotes: quotes(job),
+        avatar: job
+            .image
+            .clone()
+            .zip(attribution(job))
+            .map(|(url, attribution)| Avatar { url, attribution }),
+        page: job.wiki_lang.clone().zip(job.wiki_page_id),
+    });
+
+    RoleDraft {
+        canonical_name: job.canonical_name.clone(),
+        knowledge,
+        traits: job.traits_draft.clone(),
+        voice_type: job.voice_type.clone(),
+    }
+}
+
+fn quotes(job: &Job) -> Vec<String> {
+    job.quotes
+        .as_deref()
+        .unwrap_or_default()
+        .lines()
+        .map(str::to_string)
+        .collect()
+}
+
+fn attribution(job: &Job) -> Option<ImageAttribution> {
+    serde_json::from_str(job.image_attribution.as_ref()?).ok()
+}
+
+#[derive(Serialize)]
+pub struct ProgressData {
+    pub step: String,
+    pub job: JobData,
+}
+
+#[derive(Serialize)]
+pub struct JobData {
+    pub job_id: i32,
+    pub status: Status,
+    pub name: String,
+    pub canonical_name: Option<String>,
+    pub extract: Option<String>,
+    pub traits_draft: Option<String>,
+    pub voice_type: Option<String>,
+    pub quotes: Vec<String>,
+    pub image: Option<String>,
+    pub image_attribution: Option<ImageAttribution>,
+    pub result: Option<JobResult>,
+    pub error: Option<String>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+#[derive(Serialize)]
+pub struct JobResult {
+    pub description: String,
+    pub traits: String,
+    pub gender: Gender,
+    pub age_group: AgeGroup,
+    pub voice_type: String,
+    pub example_dialogues: Vec<ExampleDialogue>,
+    pub greetings: Vec<String>,
+    // 创建角色时传回，用于识别重复的角色
+    #[serde(flatten)]
+    pub identity: RoleIdentity,
+    pub category: Option<Category>,
+    pub tags: Vec<String>,
+}
+
+impl From<&Job> for JobData {
+    fn from(job: &Job) -> Self {
+        let result = match (
+            &job.result_description,
+            &job.result_traits,
+            &job.result_gender,
+            &job.result_age_group,
+        ) {
+            (Some(description), Some(traits), Some(gender), Some(age_group)) => Some(JobResult {
+                description: description.clone(),
+                traits: traits.clone(),
+                gender: gender.clone(),
+                age_group: age_group.clone(),
+                voice_type: job.voice_type.clone().unwrap_or_default(),
+                example_dialogues: ExampleDialogue::from_json(
+                    job.result_example_dialogues.as_deref(),
+                ),
+                greetings: greetings_from_json(job.result_greetings.as_deref()),
+                identity: RoleIdentity {
+                    canonical_title: job.canonical_name.clone(),
+                    wiki_lang: job.wiki_lang.clone(),
+                    wiki_page_id: job.wiki_page_id,
+                },
+                category: job.result_category,
+                tags: job
+                    .result_tags
+                    .as_deref()
+                    .unwrap_or_default()
+                    .lines()
+                    .map(str::to_string)
+                    .collect(),
+            }),
+            _ => None,
+        };
+
+        Self {
+            job_id: job.id,
+            status: job.status.clone(),
+            name: job.name.clone(),
+            canonical_name: job.canonical_name.clone(),
+            extract: job.extract.clone(),
+            traits_draft: job.traits_draft.clone(),
+            voice_type: job.voice_type.clone(),
+            quotes: quotes(job),
+            image: job.image.clone(),
+            image_attribution: attribution(job),
+            result,
+            error: job.error.clone(),
+            created_at: job.created_at,
+            updated_at: job.updated_at,
+        }
+    }
+}
@@ -0,0 +1,251 @@
+use super::{AI, RetryConfig, remove_prefix_assistant};
+use anyhow::{Result, anyhow};
+use llm_chain::{parameters, prompt};
+use std::collections::HashMap;
+
+// 模型输出中的一个xml标签
+struct Field {
+    tag: String,
+    // 为空时允许任意非空文本
+    allowed: Vec<String>,
+    check: Option<Check>,
+}
+
+// 自定义校验，错误信息会反馈给模型
+type Check = fn(&str) -> std::result::Result<(), String>;
+
+// 声明期望的xml字段和取值范围，校验模型输出，失败时带上错误重新提问
+#[derive(Default)]
+pub struct Schema {
+    fields: Vec<Field>,
+}
+
+pub struct Structured {
+    values: HashMap<String, String>,
+}
+
+impl Schema {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn field(mut self, tag: &str) -> Self {
+        self.fields.push(Field {
+            tag: tag.to_string(),
+            allowed: Vec::new(),
+            check: None,
+        });
+        self
+    }
+
+    pub fn checked(mut self, tag: &str, check: Check) -> Self {
+        self.fields.push(Field {
+            tag: tag.to_string(),
+            allowed: Vec::new(),
+            check: Some(check),
+        });
+        self
+    }
+
+    pub fn one_of<I, S>(mut self, tag: &str, allowed: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.fields.push(Field {
+            tag: tag.to_string(),
+            allowed: allowed.into_iter().map(Into::into).collect(),
+            check: None,
+        });
+        self
+    }
+
+    // 返回所有校验错误，便于模型一次改正
+    pub fn validate(&self, output: &str) -> std::result::Result<Structured, String> {
+        let mut values = HashMap::new();
+        let mut errors = Vec::new();
+
+        for field in &self.fields {
+            let value = match extract_content_from_xml(output, &field.tag) {
+                Some(value) if !value.is_empty() => value,
+                _ => {
+                    errors.push(format!("缺少`<{0}></{0}>`标签或内容为空", field.tag));
+                    continue;
+                }
+            };
+
+            if let Some(check) = field.check
+                && let Err(error) = check(&value)
+            {
+                errors.push(format!("`<{}>`的内容不合法：{}", field.tag, error));
+                continue;
+            }
+
+            if field.allowed.is_empty() {
+                values.insert(field.tag.clone(), value);
+                continue;
+            }
+
+            // 忽略大小写和引号，统一为声明的取值
+            let normalized = value
+                .trim_matches(['"', '\'', '“', '”', '`'])
+                .to_lowercase();
+            match field
+                .allowed
+                .iter()
+                .find(|allowed| allowed.to_lowercase() == normalized)
+            {
+                Some(allowed) => {
+                    values.insert(field.tag.clone(), allowed.clone());
+                }
+                None => errors.push(format!(
+                    "`<{}>`的值“{}”不合法，只能是：{}",
+                    field.tag,
+                    value,
+                    field.allowed.join("、")
+                )),
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(Structured { values })
+        } else {
+            Err(errors.join("\n"))
+        }
+    }
+}
+
+impl Structured {
+    pub fn get(&self, tag: &str) -> &str {
+        self.values.get(tag).map(String::as_str).unwrap_or_default()
+    }
+}
+
+impl AI {
+    // 按schema校验模型输出，不合法时把错误反馈给模型重试
+    pub async fn structured(
+        &self,
+        system: &str,
+        input: &str,
+        schema: &Schema,
+    ) -> Result<Structured> {
+        let max_retries = RetryConfig::default().max_retries;
+        let mut feedback = String::new();
+
+        for attempt in 0..=max_retries {
+            let res = prompt!("{{system}}", "{{input}}{{feedback}}\nAssistant:")
+                .run(
+                    &parameters!("system" => system, "input" => input, "feedback" => feedback.as_str()),
+                    self.executor(),
+                )
+                .await?
+                .to_immediate()
+                .await?
+                .as_content()
+                .to_text()
+                .trim()
+                .to_string();
+
+            let res = remove_prefix_assistant(&res);
+
+            match schema.validate(res) {
+                Ok(structured) => return Ok(structured),
+                Err(error) => {
+                    tracing::warn!(
+                        "Structured output invalid (attempt {}): {}",
+                        attempt + 1,
+                        error
+                    );
+
+                    if attempt == max_retries {
+                        return Err(anyhow!("Invalid model output: {}\n{}", error, res));
+                    }
+
+                    feedback = retry_feedback(res, &error);
+                }
+            }
+        }
+
+        unreachable!()
+    }
+}
+
+fn retry_feedback(previous: &str, error: &str) -> String {
+    format!(
+        "\n——\n你上一次的回复：\n{}\n不符合要求：\n{}\n请修正后按要求的xml格式重新输出。",
+        previous, error
+    )
+}
+
+pub fn extract_content_from_xml(xml: &str, tag: &str) -> Option<String> {
+    let wrapped = format!("<root>{}</root>", xml);
+
+    if let Ok(doc) = roxmltree::Document::parse(&wrapped) {
+        let node = doc.descendants().find(|n| n.has_tag_name(tag))?;
+        return Some(node.text().unwrap_or_default().trim().to_string());
+    }
+
+    // 内容中含有&、<等字符时不是合法xml，退化为字符串查找
+    let open = format!("<{}>", tag);
+    let close = format!("</{}>", tag);
+    let start = xml.find(&open)? + open.len();
+    let end = start + xml[start..].find(&close)?;
+
+    Some(xml[start..end].trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schema() -> Schema {
+        Schema::new()
+            .field("description")
+            .one_of("gender", ["male", "female"])
+    }
+
+    #[test]
+    fn test_validate() {
+        let structured = schema()
+            .validate("<description>诗人</description><gender>\"Male\"</gender>")
+            .unwrap();
+
+        assert_eq!(structured.get("description"), "诗人");
+        assert_eq!(structured.get("gender"), "male");
+    }
+
+    #[test]
+    fn test_validate_errors() {
+        let error = schema().validate("<gender>unknown</gender>").err().unwrap();
+
+        assert_eq!(
+            error,
+            "缺少`<description></description>`标签或内容为空\n`<gender>`的值“unknown”不合法，只能是：male、female"
+        );
+    }
+
+    #[test]
+    fn test_validate_checked() {
+        let schema = Schema::new().checked("count", |value| match value.parse::<u32>() {
+            Ok(_) => Ok(()),
+            Err(_) => Err("必须是数字".to_string()),
+        });
+
+        assert_eq!(
+            schema.validate("<count>3</count>").unwrap().get("count"),
+            "3"
+        );
+        assert_eq!(
+            schema.validate("<count>三</count>").err().unwrap(),
+            "`<count>`的内容不合法：必须是数字"
+        );
+    }
+
+    #[test]
+    fn test_extract_invalid_xml() {
+        assert_eq!(
+            extract_content_from_xml("<traits>A & B</traits>", "traits").unwrap(),
+            "A & B"
+        );
+    }
+}
@@ -1,5 +1,8 @@
 use super::AI;
-use crate::{agents::remove_prefix_assistant, database::Database};
+use crate::{
+    agents::remove_prefix_assistant,
+    database::{Database, models::conversation_template},
+};
 use anyhow::Result;
 use llm_chain::{parameters, prompt};
 use std::sync::Arc;
@@ -42,7 +45,7 @@ impl Summarizer {
         let res = prompt!(sys, "对话内容：{{context}}\n请提炼长期信息：")
             .run(
                 &parameters!("context" => context),
-                &self.ai.thinking_executor,
+                self.ai.thinking_executor(),
             )
             .await?
             .to_immediate()
@@ -73,7 +76,7 @@ impl Summarizer {
         )
         .run(
             &parameters!("old" => old, "new" => new),
-            &self.ai.thinking_executor,
+            self.ai.thinking_executor(),
         )
         .await?
         .to_immediate()
@@ -88,44 +91,48 @@ impl Summarizer {
         Ok(res.to_string())
     }
 
-    pub async fn check_and_trigger(&self, user_id: i32, role_id: i32) -> Result<()> {
-        let count = self.database.get_dialog_count(user_id, role_id).await?;
+    pub async fn check_and_trigger(&self, conversation_id: i32) -> Result<()> {
+        let count = self
+            .database
+            .get_dialog_count_by_id(conversation_id)
+            .await?;
 
         if count > 0 && count % 10 == 0 {
-            self.tx.send(SummarizerTask { user_id, role_id })?;
+            self.tx.send(SummarizerTask::Update { conversation_id })?;
         }
 
         Ok(())
     }
 
+    // 分支对话需要基于分叉点之前的对话重新生成长期信息
+    pub fn trigger_rebuild(&self, conversation_id: i32) -> Result<()> {
+        self.tx.send(SummarizerTask::Rebuild { conversation_id })?;
+
+        Ok(())
+    }
+
     async fn process_task(&self, task: SummarizerTask) -> Result<()> {
+        match task {
+            SummarizerTask::Update { conversation_id } => self.update(conversation_id).await,
+            SummarizerTask::Rebuild { conversation_id } => self.rebuild(conversation_id).await,
+        }
+    }
+
+    async fn update(&self, conversation_id: i32) -> Result<()> {
         tracing::info!(
-            "Processing summarizer task for user {} role {}",
-            task.user_id,
-            task.role_id
+            "Processing summarizer task for conversation {}",
+            conversation_id
         );
 
         let recent_dialogs = self
             .database
-            .get_recent_dialogs(task.user_id, task.role_id, 10)
+            .get_recent_dialogs_by_id(conversation_id, 10)
             .await?;
         if recent_dialogs.is_empty() {
             return Ok(());
         }
 
-        let recent_context = recent_dialogs
-            .iter()
-            .map(|d| {
-                if d.is_user {
-                    format!("User: {}", d.text)
-                } else {
-                    format!("Assistant: {}", d.text)
-                }
-            })
-            .collect::<Vec<String>>()
-            .join("\n");
-
-        let new_summary = self.summarize(&recent_context).await?;
+        let new_summary = self.summarize(&to_context(&recent_dialogs)).await?;
 
         if new_summary.trim() == "无" {
             return Ok(());
@@ -133,7 +140,7 @@ impl Summarizer {
 
         let conversation = self
             .database
-            .get_conversation(task.user_id, task.role_id)
+            .get_conversation_by_id(conversation_id)
             .await?;
         let old_history = conversation
             .map(|c| c.history)
@@ -147,19 +154,61 @@ impl Summarizer {
         };
 
         self.database
-            .update_conversation_history(task.user_id, task.role_id, &final_history)
+            .update_conversation_history_by_id(conversation_id, &final_history)
             .await?;
 
         tracing::info!(
-            "Summarizer task completed for user {} role {}",
-            task.user_id,
-            task.role_id
+            "Summarizer task completed for conversation {}",
+            conversation_id
         );
         Ok(())
     }
+
+    async fn rebuild(&self, conversation_id: i32) -> Result<()> {
+        tracing::info!("Rebuilding summary for conversation {}", conversation_id);
+
+        let dialogs = self.database.get_dialogs_by_id(conversation_id).await?;
+
+        // 与增量总结保持一致，每10条对话提炼一次
+        let mut history = String::new();
+        for chunk in dialogs.chunks_exact(10) {
+            let summary = self.summarize(&to_context(chunk)).await?;
+
+            if summary.trim() == "无" {
+                continue;
+            }
+
+            history = if history.is_empty() {
+                summary
+            } else {
+                self.merge(&history, &summary).await?
+            };
+        }
+
+        self.database
+            .update_conversation_history_by_id(conversation_id, &history)
+            .await?;
+
+        tracing::info!("Summary rebuilt for conversation {}", conversation_id);
+        Ok(())
+    }
+}
+
+fn to_context(dialogs: &[conversation_template::Model]) -> String {
+    dialogs
+        .iter()
+        .map(|d| {
+            if d.is_user {
+                format!("User: {}", d.text)
+            } else {
+                format!("Assistant: {}", d.text)
+            }
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
 }
 
-pub struct SummarizerTask {
-    pub user_id: i32,
-    pub role_id: i32,
+pub enum SummarizerTask {
+    Update { conversation_id: i32 },
+    Rebuild { conversation_id: i32 },
 }
@@ -0,0 +1,427 @@
+pub mod png;
+
+use crate::database::models::roles::{
+    self, AgeGroup, Category, ExampleDialogue, Gender, MAX_EXAMPLE_DIALOGUES, MAX_GREETINGS,
+    MAX_TAG_CHARS, MAX_TAGS, normalize_tags,
+};
+use anyhow::{Result, anyhow};
+use base64::{Engine, engine::general_purpose};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+// 兼容TavernAI/SillyTavern的角色卡字段，V1卡片直接使用顶层字段
+#[derive(Default, Deserialize, Serialize)]
+pub struct CardData {
+    #[serde(default, alias = "char_name")]
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default, alias = "char_persona")]
+    pub personality: String,
+    #[serde(default, alias = "world_scenario")]
+    pub scenario: String,
+    #[serde(default, alias = "char_greeting")]
+    pub first_mes: String,
+    #[serde(default, alias = "example_dialogue")]
+    pub mes_example: String,
+    #[serde(default)]
+    pub creator_notes: String,
+    #[serde(default)]
+    pub system_prompt: String,
+    #[serde(default)]
+    pub post_history_instructions: String,
+    #[serde(default)]
+    pub alternate_greetings: Vec<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub creator: String,
+    #[serde(default)]
+    pub character_version: String,
+    #[serde(default)]
+    pub extensions: Map<String, Value>,
+}
+
+// 本平台写入extensions的字段，导入时据此无损还原角色
+#[derive(Deserialize, Serialize)]
+pub struct RoleExtension {
+    pub description: String,
+    pub traits: String,
+    pub gender: Gender,
+    pub age_group: AgeGroup,
+    pub voice_type: String,
+    #[serde(default)]
+    pub example_dialogues: Vec<ExampleDialogue>,
+    #[serde(default)]
+    pub category: Option<Category>,
+}
+
+pub const EXTENSION_KEY: &str = "role_play_ai";
+
+pub struct ParsedCard {
+    pub data: CardData,
+    // PNG角色卡的图片本身即为头像
+    pub avatar: Option<Vec<u8>>,
+}
+
+// PNG角色卡中存放卡片的tEXt关键字，V2为chara，V3为ccv3
+pub const PNG_KEYWORDS: [&str; 2] = ["chara", "ccv3"];
+
+pub fn parse(bytes: &[u8]) -> Result<ParsedCard> {
+    if png::is_png(bytes) {
+        return parse_png(bytes);
+    }
+
+    let data = parse_json(std::str::from_utf8(bytes)?)?;
+
+    Ok(ParsedCard { data, avatar: None })
+}
+
+pub fn parse_json(json: &str) -> Result<CardData> {
+    let value: Value = serde_json::from_str(json)?;
+
+    let data = match value.get("data") {
+        Some(data) if value.get("spec").is_some() => data.clone(),
+        _ => value,
+    };
+
+    let data: CardData = serde_json::from_value(data)?;
+
+    if data.name.trim().is_empty() {
+        return Err(anyhow!("Character card has no name"));
+    }
+
+    Ok(data)
+}
+
+fn parse_png(bytes: &[u8]) -> Result<ParsedCard> {
+    let texts = png::read_text_chunks(bytes)?;
+
+    // 同时存在时优先使用V3
+    let encoded = PNG_KEYWORDS
+        .iter()
+        .rev()
+        .find_map(|keyword| {
+            texts
+                .iter()
+                .find(|(k, _)| k == keyword)
+                .map(|(_, text)| text)
+        })
+        .ok_or_else(|| anyhow!("No character card found in PNG"))?;
+
+    let json = general_purpose::STANDARD.decode(encoded.trim())?;
+    let data = parse_json(std::str::from_utf8(&json)?)?;
+
+    let avatar = png::remove_text_chunks(bytes, &PNG_KEYWORDS)?;
+
+    Ok(ParsedCard {
+        data,
+        avatar: Some(avatar),
+    })
+}
+
+// 生成V2角色卡JSON
+pub fn to_json(data: &CardData) -> Result<String> {
+    Ok(serde_json::to_string(&serde_json::json!({
+        "spec": "chara_card_v2",
+        "spec_version": "2.0",
+        "data": data,
+    }))?)
+}
+
+// 将V2角色卡写入PNG的chara块
+pub fn to_png(data: &CardData, image: &[u8]) -> Result<Vec<u8>> {
+    let encoded = general_purpose::STANDARD.encode(to_json(data)?);
+    let image = png::remove_text_chunks(image, &PNG_KEYWORDS)?;
+
+    png::insert_text_chunk(&image, PNG_KEYWORDS[0], &encoded)
+}
+
+impl CardData {
+    pub fn from_role(role: &roles::Model, creator: &str) -> Result<Self> {
+        let extension = RoleExtension {
+            description: role.description.clone(),
+            traits: role.traits.clone(),
+            gender: role.gender.clone(),
+            age_group: role.age_group.clone(),
+            voice_type: role.voice_type.clone(),
+            example_dialogues: role.example_dialogues(),
+            category: role.category,
+        };
+
+        let mut extensions = Map::new();
+        extensions.insert(EXTENSION_KEY.to_string(), serde_json::to_value(&extension)?);
+
+        // 第一条开场白对应first_mes，其余作为备选
+        let mut greetings = role.greetings().into_iter();
+
+        Ok(Self {
+            name: role.name.clone(),
+            description: role.description.clone(),
+            personality: role.traits.clone(),
+            first_mes: greetings.next().unwrap_or_default(),
+            alternate_greetings: greetings.collect(),
+            mes_example: format_examples(&extension.example_dialogues),
+            creator: creator.to_string(),
+            extensions,
+            ..Default::default()
+        })
+    }
+
+    pub fn role_extension(&self) -> Option<RoleExtension> {
+        self.extensions
+            .get(EXTENSION_KEY)
+            .and_then(|value| serde_json::from_value(value.clone()).ok())
+    }
+
+    pub fn role_description(&self) -> String {
+        let mut parts = Vec::new();
+
+        if !self.description.trim().is_empty() {
+            parts.push(self.replace_placeholders(&self.description));
+        }
+        if !self.scenario.trim().is_empty() {
+            parts.push(format!(
+                "场景：{}",
+                self.replace_placeholders(&self.scenario)
+            ));
+        }
+
+        parts.join("\n\n")
+    }
+
+    pub fn role_traits(&self) -> String {
+        let mut parts = Vec::new();
+
+        if !self.personality.trim().is_empty() {
+            parts.push(format!(
+                "性格：{}",
+                self.replace_placeholders(&self.personality)
+            ));
+        }
+        if !self.system_prompt.trim().is_empty() {
+            parts.push(self.replace_placeholders(&self.system_prompt));
+        }
+        // 能解析为一问一答的示例对话单独保存
+        if !self.mes_example.trim().is_empty() && self.role_examples().is_empty() {
+            parts.push(format!(
+                "示例对话：\n{}",
+                self.replace_placeholders(&self.mes_example)
+            ));
+        }
+
+        parts.join("\n\n")
+    }
+
+    pub fn role_greetings(&self) -> Vec<String> {
+        std::iter::once(&self.first_mes)
+            .chain(&self.alternate_greetings)
+            .map(|greeting| self.replace_placeholders(greeting))
+            .filter(|greeting| !greeting.is_empty())
+            .take(MAX_GREETINGS)
+            .collect()
+    }
+
+    // 卡片标签中过长的丢弃，超出数量的截断
+    pub fn role_tags(&self) -> Vec<String> {
+        normalize_tags(&self.tags)
+            .into_iter()
+            .filter(|tag| tag.chars().count() <= MAX_TAG_CHARS)
+            .take(MAX_TAGS)
+            .collect()
+    }
+
+    // 解析mes_example中的示例对话，每个<START>块中取成对的{{user}}和{{char}}发言
+    pub fn role_examples(&self) -> Vec<ExampleDialogue> {
+        let mut examples = Vec::new();
+        let mut user = None;
+
+        for line in self.mes_example.lines().map(str::trim) {
+            if let Some(text) = strip_speaker(line, &["{{user}}:", "{{User}}:", "<USER>:"]) {
+                user = Some(self.replace_placeholders(text));
+            } else if let Some(text) = strip_speaker(line, &["{{char}}:", "{{Char}}:", "<BOT>:"])
+                && let Some(user) = user.take()
+            {
+                examples.push(ExampleDialogue {
+                    user,
+                    role: self.replace_placeholders(text),
+                });
+            } else if line == "<START>" {
+                user = None;
+            }
+        }
+
+        examples.truncate(MAX_EXAMPLE_DIALOGUES);
+        examples
+    }
+
+    // 替换卡片中的{{char}}、{{user}}等占位符
+    fn replace_placeholders(&self, text: &str) -> String {
+        text.replace("{{char}}", &self.name)
+            .replace("{{Char}}", &self.name)
+            .replace("<BOT>", &self.name)
+            .replace("{{user}}", "User")
+            .replace("{{User}}", "User")
+            .replace("<USER>", "User")
+            .trim()
+            .to_string()
+    }
+}
+
+fn strip_speaker<'a>(line: &'a str, prefixes: &[&str]) -> Option<&'a str> {
+    prefixes
+        .iter()
+        .find_map(|prefix| line.strip_prefix(prefix))
+        .map(str::trim)
+        .filter(|text| !text.is_empty())
+}
+
+// 示例对话转换为mes_example格式
+fn format_examples(examples: &[ExampleDialogue]) -> String {
+    examples
+        .iter()
+        .map(|example| {
+            format!(
+                "<START>\n{{{{user}}}}: {}\n{{{{char}}}}: {}",
+                example.user, example.role
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const V2_CARD: &str = r#"{
+        "spec": "chara_card_v2",
+        "spec_version": "2.0",
+        "data": {
+            "name": "Sherlock",
+            "description": "{{char}} is a detective in London.",
+            "personality": "observant, arrogant",
+            "scenario": "221B Baker Street",
+            "first_mes": "You've been in Afghanistan, I perceive.",
+            "mes_example": "<START>\n{{user}}: Hi\n{{char}}: Elementary.",
+            "tags": ["detective"],
+            "extensions": {"talkativeness": "0.5"}
+        }
+    }"#;
+
+    #[test]
+    fn test_parse_v2_json() {
+        let card = parse(V2_CARD.as_bytes()).unwrap();
+
+        assert!(card.avatar.is_none());
+        assert_eq!(card.data.name, "Sherlock");
+        assert_eq!(card.data.tags, vec!["detective"]);
+        assert_eq!(card.data.role_tags(), vec!["detective"]);
+        assert_eq!(
+            card.data.role_description(),
+            "Sherlock is a detective in London.\n\n场景：221B Baker Street"
+        );
+        assert_eq!(card.data.role_traits(), "性格：observant, arrogant");
+        assert_eq!(
+            card.data.role_examples(),
+            vec![ExampleDialogue {
+                user: "Hi".to_string(),
+                role: "Elementary.".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_unparsed_examples() {
+        let card = parse_json(r#"{"name": "Alice", "mes_example": "Alice waves."}"#).unwrap();
+
+        assert!(card.role_examples().is_empty());
+        assert_eq!(card.role_traits(), "示例对话：\nAlice waves.");
+    }
+
+    #[test]
+    fn test_parse_v1_json() {
+        let card = parse_json(r#"{"char_name": "Alice", "char_persona": "curious"}"#).unwrap();
+
+        assert_eq!(card.name, "Alice");
+        assert_eq!(card.personality, "curious");
+    }
+
+    #[test]
+    fn test_parse_without_name() {
+        assert!(parse_json(r#"{"description": "nobody"}"#).is_err());
+    }
+
+    #[test]
+    fn test_parse_png() {
+        let encoded = general_purpose::STANDARD.encode(V2_CARD);
+        let bytes = png::tests::sample_png(&[("chara", &encoded)]);
+
+        let card = parse(&bytes).unwrap();
+
+        assert_eq!(card.data.name, "Sherlock");
+        assert_eq!(card.avatar.unwrap(), png::tests::sample_png(&[]));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let role = roles::Model {
+            id: 1,
+            user_id: 1,
+            name: "李白".to_string(),
+            description: "唐代诗人".to_string(),
+            traits: "豪放飘逸".to_string(),
+            image: String::new(),
+            gender: Gender::Male,
+            age_group: AgeGroup::Mature,
+            voice_type: "zh_male_1".to_string(),
+            version: 1,
+            image_attribution: None,
+            example_dialogues: Some(r#"[{"user":"你好","role":"幸会"}]"#.to_string()),
+            greetings: Some(r#"["幸会，且饮一杯。","今夜月色正好。"]"#.to_string()),
+            canonical_title: Some("Li Bai".to_string()),
+            wiki_lang: Some("zh".to_string()),
+            wiki_page_id: Some(9514),
+            visibility: roles::Visibility::Public,
+            category: Some(roles::Category::Literature),
+            parent_id: None,
+            allow_fork: true,
+            hidden: false,
+        };
+        let data = CardData::from_role(&role, "alice").unwrap();
+        let image = png::tests::sample_png(&[("Software", "test")]);
+
+        let card = parse(&to_png(&data, &image).unwrap()).unwrap();
+        let extension = card.data.role_extension().unwrap();
+
+        assert_eq!(card.avatar.unwrap(), image);
+        assert_eq!(card.data.name, role.name);
+        assert_eq!(card.data.creator, "alice");
+        assert_eq!(extension.description, role.description);
+        assert_eq!(extension.traits, role.traits);
+        assert_eq!(extension.gender, role.gender);
+        assert_eq!(extension.age_group, role.age_group);
+        assert_eq!(extension.voice_type, role.voice_type);
+        assert_eq!(extension.example_dialogues, role.example_dialogues());
+        assert_eq!(extension.category, role.category);
+        assert_eq!(card.data.role_examples(), role.example_dialogues());
+        assert_eq!(card.data.first_mes, "幸会，且饮一杯。");
+        assert_eq!(card.data.role_greetings(), role.greetings());
+
+        let card = parse(to_json(&data).unwrap().as_bytes()).unwrap();
+        assert!(card.data.role_extension().is_some());
+    }
+
+    #[test]
+    fn test_format_examples() {
+        let examples = vec![ExampleDialogue {
+            user: "你好".to_string(),
+            role: "幸会".to_string(),
+        }];
+
+        assert_eq!(
+            format_examples(&examples),
+            "<START>\n{{user}}: 你好\n{{char}}: 幸会"
+        );
+    }
+}
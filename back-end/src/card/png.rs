@@ -0,0 +1,230 @@
+use anyhow::{Result, anyhow};
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+
+pub fn is_png(data: &[u8]) -> bool {
+    data.starts_with(&SIGNATURE)
+}
+
+struct Chunk<'a> {
+    chunk_type: &'a [u8],
+    data: &'a [u8],
+    // 包含长度、类型、数据和CRC的完整字节
+    raw: &'a [u8],
+}
+
+fn chunks(data: &[u8]) -> Result<Vec<Chunk<'_>>> {
+    if !is_png(data) {
+        return Err(anyhow!("Not a PNG file"));
+    }
+
+    let mut chunks = Vec::new();
+    let mut pos = SIGNATURE.len();
+
+    while pos < data.len() {
+        if pos + 8 > data.len() {
+            return Err(anyhow!("Truncated PNG chunk header"));
+        }
+
+        let length = u32::from_be_bytes(data[pos..pos + 4].try_into()?) as usize;
+        let end = pos + 12 + length;
+
+        if end > data.len() {
+            return Err(anyhow!("Truncated PNG chunk"));
+        }
+
+        chunks.push(Chunk {
+            chunk_type: &data[pos + 4..pos + 8],
+            data: &data[pos + 8..pos + 8 + length],
+            raw: &data[pos..end],
+        });
+
+        pos = end;
+    }
+
+    Ok(chunks)
+}
+
+// 读取所有tEXt块，返回(关键字, 文本)
+pub fn read_text_chunks(data: &[u8]) -> Result<Vec<(String, String)>> {
+    let mut texts = Vec::new();
+
+    for chunk in chunks(data)? {
+        if chunk.chunk_type != b"tEXt" {
+            continue;
+        }
+
+        if let Some(separator) = chunk.data.iter().position(|&b| b == 0) {
+            // tEXt使用Latin-1编码
+            let keyword = chunk.data[..separator]
+                .iter()
+                .map(|&b| b as char)
+                .collect::<String>();
+            let text = chunk.data[separator + 1..]
+                .iter()
+                .map(|&b| b as char)
+                .collect::<String>();
+
+            texts.push((keyword, text));
+        }
+    }
+
+    Ok(texts)
+}
+
+// 删除指定关键字的tEXt块
+pub fn remove_text_chunks(data: &[u8], keywords: &[&str]) -> Result<Vec<u8>> {
+    let mut output = SIGNATURE.to_vec();
+
+    for chunk in chunks(data)? {
+        let is_removed = chunk.chunk_type == b"tEXt"
+            && keywords.iter().any(|keyword| {
+                chunk.data.starts_with(keyword.as_bytes())
+                    && chunk.data.get(keyword.len()) == Some(&0)
+            });
+
+        if !is_removed {
+            output.extend_from_slice(chunk.raw);
+        }
+    }
+
+    Ok(output)
+}
+
+// 在IEND之前插入tEXt块，同时删除同关键字的旧块
+pub fn insert_text_chunk(data: &[u8], keyword: &str, text: &str) -> Result<Vec<u8>> {
+    let data = remove_text_chunks(data, &[keyword])?;
+    let mut output = SIGNATURE.to_vec();
+    let mut inserted = false;
+
+    for chunk in chunks(&data)? {
+        if chunk.chunk_type == b"IEND" {
+            let mut content = keyword.as_bytes().to_vec();
+            content.push(0);
+            content.extend_from_slice(text.as_bytes());
+
+            output.extend(encode_chunk(b"tEXt", &content));
+            inserted = true;
+        }
+
+        output.extend_from_slice(chunk.raw);
+    }
+
+    if !inserted {
+        return Err(anyhow!("PNG has no IEND chunk"));
+    }
+
+    Ok(output)
+}
+
+// 1x1透明图片，角色没有PNG头像时使用
+pub fn placeholder() -> Vec<u8> {
+    // zlib存储块：过滤类型0和一个RGBA像素，末尾为adler32
+    const IDAT: [u8; 16] = [
+        0x78, 0x01, 0x01, 0x05, 0x00, 0xfa, 0xff, 0, 0, 0, 0, 0, 0x00, 0x05, 0x00, 0x01,
+    ];
+
+    let mut output = SIGNATURE.to_vec();
+    output.extend(encode_chunk(
+        b"IHDR",
+        &[0, 0, 0, 1, 0, 0, 0, 1, 8, 6, 0, 0, 0],
+    ));
+    output.extend(encode_chunk(b"IDAT", &IDAT));
+    output.extend(encode_chunk(b"IEND", &[]));
+    output
+}
+
+fn encode_chunk(chunk_type: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut raw = (data.len() as u32).to_be_bytes().to_vec();
+    raw.extend_from_slice(chunk_type);
+    raw.extend_from_slice(data);
+    raw.extend_from_slice(&crc32(&raw[4..]).to_be_bytes());
+    raw
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = 0xffff_ffffu32;
+
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+
+    !crc
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    pub fn chunk(chunk_type: &[u8], data: &[u8]) -> Vec<u8> {
+        let mut raw = (data.len() as u32).to_be_bytes().to_vec();
+        raw.extend_from_slice(chunk_type);
+        raw.extend_from_slice(data);
+        // 读取时不校验CRC
+        raw.extend_from_slice(&[0, 0, 0, 0]);
+        raw
+    }
+
+    pub fn sample_png(texts: &[(&str, &str)]) -> Vec<u8> {
+        let mut png = SIGNATURE.to_vec();
+        png.extend(chunk(b"IHDR", &[0, 0, 0, 1, 0, 0, 0, 1, 8, 6, 0, 0, 0]));
+        for (keyword, text) in texts {
+            png.extend(chunk(b"tEXt", format!("{}\0{}", keyword, text).as_bytes()));
+        }
+        png.extend(chunk(b"IEND", &[]));
+        png
+    }
+
+    #[test]
+    fn test_read_and_remove_text_chunks() {
+        let png = sample_png(&[("chara", "abc"), ("Software", "test")]);
+
+        let texts = read_text_chunks(&png).unwrap();
+        assert_eq!(
+            texts,
+            vec![
+                ("chara".to_string(), "abc".to_string()),
+                ("Software".to_string(), "test".to_string())
+            ]
+        );
+
+        let stripped = remove_text_chunks(&png, &["chara"]).unwrap();
+        assert_eq!(stripped, sample_png(&[("Software", "test")]));
+    }
+
+    #[test]
+    fn test_insert_text_chunk() {
+        let png = sample_png(&[("chara", "old")]);
+
+        let inserted = insert_text_chunk(&png, "chara", "new").unwrap();
+        assert_eq!(
+            read_text_chunks(&inserted).unwrap(),
+            vec![("chara".to_string(), "new".to_string())]
+        );
+        assert_eq!(
+            remove_text_chunks(&inserted, &["chara"]).unwrap(),
+            sample_png(&[])
+        );
+    }
+
+    #[test]
+    fn test_crc32() {
+        assert_eq!(crc32(b"IEND"), 0xae42_6082);
+        assert!(placeholder().ends_with(&[0xae, 0x42, 0x60, 0x82]));
+    }
+
+    #[test]
+    fn test_truncated_png() {
+        let mut png = sample_png(&[("chara", "abc")]);
+        png.truncate(png.len() - 6);
+
+        assert!(read_text_chunks(&png).is_err());
+    }
+}
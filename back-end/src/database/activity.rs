@@ -0,0 +1,38 @@
+use super::Database;
+use crate::database::models::{conversations, debates};
+use anyhow::Result;
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, QueryOrder, QuerySelect};
+
+impl Database {
+    // 用户最近的对话，不含分支
+    pub async fn list_recent_conversations(
+        &self,
+        user_id: i32,
+        limit: u64,
+    ) -> Result<Vec<conversations::Model>> {
+        let conversations = conversations::Entity::find()
+            .filter(conversations::Column::UserId.eq(user_id))
+            .filter(conversations::Column::ParentId.is_null())
+            .order_by_desc(conversations::Column::LastDialogTimestamp)
+            .limit(limit)
+            .all(&self.connection)
+            .await?;
+
+        Ok(conversations)
+    }
+
+    pub async fn list_recent_debates(
+        &self,
+        user_id: i32,
+        limit: u64,
+    ) -> Result<Vec<debates::Model>> {
+        let debates = debates::Entity::find()
+            .filter(debates::Column::UserId.eq(user_id))
+            .order_by_desc(debates::Column::LastDialogTimestamp)
+            .limit(limit)
+            .all(&self.connection)
+            .await?;
+
+        Ok(debates)
+    }
+}
@@ -0,0 +1,119 @@
+use super::{Database, PaginatedResult};
+use crate::{
+    database::models::{
+        conversations, debates, roles,
+        users::{self, UserRole},
+    },
+    stats::GlobalStats,
+};
+use anyhow::Result;
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, EntityTrait, PaginatorTrait, QueryFilter, QueryOrder,
+    QuerySelect, prelude::Expr,
+};
+
+// 统计消息数时每条SQL合并的对话表数
+const COUNT_TABLES_PER_QUERY: usize = 100;
+
+impl Database {
+    // 按用户名搜索用户，为空时列出所有用户，先注册的在前
+    pub async fn search_users_paginated(
+        &self,
+        query: &str,
+        offset: i64,
+        limit: i64,
+    ) -> Result<PaginatedResult<users::Model>> {
+        let mut select = users::Entity::find();
+
+        if !query.is_empty() {
+            select = select.filter(users::Column::Username.contains(query));
+        }
+
+        let paginator = select
+            .order_by_asc(users::Column::Id)
+            .paginate(&self.connection, limit as u64);
+
+        let num_pages = paginator.num_pages().await?;
+        let total = paginator.num_items().await?;
+
+        let page_number = (offset / limit) as u64;
+        let items = paginator.fetch_page(page_number).await?;
+        let has_more = (page_number + 1) < num_pages;
+
+        Ok(PaginatedResult {
+            items,
+            total: total as i64,
+            has_more,
+        })
+    }
+
+    pub async fn set_user_role(&self, user_id: i32, role: UserRole) -> Result<()> {
+        users::Entity::update_many()
+            .col_expr(users::Column::Role, Expr::value(role))
+            .filter(users::Column::Id.eq(user_id))
+            .exec(&self.connection)
+            .await?;
+
+        Ok(())
+    }
+
+    // 只转移角色本身，原作者与角色的对话保留
+    pub async fn transfer_role(&self, role_id: i32, user_id: i32) -> Result<()> {
+        roles::Entity::update_many()
+            .col_expr(roles::Column::UserId, Expr::value(user_id))
+            .filter(roles::Column::Id.eq(role_id))
+            .exec(&self.connection)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_global_stats(&self) -> Result<GlobalStats> {
+        Ok(GlobalStats {
+            users: users::Entity::find().count(&self.connection).await? as i64,
+            roles: roles::Entity::find().count(&self.connection).await? as i64,
+            conversations: conversations::Entity::find()
+                .count(&self.connection)
+                .await? as i64,
+            dialogs: self.count_all_dialogs().await?,
+            debates: debates::Entity::find().count(&self.connection).await? as i64,
+        })
+    }
+
+    // 每个对话一张表，分批用UNION ALL合计；MySQL中SUM的结果为DECIMAL，转为整数读取
+    async fn count_all_dialogs(&self) -> Result<i64> {
+        let table_names: Vec<String> = conversations::Entity::find()
+            .select_only()
+            .column(conversations::Column::TableName)
+            .into_tuple()
+            .all(&self.connection)
+            .await?;
+
+        let mut total = 0;
+
+        for chunk in table_names.chunks(COUNT_TABLES_PER_QUERY) {
+            let sql = format!(
+                "SELECT CAST(COALESCE(SUM(count), 0) AS SIGNED) AS count FROM ({}) AS counts",
+                chunk
+                    .iter()
+                    .map(|table_name| format!("SELECT COUNT(*) AS count FROM `{}`", table_name))
+                    .collect::<Vec<_>>()
+                    .join(" UNION ALL ")
+            );
+
+            let res = self
+                .connection
+                .query_one(sea_orm::Statement::from_string(
+                    self.connection.get_database_backend(),
+                    sql,
+                ))
+                .await?;
+
+            total += res
+                .and_then(|row| row.try_get::<i64>("", "count").ok())
+                .unwrap_or(0);
+        }
+
+        Ok(total)
+    }
+}
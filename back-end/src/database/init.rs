@@ -7,10 +7,22 @@
 
 use super::Database;
 use crate::database::models::{
-    conversation_template, conversations, debate_template, debates, roles, users,
+    conversation_template, conversations, debate_template, debates, lorebook_entries, lorebooks,
+    migrations, moderation_logs, reports, role_batch_items, role_batches, role_daily_stats,
+    role_embeddings, role_jobs, role_reactions, role_reviews, role_tags, role_users, role_versions,
+    roles, users,
 };
 use anyhow::Result;
-use sea_orm::{ConnectionTrait, DatabaseConnection, EntityName, EntityTrait, Schema, Statement};
+use chrono::Utc;
+use sea_orm::{
+    ActiveValue::Set,
+    ConnectionTrait, DatabaseConnection, EntityName, EntityTrait, IdenStatic, Schema, Statement,
+    sea_query::{Index, IndexCreateStatement, Table},
+};
+
+// 一次性迁移的名称
+const CONVERSATION_ROLE_VERSION: &str = "conversation_role_version";
+const ROLE_VERSIONS_BACKFILL: &str = "role_versions_backfill";
 
 impl Database {
     pub async fn create_database_if_not_exists(connection: &DatabaseConnection) -> Result<()> {
@@ -36,6 +48,163 @@ impl Database {
         self.create_table_if_not_exists(debate_template::Entity)
             .await?;
         self.create_table_if_not_exists(debates::Entity).await?;
+        self.create_table_if_not_exists(lorebooks::Entity).await?;
+        self.create_table_if_not_exists(lorebook_entries::Entity)
+            .await?;
+        // 同一角色的版本号唯一
+        self.create_table_with_index_if_not_exists(
+            role_versions::Entity,
+            Index::create()
+                .name("idx_role_versions_role_version")
+                .col(role_versions::Column::RoleId)
+                .col(role_versions::Column::Version)
+                .unique()
+                .to_owned(),
+        )
+        .await?;
+        self.create_table_if_not_exists(role_jobs::Entity).await?;
+        self.create_table_if_not_exists(role_tags::Entity).await?;
+        self.create_table_if_not_exists(role_batches::Entity)
+            .await?;
+        self.create_table_if_not_exists(role_batch_items::Entity)
+            .await?;
+        self.create_table_if_not_exists(role_embeddings::Entity)
+            .await?;
+        self.create_table_if_not_exists(role_daily_stats::Entity)
+            .await?;
+        self.create_table_if_not_exists(role_users::Entity).await?;
+        self.create_table_if_not_exists(role_reactions::Entity)
+            .await?;
+        self.create_table_if_not_exists(role_reviews::Entity)
+            .await?;
+        self.create_table_if_not_exists(reports::Entity).await?;
+        self.create_table_if_not_exists(moderation_logs::Entity)
+            .await?;
+        self.create_table_if_not_exists(migrations::Entity).await?;
+
+        self.migrate().await?;
+
+        Ok(())
+    }
+
+    // 为旧版本数据库补齐新增的列
+    async fn migrate(&self) -> Result<()> {
+        self.add_column_if_not_exists(conversations::Entity, conversations::Column::TableName)
+            .await?;
+        self.add_column_if_not_exists(conversations::Entity, conversations::Column::ParentId)
+            .await?;
+        self.add_column_if_not_exists(conversations::Entity, conversations::Column::ForkDialogId)
+            .await?;
+
+        // 旧对话的表名由用户ID和角色ID计算得出
+        self.connection
+            .execute(Statement::from_string(
+                self.connection.get_database_backend(),
+                "UPDATE `conversations` SET `table_name` = CONCAT('conv_', `user_id`, '_', `role_id`) WHERE `table_name` = ''",
+            ))
+            .await?;
+
+        self.add_column_if_not_exists(roles::Entity, roles::Column::Version)
+            .await?;
+        self.add_column_if_not_exists(roles::Entity, roles::Column::ImageAttribution)
+            .await?;
+        self.add_column_if_not_exists(roles::Entity, roles::Column::ExampleDialogues)
+            .await?;
+        self.add_column_if_not_exists(
+            role_versions::Entity,
+            role_versions::Column::ExampleDialogues,
+        )
+        .await?;
+        self.add_column_if_not_exists(roles::Entity, roles::Column::Greetings)
+            .await?;
+        self.add_column_if_not_exists(role_versions::Entity, role_versions::Column::Greetings)
+            .await?;
+        self.add_column_if_not_exists(roles::Entity, roles::Column::CanonicalTitle)
+            .await?;
+        self.add_column_if_not_exists(roles::Entity, roles::Column::WikiLang)
+            .await?;
+        self.add_column_if_not_exists(roles::Entity, roles::Column::WikiPageId)
+            .await?;
+        self.add_column_if_not_exists(roles::Entity, roles::Column::Visibility)
+            .await?;
+        self.add_column_if_not_exists(roles::Entity, roles::Column::Category)
+            .await?;
+        self.add_column_if_not_exists(roles::Entity, roles::Column::ParentId)
+            .await?;
+        self.add_column_if_not_exists(roles::Entity, roles::Column::AllowFork)
+            .await?;
+        self.add_column_if_not_exists(roles::Entity, roles::Column::Hidden)
+            .await?;
+        self.add_column_if_not_exists(users::Entity, users::Column::Role)
+            .await?;
+        self.add_column_if_not_exists(users::Entity, users::Column::SuspendedUntil)
+            .await?;
+
+        self.add_column_if_not_exists(role_jobs::Entity, role_jobs::Column::WikiLang)
+            .await?;
+        self.add_column_if_not_exists(role_jobs::Entity, role_jobs::Column::WikiPageId)
+            .await?;
+        self.add_column_if_not_exists(role_jobs::Entity, role_jobs::Column::Quotes)
+            .await?;
+        self.add_column_if_not_exists(role_jobs::Entity, role_jobs::Column::Image)
+            .await?;
+        self.add_column_if_not_exists(role_jobs::Entity, role_jobs::Column::ImageAttribution)
+            .await?;
+        self.add_column_if_not_exists(role_jobs::Entity, role_jobs::Column::ResultExampleDialogues)
+            .await?;
+        self.add_column_if_not_exists(role_jobs::Entity, role_jobs::Column::ResultGreetings)
+            .await?;
+        self.add_column_if_not_exists(role_jobs::Entity, role_jobs::Column::ResultCategory)
+            .await?;
+        self.add_column_if_not_exists(role_jobs::Entity, role_jobs::Column::ResultTags)
+            .await?;
+        self.add_column_if_not_exists(
+            conversation_template::Entity,
+            conversation_template::Column::RoleVersion,
+        )
+        .await?;
+
+        // 已有的对话记录表由旧模板生成，需要逐个补齐，只执行一次
+        if !self.is_migration_applied(CONVERSATION_ROLE_VERSION).await? {
+            let conversations = conversations::Entity::find().all(&self.connection).await?;
+            for conversation in conversations {
+                // 表可能已被删除
+                if !self.is_table_name_exists(&conversation.table_name).await?
+                    || self
+                        .is_column_exists(&conversation.table_name, "role_version")
+                        .await?
+                {
+                    continue;
+                }
+
+                let sql = format!(
+                    "ALTER TABLE `{}` ADD COLUMN `role_version` INT NULL",
+                    conversation.table_name
+                );
+
+                self.connection
+                    .execute(Statement::from_string(
+                        self.connection.get_database_backend(),
+                        sql,
+                    ))
+                    .await?;
+            }
+
+            self.mark_migration_applied(CONVERSATION_ROLE_VERSION)
+                .await?;
+        }
+
+        // 旧角色以当前定义作为第一个版本，只执行一次
+        if !self.is_migration_applied(ROLE_VERSIONS_BACKFILL).await? {
+            self.connection
+                .execute(Statement::from_string(
+                    self.connection.get_database_backend(),
+                    "INSERT INTO `role_versions` (`role_id`, `version`, `name`, `description`, `traits`, `image`, `gender`, `age_group`, `voice_type`, `created_at`) SELECT `id`, `version`, `name`, `description`, `traits`, `image`, `gender`, `age_group`, `voice_type`, 0 FROM `roles` WHERE `id` NOT IN (SELECT `role_id` FROM `role_versions`)",
+                ))
+                .await?;
+
+            self.mark_migration_applied(ROLE_VERSIONS_BACKFILL).await?;
+        }
 
         Ok(())
     }
@@ -65,4 +234,115 @@ impl Database {
 
         Ok(())
     }
+
+    async fn create_table_with_index_if_not_exists<E>(
+        &self,
+        entity: E,
+        mut index: IndexCreateStatement,
+    ) -> Result<()>
+    where
+        E: EntityTrait + EntityName,
+    {
+        if !self.is_table_exists::<E>().await {
+            let backend = self.connection.get_database_backend();
+
+            let table_create_statement = Schema::new(backend)
+                .create_table_from_entity(entity)
+                .index(&mut index)
+                .to_owned();
+
+            self.connection
+                .execute(backend.build(&table_create_statement))
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn is_table_name_exists(&self, table_name: &str) -> Result<bool> {
+        let sql = "SELECT COUNT(*) as count FROM information_schema.tables WHERE table_schema = ? AND table_name = ?";
+
+        let res = self
+            .connection
+            .query_one(Statement::from_sql_and_values(
+                self.connection.get_database_backend(),
+                sql,
+                vec![
+                    sea_orm::Value::from(super::DB_NAME),
+                    sea_orm::Value::from(table_name),
+                ],
+            ))
+            .await?;
+
+        let count: i64 = res
+            .and_then(|row| row.try_get("", "count").ok())
+            .unwrap_or(0);
+
+        Ok(count > 0)
+    }
+
+    async fn is_migration_applied(&self, name: &str) -> Result<bool> {
+        let migration = migrations::Entity::find_by_id(name.to_string())
+            .one(&self.connection)
+            .await?;
+
+        Ok(migration.is_some())
+    }
+
+    async fn mark_migration_applied(&self, name: &str) -> Result<()> {
+        migrations::Entity::insert(migrations::ActiveModel {
+            name: Set(name.to_string()),
+            created_at: Set(Utc::now().timestamp_millis()),
+        })
+        .exec(&self.connection)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn is_column_exists(&self, table_name: &str, column_name: &str) -> Result<bool> {
+        let sql = "SELECT COUNT(*) as count FROM information_schema.columns WHERE table_schema = ? AND table_name = ? AND column_name = ?";
+
+        let res = self
+            .connection
+            .query_one(Statement::from_sql_and_values(
+                self.connection.get_database_backend(),
+                sql,
+                vec![
+                    sea_orm::Value::from(super::DB_NAME),
+                    sea_orm::Value::from(table_name),
+                    sea_orm::Value::from(column_name),
+                ],
+            ))
+            .await?;
+
+        let count: i64 = res
+            .and_then(|row| row.try_get("", "count").ok())
+            .unwrap_or(0);
+
+        Ok(count > 0)
+    }
+
+    async fn add_column_if_not_exists<E>(&self, entity: E, column: E::Column) -> Result<()>
+    where
+        E: EntityTrait + EntityName,
+    {
+        if !self
+            .is_column_exists(entity.table_name(), column.as_str())
+            .await?
+        {
+            let backend = self.connection.get_database_backend();
+
+            let table_alter_statement = Table::alter()
+                .table(entity)
+                .add_column(Schema::new(backend).get_column_def::<E>(column))
+                .to_owned();
+
+            self.connection
+                .execute(backend.build(&table_alter_statement))
+                .await?;
+        }
+
+        Ok(())
+    }
 }
@@ -0,0 +1,192 @@
+use super::Database;
+use crate::{
+    database::models::{lorebook_entries, lorebooks},
+    lorebook::Lorebook,
+};
+use anyhow::Result;
+use sea_orm::{
+    ActiveValue::{self, Set},
+    ColumnTrait, Condition, EntityTrait, QueryFilter, QueryOrder,
+};
+
+impl Database {
+    pub async fn add_lorebook(
+        &self,
+        user_id: i32,
+        name: &str,
+        role_id: Option<i32>,
+        conversation_id: Option<i32>,
+        token_budget: i32,
+    ) -> Result<i32> {
+        let lorebook = lorebooks::ActiveModel {
+            id: ActiveValue::default(),
+            user_id: Set(user_id),
+            name: Set(name.to_string()),
+            role_id: Set(role_id),
+            conversation_id: Set(conversation_id),
+            token_budget: Set(token_budget),
+        };
+
+        let res = lorebooks::Entity::insert(lorebook)
+            .exec(&self.connection)
+            .await?;
+
+        Ok(res.last_insert_id)
+    }
+
+    pub async fn get_lorebook(&self, lorebook_id: i32) -> Result<lorebooks::Model> {
+        let lorebook = lorebooks::Entity::find_by_id(lorebook_id)
+            .one(&self.connection)
+            .await?;
+
+        lorebook.ok_or_else(|| anyhow::anyhow!("Lorebook not found"))
+    }
+
+    // 列出用户创建的世界书，可按角色或对话过滤
+    pub async fn list_lorebooks(
+        &self,
+        user_id: i32,
+        role_id: Option<i32>,
+        conversation_id: Option<i32>,
+    ) -> Result<Vec<lorebooks::Model>> {
+        let mut query = lorebooks::Entity::find().filter(lorebooks::Column::UserId.eq(user_id));
+
+        if let Some(role_id) = role_id {
+            query = query.filter(lorebooks::Column::RoleId.eq(role_id));
+        }
+        if let Some(conversation_id) = conversation_id {
+            query = query.filter(lorebooks::Column::ConversationId.eq(conversation_id));
+        }
+
+        let lorebooks = query
+            .order_by_asc(lorebooks::Column::Id)
+            .all(&self.connection)
+            .await?;
+
+        Ok(lorebooks)
+    }
+
+    pub async fn delete_lorebook(&self, lorebook_id: i32) -> Result<()> {
+        lorebook_entries::Entity::delete_many()
+            .filter(lorebook_entries::Column::LorebookId.eq(lorebook_id))
+            .exec(&self.connection)
+            .await?;
+
+        lorebooks::Entity::delete_by_id(lorebook_id)
+            .exec(&self.connection)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn delete_role_lorebooks(&self, role_id: i32) -> Result<()> {
+        let lorebooks = lorebooks::Entity::find()
+            .filter(lorebooks::Column::RoleId.eq(role_id))
+            .all(&self.connection)
+            .await?;
+
+        for lorebook in lorebooks {
+            self.delete_lorebook(lorebook.id).await?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn list_lorebook_entries(
+        &self,
+        lorebook_id: i32,
+    ) -> Result<Vec<lorebook_entries::Model>> {
+        let entries = lorebook_entries::Entity::find()
+            .filter(lorebook_entries::Column::LorebookId.eq(lorebook_id))
+            .order_by_desc(lorebook_entries::Column::Priority)
+            .order_by_asc(lorebook_entries::Column::Id)
+            .all(&self.connection)
+            .await?;
+
+        Ok(entries)
+    }
+
+    pub async fn get_lorebook_entry(&self, entry_id: i32) -> Result<lorebook_entries::Model> {
+        let entry = lorebook_entries::Entity::find_by_id(entry_id)
+            .one(&self.connection)
+            .await?;
+
+        entry.ok_or_else(|| anyhow::anyhow!("Lorebook entry not found"))
+    }
+
+    // entry_id为空时新增条目，否则更新条目
+    pub async fn save_lorebook_entry(
+        &self,
+        entry_id: Option<i32>,
+        lorebook_id: i32,
+        keywords: &[String],
+        content: &str,
+        priority: i32,
+        position: lorebook_entries::Position,
+        enabled: bool,
+    ) -> Result<i32> {
+        let mut entry = lorebook_entries::ActiveModel {
+            id: ActiveValue::default(),
+            lorebook_id: Set(lorebook_id),
+            keywords: Set(keywords.join("\n")),
+            content: Set(content.to_string()),
+            priority: Set(priority),
+            position: Set(position),
+            enabled: Set(enabled),
+        };
+
+        match entry_id {
+            Some(entry_id) => {
+                entry.id = Set(entry_id);
+                lorebook_entries::Entity::update(entry)
+                    .exec(&self.connection)
+                    .await?;
+
+                Ok(entry_id)
+            }
+            None => {
+                let res = lorebook_entries::Entity::insert(entry)
+                    .exec(&self.connection)
+                    .await?;
+
+                Ok(res.last_insert_id)
+            }
+        }
+    }
+
+    pub async fn delete_lorebook_entry(&self, entry_id: i32) -> Result<()> {
+        lorebook_entries::Entity::delete_by_id(entry_id)
+            .exec(&self.connection)
+            .await?;
+
+        Ok(())
+    }
+
+    // 获取对话生效的世界书：角色上的和对话上的
+    pub async fn get_active_lorebooks(
+        &self,
+        role_id: i32,
+        conversation_id: i32,
+    ) -> Result<Vec<Lorebook>> {
+        let lorebooks = lorebooks::Entity::find()
+            .filter(
+                Condition::any()
+                    .add(lorebooks::Column::RoleId.eq(role_id))
+                    .add(lorebooks::Column::ConversationId.eq(conversation_id)),
+            )
+            .order_by_asc(lorebooks::Column::Id)
+            .all(&self.connection)
+            .await?;
+
+        let mut active = Vec::new();
+
+        for lorebook in lorebooks {
+            active.push(Lorebook {
+                token_budget: lorebook.token_budget,
+                entries: self.list_lorebook_entries(lorebook.id).await?,
+            });
+        }
+
+        Ok(active)
+    }
+}
@@ -1,17 +1,46 @@
+mod activity;
+mod admin;
 pub mod init;
+mod lorebook;
 pub mod models;
-
+mod moderation;
+mod role_batch;
+mod role_embedding;
+mod role_fork;
+mod role_job;
+mod role_review;
+mod role_search;
+mod role_stats;
+mod role_tag;
+mod role_version;
+
+use crate::{
+    search::{RoleFilter, SearchIndex},
+    stats::Usage,
+};
 use anyhow::Result;
 use chrono::Utc;
-use models::roles::{AgeGroup, Column, Entity, Gender};
+use models::roles::{AgeGroup, Column, Entity, ExampleDialogue, Gender, RoleIdentity, Visibility};
+use models::users::UserRole;
 use sea_orm::{
     ActiveValue::{self, Set},
-    ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, QueryFilter,
+    ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryResult,
+    QuerySelect, TransactionTrait,
     prelude::Expr,
 };
+use tokio::sync::broadcast;
 use uuid::Uuid;
 
 const DB_NAME: &str = "role-play-ai";
+// 未被订阅者取走的角色事件最多保留这么多条
+const ROLE_EVENT_CAPACITY: usize = 256;
+
+// 角色创建、修改（包括标签）和删除时通知订阅者，如同步角色向量
+#[derive(Clone, Copy, Debug)]
+pub enum RoleEvent {
+    Saved(i32),
+    Deleted(i32),
+}
 
 pub struct PaginatedResult<T> {
     pub items: Vec<T>,
@@ -21,6 +50,8 @@ pub struct PaginatedResult<T> {
 
 pub struct Database {
     connection: DatabaseConnection,
+    search_index: SearchIndex,
+    role_events: broadcast::Sender<RoleEvent>,
 }
 
 impl Database {
@@ -37,7 +68,20 @@ impl Database {
         ))
         .await?;
 
-        Ok(Self { connection })
+        Ok(Self {
+            connection,
+            search_index: SearchIndex::new(),
+            role_events: broadcast::channel(ROLE_EVENT_CAPACITY).0,
+        })
+    }
+
+    pub fn subscribe_role_events(&self) -> broadcast::Receiver<RoleEvent> {
+        self.role_events.subscribe()
+    }
+
+    // 没有订阅者时发送失败，忽略即可
+    fn notify_role_event(&self, event: RoleEvent) {
+        let _ = self.role_events.send(event);
     }
 
     pub async fn add_user(&self, username: &str, password_hash: &str, image: &str) -> Result<i32> {
@@ -49,6 +93,8 @@ impl Database {
             password_hash: Set(password_hash.to_string()),
             image: Set(image.to_string()),
             jwt_secret: Set(jwt_secret),
+            role: Set(UserRole::User),
+            suspended_until: Set(None),
         };
 
         let res = models::users::Entity::insert(user)
@@ -78,7 +124,15 @@ impl Database {
         user.ok_or_else(|| anyhow::anyhow!("User not found"))
     }
 
-    pub async fn create_conversation_table(&self, user_id: i32, role_id: i32) -> Result<()> {
+    pub async fn get_user_by_id(&self, user_id: i32) -> Result<models::users::Model> {
+        let user = models::users::Entity::find_by_id(user_id)
+            .one(&self.connection)
+            .await?;
+
+        user.ok_or_else(|| anyhow::anyhow!("User not found"))
+    }
+
+    pub async fn create_conversation_table(&self, user_id: i32, role_id: i32) -> Result<i32> {
         let table_name = format!("conv_{}_{}", user_id, role_id);
         let sql = format!(
             "CREATE TABLE IF NOT EXISTS `{}` LIKE `conversation_template`",
@@ -92,14 +146,8 @@ impl Database {
             ))
             .await?;
 
-        let existing = models::conversations::Entity::find()
-            .filter(models::conversations::Column::UserId.eq(user_id))
-            .filter(models::conversations::Column::RoleId.eq(role_id))
-            .one(&self.connection)
-            .await?;
-
-        if existing.is_some() {
-            return Ok(());
+        if let Some(existing) = self.get_conversation(user_id, role_id).await? {
+            return Ok(existing.id);
         }
 
         let conversation = models::conversations::ActiveModel {
@@ -108,15 +156,182 @@ impl Database {
             role_id: Set(role_id),
             last_dialog_timestamp: Set(Utc::now().timestamp()),
             history: Set(String::new()),
+            table_name: Set(table_name),
+            parent_id: Set(None),
+            fork_dialog_id: Set(None),
+        };
+
+        let res = models::conversations::Entity::insert(conversation)
+            .exec(&self.connection)
+            .await?;
+
+        self.record_role_usage(
+            role_id,
+            user_id,
+            Usage {
+                conversations: 1,
+                ..Default::default()
+            },
+        )
+        .await?;
+
+        Ok(res.last_insert_id)
+    }
+
+    pub async fn fork_conversation(&self, conversation_id: i32, dialog_id: i32) -> Result<i32> {
+        let conversation = self
+            .get_conversation_by_id(conversation_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Conversation not found"))?;
+
+        let sql = format!(
+            "SELECT timestamp FROM `{}` WHERE id = ?",
+            conversation.table_name
+        );
+
+        let fork_timestamp: i64 = self
+            .connection
+            .query_one(sea_orm::Statement::from_sql_and_values(
+                self.connection.get_database_backend(),
+                sql,
+                vec![sea_orm::Value::from(dialog_id)],
+            ))
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Dialog not found"))?
+            .try_get("", "timestamp")?;
+
+        // 分支使用带UUID的唯一表名
+        let uuid = Uuid::new_v4().to_string().replace('-', "");
+        let table_name = format!(
+            "conv_{}_{}_{}",
+            conversation.user_id, conversation.role_id, uuid
+        );
+
+        let sql = format!(
+            "CREATE TABLE IF NOT EXISTS `{}` LIKE `conversation_template`",
+            table_name
+        );
+
+        self.connection
+            .execute(sea_orm::Statement::from_string(
+                self.connection.get_database_backend(),
+                sql,
+            ))
+            .await?;
+
+        // 复制分叉点及之前的对话
+        let sql = format!(
+            "INSERT INTO `{}` (is_user, timestamp, text, voice, role_version) SELECT is_user, timestamp, text, voice, role_version FROM `{}` WHERE id <= ? ORDER BY id ASC",
+            table_name, conversation.table_name
+        );
+
+        self.connection
+            .execute(sea_orm::Statement::from_sql_and_values(
+                self.connection.get_database_backend(),
+                sql,
+                vec![sea_orm::Value::from(dialog_id)],
+            ))
+            .await?;
+
+        let branch = models::conversations::ActiveModel {
+            id: ActiveValue::default(),
+            user_id: Set(conversation.user_id),
+            role_id: Set(conversation.role_id),
+            last_dialog_timestamp: Set(fork_timestamp),
+            history: Set(String::new()),
+            table_name: Set(table_name),
+            parent_id: Set(Some(conversation.id)),
+            fork_dialog_id: Set(Some(dialog_id)),
         };
 
-        models::conversations::Entity::insert(conversation)
+        let res = models::conversations::Entity::insert(branch)
             .exec(&self.connection)
             .await?;
 
+        Ok(res.last_insert_id)
+    }
+
+    // 批量导入对话记录，要求对话为空以保证顺序
+    pub async fn import_dialogs_by_id(
+        &self,
+        conversation_id: i32,
+        dialogs: &[(bool, i64, String)],
+    ) -> Result<()> {
+        // 整段对话一次写入，中途失败不会留下半截记录；锁住对话行，并发导入时只有一个能通过空对话检查
+        let txn = self.connection.begin().await?;
+
+        let table_name = models::conversations::Entity::find_by_id(conversation_id)
+            .lock_exclusive()
+            .one(&txn)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Conversation not found"))?
+            .table_name;
+
+        let count: i64 = txn
+            .query_one(sea_orm::Statement::from_string(
+                self.connection.get_database_backend(),
+                format!("SELECT COUNT(*) as count FROM `{}`", table_name),
+            ))
+            .await?
+            .and_then(|row| row.try_get("", "count").ok())
+            .unwrap_or(0);
+
+        if count > 0 {
+            return Err(anyhow::anyhow!("Conversation is not empty"));
+        }
+
+        let sql = format!(
+            "INSERT INTO `{}` (is_user, timestamp, text, voice) VALUES (?, ?, ?, ?)",
+            table_name
+        );
+
+        for (is_user, timestamp, text) in dialogs {
+            txn.execute(sea_orm::Statement::from_sql_and_values(
+                self.connection.get_database_backend(),
+                &sql,
+                vec![
+                    sea_orm::Value::from(*is_user),
+                    sea_orm::Value::from(*timestamp),
+                    sea_orm::Value::from(text.as_str()),
+                    sea_orm::Value::from(None::<String>),
+                ],
+            ))
+            .await?;
+        }
+
+        if let Some((_, timestamp, _)) = dialogs.last() {
+            models::conversations::Entity::update_many()
+                .col_expr(
+                    models::conversations::Column::LastDialogTimestamp,
+                    Expr::value(*timestamp),
+                )
+                .filter(models::conversations::Column::Id.eq(conversation_id))
+                .exec(&txn)
+                .await?;
+        }
+
+        txn.commit().await?;
+
         Ok(())
     }
 
+    pub async fn list_conversation_branches(
+        &self,
+        user_id: i32,
+        role_id: i32,
+    ) -> Result<Vec<models::conversations::Model>> {
+        use sea_orm::QueryOrder;
+
+        let conversations = models::conversations::Entity::find()
+            .filter(models::conversations::Column::UserId.eq(user_id))
+            .filter(models::conversations::Column::RoleId.eq(role_id))
+            .order_by_asc(models::conversations::Column::Id)
+            .all(&self.connection)
+            .await?;
+
+        Ok(conversations)
+    }
+
     pub async fn list_conversations_paginated(
         &self,
         user_id: i32,
@@ -127,6 +342,7 @@ impl Database {
 
         let paginator = models::conversations::Entity::find()
             .filter(models::conversations::Column::UserId.eq(user_id))
+            .filter(models::conversations::Column::ParentId.is_null())
             .order_by_desc(models::conversations::Column::LastDialogTimestamp)
             .paginate(&self.connection, limit as u64);
 
@@ -146,8 +362,7 @@ impl Database {
 
     pub async fn update_conversation_last_dialog_timestamp(
         &self,
-        user_id: i32,
-        role_id: i32,
+        conversation_id: i32,
         timestamp: i64,
     ) -> Result<()> {
         models::conversations::Entity::update_many()
@@ -155,22 +370,20 @@ impl Database {
                 models::conversations::Column::LastDialogTimestamp,
                 Expr::value(timestamp),
             )
-            .filter(models::conversations::Column::UserId.eq(user_id))
-            .filter(models::conversations::Column::RoleId.eq(role_id))
+            .filter(models::conversations::Column::Id.eq(conversation_id))
             .exec(&self.connection)
             .await?;
 
         Ok(())
     }
 
-    pub async fn list_dialogs_paginated(
+    pub async fn list_dialogs_paginated_by_id(
         &self,
-        user_id: i32,
-        role_id: i32,
+        conversation_id: i32,
         offset: i64,
         limit: i64,
     ) -> Result<PaginatedResult<models::conversation_template::Model>> {
-        let table_name = format!("conv_{}_{}", user_id, role_id);
+        let table_name = self.get_conversation_table_name(conversation_id).await?;
 
         let count_sql = format!("SELECT COUNT(*) as total FROM `{}`", table_name);
         let count_res = self
@@ -200,13 +413,7 @@ impl Database {
 
         let dialogs = res
             .into_iter()
-            .map(|row| models::conversation_template::Model {
-                id: row.try_get("", "id").unwrap_or(0),
-                is_user: row.try_get("", "is_user").unwrap_or(false),
-                timestamp: row.try_get("", "timestamp").unwrap_or(0),
-                text: row.try_get("", "text").unwrap_or_default(),
-                voice: row.try_get("", "voice").ok(),
-            })
+            .map(dialog_from_row)
             .collect::<Vec<models::conversation_template::Model>>();
 
         let has_more = (offset + limit) < total;
@@ -218,18 +425,18 @@ impl Database {
         })
     }
 
-    pub async fn add_dialog(
+    pub async fn add_dialog_by_id(
         &self,
-        user_id: i32,
-        role_id: i32,
+        conversation_id: i32,
         is_user: bool,
         timestamp: i64,
         text: &str,
         voice: Option<String>,
+        role_version: Option<i32>,
     ) -> Result<i32> {
-        let table_name = format!("conv_{}_{}", user_id, role_id);
+        let table_name = self.get_conversation_table_name(conversation_id).await?;
         let sql = format!(
-            "INSERT INTO `{}` (is_user, timestamp, text, voice) VALUES (?, ?, ?, ?)",
+            "INSERT INTO `{}` (is_user, timestamp, text, voice, role_version) VALUES (?, ?, ?, ?, ?)",
             table_name
         );
 
@@ -243,13 +450,30 @@ impl Database {
                     sea_orm::Value::from(timestamp),
                     sea_orm::Value::from(text),
                     sea_orm::Value::from(voice),
+                    sea_orm::Value::from(role_version),
                 ],
             ))
             .await?;
 
-        self.update_conversation_last_dialog_timestamp(user_id, role_id, timestamp)
+        self.update_conversation_last_dialog_timestamp(conversation_id, timestamp)
             .await?;
 
+        // 统计失败不影响消息入库
+        if let Some(conversation) = self.get_conversation_by_id(conversation_id).await?
+            && let Err(e) = self
+                .record_role_usage(
+                    conversation.role_id,
+                    conversation.user_id,
+                    Usage {
+                        messages: 1,
+                        ..Default::default()
+                    },
+                )
+                .await
+        {
+            tracing::warn!("Failed to record role usage: {}", e);
+        }
+
         Ok(res.last_insert_id() as i32)
     }
 
@@ -263,6 +487,9 @@ impl Database {
         gender: Gender,
         age_group: AgeGroup,
         voice_type: &str,
+        example_dialogues: &[ExampleDialogue],
+        greetings: &[String],
+        visibility: Visibility,
     ) -> Result<i32> {
         let role = models::roles::ActiveModel {
             id: ActiveValue::default(),
@@ -274,12 +501,28 @@ impl Database {
             gender: Set(gender),
             age_group: Set(age_group),
             voice_type: Set(voice_type.to_string()),
+            version: Set(1),
+            image_attribution: Set(None),
+            example_dialogues: Set(Some(ExampleDialogue::to_json(example_dialogues))),
+            greetings: Set(Some(serde_json::to_string(greetings)?)),
+            canonical_title: Set(None),
+            wiki_lang: Set(None),
+            wiki_page_id: Set(None),
+            visibility: Set(visibility),
+            category: Set(None),
+            parent_id: Set(None),
+            allow_fork: Set(true),
+            hidden: Set(false),
         };
 
         let res = models::roles::Entity::insert(role)
             .exec(&self.connection)
             .await?;
 
+        let role = self.get_role(res.last_insert_id).await?;
+        self.add_role_version(&self.connection, &role).await?;
+        self.index_role(role.id).await?;
+
         Ok(res.last_insert_id)
     }
 
@@ -291,14 +534,110 @@ impl Database {
         role.ok_or_else(|| anyhow::anyhow!("Role not found"))
     }
 
+    // 署名不属于角色设定，修改时不产生新版本
+    pub async fn set_role_image_attribution(
+        &self,
+        role_id: i32,
+        image_attribution: Option<&str>,
+    ) -> Result<()> {
+        models::roles::Entity::update_many()
+            .col_expr(
+                models::roles::Column::ImageAttribution,
+                Expr::value(image_attribution.map(str::to_string)),
+            )
+            .filter(models::roles::Column::Id.eq(role_id))
+            .exec(&self.connection)
+            .await?;
+
+        Ok(())
+    }
+
+    // 可见性不属于角色设定，修改时不产生新版本
+    pub async fn set_role_visibility(&self, role_id: i32, visibility: Visibility) -> Result<()> {
+        Entity::update_many()
+            .col_expr(Column::Visibility, Expr::value(visibility))
+            .filter(Column::Id.eq(role_id))
+            .exec(&self.connection)
+            .await?;
+
+        Ok(())
+    }
+
+    // 身份由生成角色时查到的维基条目决定，修改时不产生新版本
+    pub async fn set_role_identity(&self, role_id: i32, identity: &RoleIdentity) -> Result<()> {
+        models::roles::Entity::update_many()
+            .col_expr(
+                models::roles::Column::CanonicalTitle,
+                Expr::value(identity.canonical_title.clone()),
+            )
+            .col_expr(
+                models::roles::Column::WikiLang,
+                Expr::value(identity.wiki_lang.clone()),
+            )
+            .col_expr(
+                models::roles::Column::WikiPageId,
+                Expr::value(identity.wiki_page_id),
+            )
+            .filter(models::roles::Column::Id.eq(role_id))
+            .exec(&self.connection)
+            .await?;
+
+        // 规范标题也参与搜索
+        self.index_role(role_id).await?;
+
+        Ok(())
+    }
+
+    // 同一维基条目或同一规范标题的角色
+    pub async fn find_roles_by_identity(
+        &self,
+        identity: &RoleIdentity,
+    ) -> Result<Vec<models::roles::Model>> {
+        use sea_orm::{Condition, QueryOrder};
+
+        let mut condition = Condition::any();
+
+        if let (Some(lang), Some(page_id)) = (&identity.wiki_lang, identity.wiki_page_id) {
+            condition = condition.add(
+                Condition::all()
+                    .add(Column::WikiLang.eq(lang))
+                    .add(Column::WikiPageId.eq(page_id)),
+            );
+        }
+
+        if let Some(title) = identity
+            .canonical_title
+            .as_deref()
+            .map(str::trim)
+            .filter(|title| !title.is_empty())
+        {
+            condition = condition.add(Column::CanonicalTitle.eq(title));
+        }
+
+        if condition.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let roles = Entity::find()
+            .filter(condition)
+            .order_by_asc(Column::Id)
+            .all(&self.connection)
+            .await?;
+
+        Ok(roles)
+    }
+
     pub async fn list_roles_paginated(
         &self,
         offset: i64,
         limit: i64,
+        filter: &RoleFilter,
     ) -> Result<PaginatedResult<models::roles::Model>> {
         use sea_orm::{PaginatorTrait, QueryOrder};
 
+        // 只列出公开角色
         let paginator = models::roles::Entity::find()
+            .filter(filter.condition())
             .order_by_asc(models::roles::Column::Id)
             .paginate(&self.connection, limit as u64);
 
@@ -316,32 +655,20 @@ impl Database {
         })
     }
 
-    pub async fn search_roles(&self, keyword: &str) -> Result<Vec<models::roles::Model>> {
-        let keyword = format!("%{}%", keyword);
-
-        let roles = Entity::find()
-            .filter(
-                Expr::col(Column::Name)
-                    .like(&keyword)
-                    .or(Expr::col(Column::Description).like(&keyword))
-                    .or(Expr::col(Column::Traits).like(&keyword)),
-            )
-            .all(&self.connection)
-            .await?;
-
-        Ok(roles)
-    }
-
     pub async fn delete_conversation(&self, user_id: i32, role_id: i32) -> Result<()> {
-        let table_name = format!("conv_{}_{}", user_id, role_id);
-        let drop_sql = format!("DROP TABLE IF EXISTS `{}`", table_name);
+        // 同时删除该对话的所有分支
+        let conversations = self.list_conversation_branches(user_id, role_id).await?;
 
-        self.connection
-            .execute(sea_orm::Statement::from_string(
-                self.connection.get_database_backend(),
-                drop_sql,
-            ))
-            .await?;
+        for conv in conversations {
+            let drop_sql = format!("DROP TABLE IF EXISTS `{}`", conv.table_name);
+
+            self.connection
+                .execute(sea_orm::Statement::from_string(
+                    self.connection.get_database_backend(),
+                    drop_sql,
+                ))
+                .await?;
+        }
 
         models::conversations::Entity::delete_many()
             .filter(models::conversations::Column::UserId.eq(user_id))
@@ -352,8 +679,8 @@ impl Database {
         Ok(())
     }
 
-    pub async fn get_dialog_count(&self, user_id: i32, role_id: i32) -> Result<i64> {
-        let table_name = format!("conv_{}_{}", user_id, role_id);
+    pub async fn get_dialog_count_by_id(&self, conversation_id: i32) -> Result<i64> {
+        let table_name = self.get_conversation_table_name(conversation_id).await?;
         let sql = format!("SELECT COUNT(*) as count FROM `{}`", table_name);
 
         let res = self
@@ -367,13 +694,12 @@ impl Database {
         Ok(res.unwrap().try_get("", "count").unwrap_or(0))
     }
 
-    pub async fn get_recent_dialogs(
+    pub async fn get_recent_dialogs_by_id(
         &self,
-        user_id: i32,
-        role_id: i32,
+        conversation_id: i32,
         limit: i64,
     ) -> Result<Vec<models::conversation_template::Model>> {
-        let table_name = format!("conv_{}_{}", user_id, role_id);
+        let table_name = self.get_conversation_table_name(conversation_id).await?;
         let sql = format!(
             "SELECT * FROM `{}` ORDER BY timestamp DESC LIMIT {}",
             table_name, limit
@@ -389,19 +715,37 @@ impl Database {
 
         let mut dialogs = res
             .into_iter()
-            .map(|row| models::conversation_template::Model {
-                id: row.try_get("", "id").unwrap_or(0),
-                is_user: row.try_get("", "is_user").unwrap_or(false),
-                timestamp: row.try_get("", "timestamp").unwrap_or(0),
-                text: row.try_get("", "text").unwrap_or_default(),
-                voice: row.try_get("", "voice").ok(),
-            })
+            .map(dialog_from_row)
             .collect::<Vec<models::conversation_template::Model>>();
 
         dialogs.reverse();
         Ok(dialogs)
     }
 
+    pub async fn get_dialogs_by_id(
+        &self,
+        conversation_id: i32,
+    ) -> Result<Vec<models::conversation_template::Model>> {
+        let table_name = self.get_conversation_table_name(conversation_id).await?;
+        let sql = format!("SELECT * FROM `{}` ORDER BY id ASC", table_name);
+
+        let res = self
+            .connection
+            .query_all(sea_orm::Statement::from_string(
+                self.connection.get_database_backend(),
+                sql,
+            ))
+            .await?;
+
+        let dialogs = res
+            .into_iter()
+            .map(dialog_from_row)
+            .collect::<Vec<models::conversation_template::Model>>();
+
+        Ok(dialogs)
+    }
+
+    // 获取用户与角色的主对话（非分支）
     pub async fn get_conversation(
         &self,
         user_id: i32,
@@ -410,16 +754,56 @@ impl Database {
         let conversation = models::conversations::Entity::find()
             .filter(models::conversations::Column::UserId.eq(user_id))
             .filter(models::conversations::Column::RoleId.eq(role_id))
+            .filter(models::conversations::Column::ParentId.is_null())
             .one(&self.connection)
             .await?;
 
         Ok(conversation)
     }
 
-    pub async fn update_conversation_history(
+    pub async fn get_conversation_by_id(
+        &self,
+        conversation_id: i32,
+    ) -> Result<Option<models::conversations::Model>> {
+        let conversation = models::conversations::Entity::find_by_id(conversation_id)
+            .one(&self.connection)
+            .await?;
+
+        Ok(conversation)
+    }
+
+    // 优先使用conversation_id定位对话（可能是分支），否则使用主对话
+    pub async fn resolve_conversation(
         &self,
         user_id: i32,
         role_id: i32,
+        conversation_id: Option<i32>,
+    ) -> Result<models::conversations::Model> {
+        let conversation = match conversation_id {
+            Some(conversation_id) => self.get_conversation_by_id(conversation_id).await?,
+            None => self.get_conversation(user_id, role_id).await?,
+        }
+        .ok_or_else(|| anyhow::anyhow!("Conversation not found"))?;
+
+        if conversation.user_id != user_id || conversation.role_id != role_id {
+            return Err(anyhow::anyhow!("Permission denied"));
+        }
+
+        Ok(conversation)
+    }
+
+    async fn get_conversation_table_name(&self, conversation_id: i32) -> Result<String> {
+        let conversation = self
+            .get_conversation_by_id(conversation_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Conversation not found"))?;
+
+        Ok(conversation.table_name)
+    }
+
+    pub async fn update_conversation_history_by_id(
+        &self,
+        conversation_id: i32,
         history: &str,
     ) -> Result<()> {
         models::conversations::Entity::update_many()
@@ -427,21 +811,20 @@ impl Database {
                 models::conversations::Column::History,
                 Expr::value(history.to_string()),
             )
-            .filter(models::conversations::Column::UserId.eq(user_id))
-            .filter(models::conversations::Column::RoleId.eq(role_id))
+            .filter(models::conversations::Column::Id.eq(conversation_id))
             .exec(&self.connection)
             .await?;
         Ok(())
     }
 
-    pub async fn get_history(&self, user_id: i32, role_id: i32) -> Result<String> {
-        let conversation = self.get_conversation(user_id, role_id).await?;
+    pub async fn get_history_by_id(&self, conversation_id: i32) -> Result<String> {
+        let conversation = self.get_conversation_by_id(conversation_id).await?;
         let stored_history = conversation
             .map(|c| c.history)
             .filter(|h| !h.is_empty() && h != "无")
             .unwrap_or("无".to_string());
 
-        let recent_dialogs = self.get_recent_dialogs(user_id, role_id, 10).await?;
+        let recent_dialogs = self.get_recent_dialogs_by_id(conversation_id, 10).await?;
         let recent_history = {
             let recent_history = recent_dialogs
                 .into_iter()
@@ -501,8 +884,7 @@ impl Database {
         let mut deleted_count = 0u64;
 
         for conv in conversations {
-            let table_name = format!("conv_{}_{}", conv.user_id, conv.role_id);
-            let drop_sql = format!("DROP TABLE IF EXISTS `{}`", table_name);
+            let drop_sql = format!("DROP TABLE IF EXISTS `{}`", conv.table_name);
 
             self.connection
                 .execute(sea_orm::Statement::from_string(
@@ -529,8 +911,7 @@ impl Database {
             .await?;
 
         for conv in conversations {
-            let table_name = format!("conv_{}_{}", conv.user_id, conv.role_id);
-            let drop_sql = format!("DROP TABLE IF EXISTS `{}`", table_name);
+            let drop_sql = format!("DROP TABLE IF EXISTS `{}`", conv.table_name);
 
             self.connection
                 .execute(sea_orm::Statement::from_string(
@@ -545,13 +926,29 @@ impl Database {
             .exec(&self.connection)
             .await?;
 
+        self.delete_role_lorebooks(role_id).await?;
+        self.set_role_tags(role_id, &[]).await?;
+        self.delete_role_embedding(role_id).await?;
+        self.delete_role_stats(role_id).await?;
+        self.detach_role_forks(role_id).await?;
+        self.delete_role_reviews(role_id).await?;
+
         models::roles::Entity::delete_by_id(role_id)
             .exec(&self.connection)
             .await?;
 
+        self.search_index.remove(role_id);
+        self.notify_role_event(RoleEvent::Deleted(role_id));
+
         Ok(())
     }
 
+    pub async fn list_all_roles(&self) -> Result<Vec<models::roles::Model>> {
+        let roles = models::roles::Entity::find().all(&self.connection).await?;
+
+        Ok(roles)
+    }
+
     pub async fn get_user_roles(&self, user_id: i32) -> Result<Vec<models::roles::Model>> {
         let roles = models::roles::Entity::find()
             .filter(models::roles::Column::UserId.eq(user_id))
@@ -600,6 +997,18 @@ impl Database {
             .exec(&self.connection)
             .await?;
 
+        for role_id in [role1_id, role2_id] {
+            self.record_role_usage(
+                role_id,
+                user_id,
+                Usage {
+                    debates: 1,
+                    ..Default::default()
+                },
+            )
+            .await?;
+        }
+
         Ok(res.last_insert_id)
     }
 
@@ -954,6 +1363,17 @@ impl Database {
     }
 }
 
+fn dialog_from_row(row: QueryResult) -> models::conversation_template::Model {
+    models::conversation_template::Model {
+        id: row.try_get("", "id").unwrap_or(0),
+        is_user: row.try_get("", "is_user").unwrap_or(false),
+        timestamp: row.try_get("", "timestamp").unwrap_or(0),
+        text: row.try_get("", "text").unwrap_or_default(),
+        voice: row.try_get("", "voice").ok(),
+        role_version: row.try_get("", "role_version").unwrap_or(None),
+    }
+}
+
 fn generate_jwt_secret() -> String {
     use uuid::Uuid;
     format!(
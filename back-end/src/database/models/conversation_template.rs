@@ -13,6 +13,8 @@ pub struct Model {
     #[sea_orm(column_type = "Text")]
     pub text: String,
     pub voice: Option<String>,
+    // 生成回复时的角色版本，用户消息为空
+    pub role_version: Option<i32>,
 }
 
 #[derive(Clone, Debug, EnumIter, DeriveRelation)]
@@ -13,6 +13,9 @@ pub struct Model {
     pub last_dialog_timestamp: i64,
     #[sea_orm(column_type = "Text")]
     pub history: String,
+    pub table_name: String,
+    pub parent_id: Option<i32>,
+    pub fork_dialog_id: Option<i32>,
 }
 
 #[derive(Clone, Debug, EnumIter, DeriveRelation)]
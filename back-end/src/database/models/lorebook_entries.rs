@@ -0,0 +1,117 @@
+use std::fmt::Display;
+
+use sea_orm::{
+    ActiveModelBehavior, ColIdx, ColumnType, DbErr, DerivePrimaryKey, DeriveRelation, EnumIter,
+    PrimaryKeyTrait, QueryResult, TryGetError, TryGetable, Value,
+    entity::prelude::DeriveEntityModel,
+    prelude::StringLen,
+    sea_query::{ArrayType, ValueType, ValueTypeErr},
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, DeriveEntityModel)]
+#[sea_orm(table_name = "lorebook_entries")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub lorebook_id: i32,
+    // 触发关键词，每行一个
+    #[sea_orm(column_type = "Text")]
+    pub keywords: String,
+    #[sea_orm(column_type = "Text")]
+    pub content: String,
+    pub priority: i32,
+    pub position: Position,
+    pub enabled: bool,
+}
+
+#[derive(Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl Model {
+    pub fn keyword_list(&self) -> Vec<&str> {
+        self.keywords
+            .lines()
+            .map(|keyword| keyword.trim())
+            .filter(|keyword| !keyword.is_empty())
+            .collect()
+    }
+}
+
+// 条目插入位置：角色设定之前、之后，或记忆摘要之后
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub enum Position {
+    #[serde(rename = "before_role")]
+    BeforeRole,
+    #[serde(rename = "after_role")]
+    AfterRole,
+    #[serde(rename = "after_history")]
+    AfterHistory,
+}
+
+impl Position {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::BeforeRole => "before_role",
+            Self::AfterRole => "after_role",
+            Self::AfterHistory => "after_history",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "before_role" => Some(Self::BeforeRole),
+            "after_role" => Some(Self::AfterRole),
+            "after_history" => Some(Self::AfterHistory),
+            _ => None,
+        }
+    }
+}
+
+impl ValueType for Position {
+    fn try_from(v: Value) -> Result<Self, ValueTypeErr> {
+        match v {
+            Value::String(Some(value)) => Self::parse(&value).ok_or(ValueTypeErr),
+            _ => Err(ValueTypeErr),
+        }
+    }
+
+    fn type_name() -> String {
+        "Position".to_string()
+    }
+
+    fn array_type() -> ArrayType {
+        ArrayType::String
+    }
+
+    fn column_type() -> ColumnType {
+        ColumnType::String(StringLen::N(16))
+    }
+}
+
+impl From<Position> for Value {
+    fn from(value: Position) -> Self {
+        Self::String(Some(Box::new(value.as_str().to_string())))
+    }
+}
+
+impl TryGetable for Position {
+    fn try_get_by<I: ColIdx>(res: &QueryResult, index: I) -> Result<Self, TryGetError> {
+        let value: String = res.try_get_by(index)?;
+
+        Self::parse(&value).ok_or_else(|| {
+            TryGetError::DbErr(DbErr::Type(format!(
+                "position value should be one of before_role, after_role and after_history: {}",
+                value
+            )))
+        })
+    }
+}
+
+impl Display for Position {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
@@ -0,0 +1,22 @@
+use sea_orm::{
+    ActiveModelBehavior, DerivePrimaryKey, DeriveRelation, EnumIter, PrimaryKeyTrait,
+    entity::prelude::DeriveEntityModel,
+};
+
+// 世界书，挂在角色或对话上，二者只能有一个
+#[derive(Clone, Debug, DeriveEntityModel)]
+#[sea_orm(table_name = "lorebooks")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub user_id: i32,
+    pub name: String,
+    pub role_id: Option<i32>,
+    pub conversation_id: Option<i32>,
+    pub token_budget: i32,
+}
+
+#[derive(Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
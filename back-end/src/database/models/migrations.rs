@@ -0,0 +1,19 @@
+use sea_orm::{
+    ActiveModelBehavior, DerivePrimaryKey, DeriveRelation, EnumIter, PrimaryKeyTrait,
+    entity::prelude::DeriveEntityModel,
+};
+
+// 已执行的一次性迁移，执行完成后写入，避免每次启动重复执行
+#[derive(Clone, Debug, DeriveEntityModel)]
+#[sea_orm(table_name = "migrations")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub name: String,
+    // 毫秒
+    pub created_at: i64,
+}
+
+#[derive(Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -2,5 +2,20 @@ pub mod conversation_template;
 pub mod conversations;
 pub mod debate_template;
 pub mod debates;
+pub mod lorebook_entries;
+pub mod lorebooks;
+pub mod migrations;
+pub mod moderation_logs;
+pub mod reports;
+pub mod role_batch_items;
+pub mod role_batches;
+pub mod role_daily_stats;
+pub mod role_embeddings;
+pub mod role_jobs;
+pub mod role_reactions;
+pub mod role_reviews;
+pub mod role_tags;
+pub mod role_users;
+pub mod role_versions;
 pub mod roles;
 pub mod users;
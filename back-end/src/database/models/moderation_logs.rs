@@ -0,0 +1,143 @@
+use super::reports::TargetType;
+use sea_orm::{
+    ActiveModelBehavior, ColIdx, ColumnType, DbErr, DerivePrimaryKey, DeriveRelation, EnumIter,
+    PrimaryKeyTrait, QueryResult, TryGetError, TryGetable, Value,
+    entity::prelude::DeriveEntityModel,
+    prelude::StringLen,
+    sea_query::{ArrayType, ValueType, ValueTypeErr},
+};
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+
+// 系统自动执行的审核操作使用此审核员ID，如举报过多时自动隐藏评价
+pub const SYSTEM_MODERATOR_ID: i32 = 0;
+
+// 审核记录，每次审核操作一条，不修改不删除
+#[derive(Clone, Debug, DeriveEntityModel)]
+#[sea_orm(table_name = "moderation_logs")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    #[sea_orm(indexed)]
+    pub moderator_id: i32,
+    pub action: ModerationAction,
+    pub target_type: TargetType,
+    #[sea_orm(indexed)]
+    pub target_id: i32,
+    #[sea_orm(column_type = "Text")]
+    pub reason: String,
+    // 补充信息，如封禁到期时间
+    #[sea_orm(column_type = "Text", nullable)]
+    pub detail: Option<String>,
+    // 毫秒
+    pub created_at: i64,
+}
+
+#[derive(Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum ModerationAction {
+    // 以下用于角色、评价和对话
+    #[serde(rename = "hide")]
+    Hide,
+    #[serde(rename = "delete")]
+    Delete,
+    #[serde(rename = "restore")]
+    Restore,
+    // 举报不成立，不处理内容
+    #[serde(rename = "dismiss")]
+    Dismiss,
+    // 以下用于用户
+    #[serde(rename = "warn")]
+    Warn,
+    #[serde(rename = "suspend")]
+    Suspend,
+    #[serde(rename = "reactivate")]
+    Reactivate,
+    // 以下只有管理员可用
+    #[serde(rename = "change_role")]
+    ChangeRole,
+    // 把角色转给其他用户
+    #[serde(rename = "transfer")]
+    Transfer,
+}
+
+impl ModerationAction {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Hide => "hide",
+            Self::Delete => "delete",
+            Self::Restore => "restore",
+            Self::Dismiss => "dismiss",
+            Self::Warn => "warn",
+            Self::Suspend => "suspend",
+            Self::Reactivate => "reactivate",
+            Self::ChangeRole => "change_role",
+            Self::Transfer => "transfer",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "hide" => Some(Self::Hide),
+            "delete" => Some(Self::Delete),
+            "restore" => Some(Self::Restore),
+            "dismiss" => Some(Self::Dismiss),
+            "warn" => Some(Self::Warn),
+            "suspend" => Some(Self::Suspend),
+            "reactivate" => Some(Self::Reactivate),
+            "change_role" => Some(Self::ChangeRole),
+            "transfer" => Some(Self::Transfer),
+            _ => None,
+        }
+    }
+}
+
+impl ValueType for ModerationAction {
+    fn try_from(v: Value) -> Result<Self, ValueTypeErr> {
+        match v {
+            Value::String(Some(value)) => Self::parse(&value).ok_or(ValueTypeErr),
+            _ => Err(ValueTypeErr),
+        }
+    }
+
+    fn type_name() -> String {
+        "ModerationAction".to_string()
+    }
+
+    fn array_type() -> ArrayType {
+        ArrayType::String
+    }
+
+    fn column_type() -> ColumnType {
+        ColumnType::String(StringLen::N(16))
+    }
+}
+
+impl From<ModerationAction> for Value {
+    fn from(value: ModerationAction) -> Self {
+        Self::String(Some(Box::new(value.as_str().to_string())))
+    }
+}
+
+impl TryGetable for ModerationAction {
+    fn try_get_by<I: ColIdx>(res: &QueryResult, index: I) -> Result<Self, TryGetError> {
+        let value: String = res.try_get_by(index)?;
+
+        Self::parse(&value).ok_or_else(|| {
+            TryGetError::DbErr(DbErr::Type(format!(
+                "moderation action should be one of hide, delete, restore, dismiss, warn, suspend and reactivate: {}",
+                value
+            )))
+        })
+    }
+}
+
+impl Display for ModerationAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
@@ -0,0 +1,192 @@
+use sea_orm::{
+    ActiveModelBehavior, ColIdx, ColumnType, DbErr, DerivePrimaryKey, DeriveRelation, EnumIter,
+    PrimaryKeyTrait, QueryResult, TryGetError, TryGetable, Value,
+    entity::prelude::DeriveEntityModel,
+    prelude::StringLen,
+    sea_query::{ArrayType, ValueType, ValueTypeErr},
+};
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+
+// 用户对角色、评价或对话的举报
+#[derive(Clone, Debug, DeriveEntityModel)]
+#[sea_orm(table_name = "reports")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    #[sea_orm(indexed)]
+    pub reporter_id: i32,
+    pub target_type: TargetType,
+    #[sea_orm(indexed)]
+    pub target_id: i32,
+    #[sea_orm(column_type = "Text")]
+    pub reason: String,
+    pub status: ReportStatus,
+    // 毫秒
+    pub created_at: i64,
+    // 处理时间和处理人，未处理时为空
+    pub resolved_at: Option<i64>,
+    pub resolved_by: Option<i32>,
+}
+
+#[derive(Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum TargetType {
+    #[serde(rename = "role")]
+    Role,
+    #[serde(rename = "review")]
+    Review,
+    #[serde(rename = "conversation")]
+    Conversation,
+    // 只用于审核记录，不能举报用户
+    #[serde(rename = "user")]
+    User,
+}
+
+impl TargetType {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Role => "role",
+            Self::Review => "review",
+            Self::Conversation => "conversation",
+            Self::User => "user",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "role" => Some(Self::Role),
+            "review" => Some(Self::Review),
+            "conversation" => Some(Self::Conversation),
+            "user" => Some(Self::User),
+            _ => None,
+        }
+    }
+}
+
+impl ValueType for TargetType {
+    fn try_from(v: Value) -> Result<Self, ValueTypeErr> {
+        match v {
+            Value::String(Some(value)) => Self::parse(&value).ok_or(ValueTypeErr),
+            _ => Err(ValueTypeErr),
+        }
+    }
+
+    fn type_name() -> String {
+        "TargetType".to_string()
+    }
+
+    fn array_type() -> ArrayType {
+        ArrayType::String
+    }
+
+    fn column_type() -> ColumnType {
+        ColumnType::String(StringLen::N(16))
+    }
+}
+
+impl From<TargetType> for Value {
+    fn from(value: TargetType) -> Self {
+        Self::String(Some(Box::new(value.as_str().to_string())))
+    }
+}
+
+impl TryGetable for TargetType {
+    fn try_get_by<I: ColIdx>(res: &QueryResult, index: I) -> Result<Self, TryGetError> {
+        let value: String = res.try_get_by(index)?;
+
+        Self::parse(&value).ok_or_else(|| {
+            TryGetError::DbErr(DbErr::Type(format!(
+                "target type should be one of role, review, conversation and user: {}",
+                value
+            )))
+        })
+    }
+}
+
+impl Display for TargetType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum ReportStatus {
+    #[serde(rename = "pending")]
+    Pending,
+    // 已处理内容
+    #[serde(rename = "resolved")]
+    Resolved,
+    // 内容没有问题
+    #[serde(rename = "dismissed")]
+    Dismissed,
+}
+
+impl ReportStatus {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Resolved => "resolved",
+            Self::Dismissed => "dismissed",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "pending" => Some(Self::Pending),
+            "resolved" => Some(Self::Resolved),
+            "dismissed" => Some(Self::Dismissed),
+            _ => None,
+        }
+    }
+}
+
+impl ValueType for ReportStatus {
+    fn try_from(v: Value) -> Result<Self, ValueTypeErr> {
+        match v {
+            Value::String(Some(value)) => Self::parse(&value).ok_or(ValueTypeErr),
+            _ => Err(ValueTypeErr),
+        }
+    }
+
+    fn type_name() -> String {
+        "ReportStatus".to_string()
+    }
+
+    fn array_type() -> ArrayType {
+        ArrayType::String
+    }
+
+    fn column_type() -> ColumnType {
+        ColumnType::String(StringLen::N(16))
+    }
+}
+
+impl From<ReportStatus> for Value {
+    fn from(value: ReportStatus) -> Self {
+        Self::String(Some(Box::new(value.as_str().to_string())))
+    }
+}
+
+impl TryGetable for ReportStatus {
+    fn try_get_by<I: ColIdx>(res: &QueryResult, index: I) -> Result<Self, TryGetError> {
+        let value: String = res.try_get_by(index)?;
+
+        Self::parse(&value).ok_or_else(|| {
+            TryGetError::DbErr(DbErr::Type(format!(
+                "report status should be one of pending, resolved and dismissed: {}",
+                value
+            )))
+        })
+    }
+}
+
+impl Display for ReportStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
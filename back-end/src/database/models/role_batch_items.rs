@@ -0,0 +1,128 @@
+use std::fmt::Display;
+
+use super::roles::{AgeGroup, Gender};
+use sea_orm::{
+    ActiveModelBehavior, ColIdx, ColumnType, DbErr, DerivePrimaryKey, DeriveRelation, EnumIter,
+    PrimaryKeyTrait, QueryResult, TryGetError, TryGetable, Value,
+    entity::prelude::DeriveEntityModel,
+    prelude::StringLen,
+    sea_query::{ArrayType, ValueType, ValueTypeErr},
+};
+use serde::{Deserialize, Serialize};
+
+// 批量生成任务中的一个名字及其生成结果
+#[derive(Clone, Debug, DeriveEntityModel)]
+#[sea_orm(table_name = "role_batch_items")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub batch_id: i32,
+    // 在名单中的序号，从0开始
+    pub position: i32,
+    pub name: String,
+    pub gender: Option<Gender>,
+    pub age_group: Option<AgeGroup>,
+    // 标签，换行分隔
+    #[sea_orm(column_type = "Text", nullable)]
+    pub tags: Option<String>,
+    pub status: ItemStatus,
+    pub role_id: Option<i32>,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub error: Option<String>,
+    pub updated_at: i64,
+}
+
+#[derive(Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl Model {
+    pub fn tags(&self) -> Vec<String> {
+        self.tags
+            .as_deref()
+            .unwrap_or_default()
+            .lines()
+            .map(str::to_string)
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub enum ItemStatus {
+    #[serde(rename = "pending")]
+    Pending,
+    #[serde(rename = "created")]
+    Created,
+    #[serde(rename = "failed")]
+    Failed,
+    #[serde(rename = "duplicate")]
+    Duplicate,
+}
+
+impl ItemStatus {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Created => "created",
+            Self::Failed => "failed",
+            Self::Duplicate => "duplicate",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "pending" => Some(Self::Pending),
+            "created" => Some(Self::Created),
+            "failed" => Some(Self::Failed),
+            "duplicate" => Some(Self::Duplicate),
+            _ => None,
+        }
+    }
+}
+
+impl ValueType for ItemStatus {
+    fn try_from(v: Value) -> Result<Self, ValueTypeErr> {
+        match v {
+            Value::String(Some(value)) => Self::parse(&value).ok_or(ValueTypeErr),
+            _ => Err(ValueTypeErr),
+        }
+    }
+
+    fn type_name() -> String {
+        "ItemStatus".to_string()
+    }
+
+    fn array_type() -> ArrayType {
+        ArrayType::String
+    }
+
+    fn column_type() -> ColumnType {
+        ColumnType::String(StringLen::N(16))
+    }
+}
+
+impl From<ItemStatus> for Value {
+    fn from(value: ItemStatus) -> Self {
+        Self::String(Some(Box::new(value.as_str().to_string())))
+    }
+}
+
+impl TryGetable for ItemStatus {
+    fn try_get_by<I: ColIdx>(res: &QueryResult, index: I) -> Result<Self, TryGetError> {
+        let value: String = res.try_get_by(index)?;
+
+        Self::parse(&value).ok_or_else(|| {
+            TryGetError::DbErr(DbErr::Type(format!(
+                "status value should be one of pending, created, failed and duplicate: {}",
+                value
+            )))
+        })
+    }
+}
+
+impl Display for ItemStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
@@ -0,0 +1,25 @@
+use super::role_jobs::Status;
+use sea_orm::{
+    ActiveModelBehavior, DerivePrimaryKey, DeriveRelation, EnumIter, PrimaryKeyTrait,
+    entity::prelude::DeriveEntityModel,
+};
+
+// 按名单批量生成角色的任务，每个名字对应一个role_batch_items条目
+#[derive(Clone, Debug, DeriveEntityModel)]
+#[sea_orm(table_name = "role_batches")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub user_id: i32,
+    pub status: Status,
+    pub total: i32,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub error: Option<String>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+#[derive(Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
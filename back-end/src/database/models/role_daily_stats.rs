@@ -0,0 +1,26 @@
+use sea_orm::{
+    ActiveModelBehavior, DerivePrimaryKey, DeriveRelation, EnumIter, PrimaryKeyTrait,
+    entity::prelude::DeriveEntityModel,
+};
+
+// 角色每天的使用量，用于统计总数和计算热度
+#[derive(Clone, Debug, DeriveEntityModel)]
+#[sea_orm(table_name = "role_daily_stats")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub role_id: i32,
+    // 自1970-01-01起的天数（UTC）
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub day: i32,
+    // 新开始的对话数
+    pub conversations: i32,
+    // 对话中的消息数，包括用户和角色的发言
+    pub messages: i32,
+    // 参加的辩论数
+    pub debates: i32,
+}
+
+#[derive(Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,31 @@
+use sea_orm::{
+    ActiveModelBehavior, DerivePrimaryKey, DeriveRelation, EnumIter, PrimaryKeyTrait,
+    entity::prelude::DeriveEntityModel,
+};
+
+// 角色描述和特点的向量，用于语义搜索和相似角色推荐
+#[derive(Clone, Debug, DeriveEntityModel)]
+#[sea_orm(table_name = "role_embeddings")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub role_id: i32,
+    // 计算向量的模型
+    pub model: String,
+    // 计算时角色的版本号，角色修改后重新计算
+    pub version: i32,
+    // JSON格式
+    #[sea_orm(column_type = "Text")]
+    pub vector: String,
+    pub updated_at: i64,
+}
+
+#[derive(Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl Model {
+    pub fn vector(&self) -> Vec<f32> {
+        serde_json::from_str(&self.vector).unwrap_or_default()
+    }
+}
@@ -0,0 +1,154 @@
+use std::fmt::Display;
+
+use super::roles::{AgeGroup, Category, Gender};
+use sea_orm::{
+    ActiveModelBehavior, ColIdx, ColumnType, DbErr, DerivePrimaryKey, DeriveRelation, EnumIter,
+    PrimaryKeyTrait, QueryResult, TryGetError, TryGetable, Value,
+    entity::prelude::DeriveEntityModel,
+    prelude::StringLen,
+    sea_query::{ArrayType, ValueType, ValueTypeErr},
+};
+use serde::{Deserialize, Serialize};
+
+// 后台生成角色的任务，每完成一步保存一次中间结果，便于恢复
+#[derive(Clone, Debug, DeriveEntityModel)]
+#[sea_orm(table_name = "role_jobs")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub user_id: i32,
+    pub status: Status,
+    // 用户输入
+    pub name: String,
+    #[sea_orm(column_type = "Text")]
+    pub description: String,
+    #[sea_orm(column_type = "Text")]
+    pub traits: String,
+    pub gender: Option<Gender>,
+    pub age_group: Option<AgeGroup>,
+    // 中间结果
+    pub canonical_name: Option<String>,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub extract: Option<String>,
+    // 摘要所在的维基百科条目
+    pub wiki_lang: Option<String>,
+    pub wiki_page_id: Option<i64>,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub traits_draft: Option<String>,
+    pub voice_type: Option<String>,
+    // 维基语录中的语录，换行分隔
+    #[sea_orm(column_type = "Text", nullable)]
+    pub quotes: Option<String>,
+    pub image: Option<String>,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub image_attribution: Option<String>,
+    // 最终结果
+    #[sea_orm(column_type = "Text", nullable)]
+    pub result_description: Option<String>,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub result_traits: Option<String>,
+    pub result_gender: Option<Gender>,
+    pub result_age_group: Option<AgeGroup>,
+    // 示例对话，JSON格式
+    #[sea_orm(column_type = "Text", nullable)]
+    pub result_example_dialogues: Option<String>,
+    // 开场白，JSON格式
+    #[sea_orm(column_type = "Text", nullable)]
+    pub result_greetings: Option<String>,
+    pub result_category: Option<Category>,
+    // 建议的标签，换行分隔
+    #[sea_orm(column_type = "Text", nullable)]
+    pub result_tags: Option<String>,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub error: Option<String>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+#[derive(Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub enum Status {
+    #[serde(rename = "pending")]
+    Pending,
+    #[serde(rename = "running")]
+    Running,
+    #[serde(rename = "completed")]
+    Completed,
+    #[serde(rename = "failed")]
+    Failed,
+    #[serde(rename = "cancelled")]
+    Cancelled,
+}
+
+impl Status {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Running => "running",
+            Self::Completed => "completed",
+            Self::Failed => "failed",
+            Self::Cancelled => "cancelled",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "pending" => Some(Self::Pending),
+            "running" => Some(Self::Running),
+            "completed" => Some(Self::Completed),
+            "failed" => Some(Self::Failed),
+            "cancelled" => Some(Self::Cancelled),
+            _ => None,
+        }
+    }
+}
+
+impl ValueType for Status {
+    fn try_from(v: Value) -> Result<Self, ValueTypeErr> {
+        match v {
+            Value::String(Some(value)) => Self::parse(&value).ok_or(ValueTypeErr),
+            _ => Err(ValueTypeErr),
+        }
+    }
+
+    fn type_name() -> String {
+        "Status".to_string()
+    }
+
+    fn array_type() -> ArrayType {
+        ArrayType::String
+    }
+
+    fn column_type() -> ColumnType {
+        ColumnType::String(StringLen::N(16))
+    }
+}
+
+impl From<Status> for Value {
+    fn from(value: Status) -> Self {
+        Self::String(Some(Box::new(value.as_str().to_string())))
+    }
+}
+
+impl TryGetable for Status {
+    fn try_get_by<I: ColIdx>(res: &QueryResult, index: I) -> Result<Self, TryGetError> {
+        let value: String = res.try_get_by(index)?;
+
+        Self::parse(&value).ok_or_else(|| {
+            TryGetError::DbErr(DbErr::Type(format!(
+                "status value should be one of pending, running, completed, failed and cancelled: {}",
+                value
+            )))
+        })
+    }
+}
+
+impl Display for Status {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
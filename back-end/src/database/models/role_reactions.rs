@@ -0,0 +1,106 @@
+use sea_orm::{
+    ActiveModelBehavior, ColIdx, ColumnType, DbErr, DerivePrimaryKey, DeriveRelation, EnumIter,
+    PrimaryKeyTrait, QueryResult, TryFromU64, TryGetError, TryGetable, Value,
+    entity::prelude::DeriveEntityModel,
+    prelude::StringLen,
+    sea_query::{ArrayType, ValueType, ValueTypeErr},
+};
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+
+// 用户对角色的收藏和点赞，每个用户对每个角色每种最多一条
+#[derive(Clone, Debug, DeriveEntityModel)]
+#[sea_orm(table_name = "role_reactions")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub user_id: i32,
+    #[sea_orm(primary_key, auto_increment = false, indexed)]
+    pub role_id: i32,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub kind: ReactionKind,
+    // 毫秒
+    pub created_at: i64,
+}
+
+#[derive(Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum ReactionKind {
+    #[serde(rename = "favourite")]
+    Favourite,
+    #[serde(rename = "like")]
+    Like,
+}
+
+impl ReactionKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Favourite => "favourite",
+            Self::Like => "like",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "favourite" => Some(Self::Favourite),
+            "like" => Some(Self::Like),
+            _ => None,
+        }
+    }
+}
+
+impl ValueType for ReactionKind {
+    fn try_from(v: Value) -> Result<Self, ValueTypeErr> {
+        match v {
+            Value::String(Some(value)) => Self::parse(&value).ok_or(ValueTypeErr),
+            _ => Err(ValueTypeErr),
+        }
+    }
+
+    fn type_name() -> String {
+        "ReactionKind".to_string()
+    }
+
+    fn array_type() -> ArrayType {
+        ArrayType::String
+    }
+
+    fn column_type() -> ColumnType {
+        ColumnType::String(StringLen::N(16))
+    }
+}
+
+impl From<ReactionKind> for Value {
+    fn from(value: ReactionKind) -> Self {
+        Self::String(Some(Box::new(value.as_str().to_string())))
+    }
+}
+
+impl TryGetable for ReactionKind {
+    fn try_get_by<I: ColIdx>(res: &QueryResult, index: I) -> Result<Self, TryGetError> {
+        let value: String = res.try_get_by(index)?;
+
+        Self::parse(&value).ok_or_else(|| {
+            TryGetError::DbErr(DbErr::Type(format!(
+                "reaction kind should be one of favourite and like: {}",
+                value
+            )))
+        })
+    }
+}
+
+// 作为主键的一部分时需要，但不会由自增ID生成
+impl TryFromU64 for ReactionKind {
+    fn try_from_u64(_: u64) -> Result<Self, DbErr> {
+        Err(DbErr::ConvertFromU64("ReactionKind"))
+    }
+}
+
+impl Display for ReactionKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
@@ -0,0 +1,35 @@
+use sea_orm::{
+    ActiveModelBehavior, DerivePrimaryKey, DeriveRelation, EnumIter, PrimaryKeyTrait,
+    entity::prelude::DeriveEntityModel,
+};
+
+// 用户对角色的评分和评价，每个用户对每个角色最多一条，再次评价时覆盖
+#[derive(Clone, Debug, DeriveEntityModel)]
+#[sea_orm(table_name = "role_reviews")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    #[sea_orm(indexed)]
+    pub role_id: i32,
+    #[sea_orm(indexed)]
+    pub user_id: i32,
+    // 1~5
+    pub rating: i32,
+    #[sea_orm(column_type = "Text")]
+    pub content: String,
+    // 角色作者的回复
+    #[sea_orm(column_type = "Text", nullable)]
+    pub reply: Option<String>,
+    pub replied_at: Option<i64>,
+    // 被多人举报后隐藏，不计入评分
+    #[sea_orm(default_value = false)]
+    pub hidden: bool,
+    // 毫秒
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+#[derive(Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,21 @@
+use sea_orm::{
+    ActiveModelBehavior, DerivePrimaryKey, DeriveRelation, EnumIter, PrimaryKeyTrait,
+    entity::prelude::DeriveEntityModel,
+};
+
+// 角色的标签，每个标签一行，便于按标签筛选和统计
+#[derive(Clone, Debug, DeriveEntityModel)]
+#[sea_orm(table_name = "role_tags")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    #[sea_orm(indexed)]
+    pub role_id: i32,
+    #[sea_orm(indexed)]
+    pub tag: String,
+}
+
+#[derive(Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
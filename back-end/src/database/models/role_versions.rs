@@ -0,0 +1,44 @@
+use super::roles::{AgeGroup, ExampleDialogue, Gender, greetings_from_json};
+use sea_orm::{
+    ActiveModelBehavior, DerivePrimaryKey, DeriveRelation, EnumIter, PrimaryKeyTrait,
+    entity::prelude::DeriveEntityModel,
+};
+
+// 角色每个版本的完整定义，包括当前版本
+#[derive(Clone, Debug, DeriveEntityModel)]
+#[sea_orm(table_name = "role_versions")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub role_id: i32,
+    pub version: i32,
+    pub name: String,
+    #[sea_orm(column_type = "Text")]
+    pub description: String,
+    #[sea_orm(column_type = "Text")]
+    pub traits: String,
+    pub image: String,
+    pub gender: Gender,
+    pub age_group: AgeGroup,
+    pub voice_type: String,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub example_dialogues: Option<String>,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub greetings: Option<String>,
+    pub created_at: i64,
+}
+
+#[derive(Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl Model {
+    pub fn example_dialogues(&self) -> Vec<ExampleDialogue> {
+        ExampleDialogue::from_json(self.example_dialogues.as_deref())
+    }
+
+    pub fn greetings(&self) -> Vec<String> {
+        greetings_from_json(self.greetings.as_deref())
+    }
+}
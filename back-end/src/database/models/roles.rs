@@ -1,11 +1,12 @@
 use std::fmt::Display;
 
+use crate::lorebook::estimate_tokens;
 use sea_orm::{
     ActiveModelBehavior, ColIdx, ColumnType, DbErr, DerivePrimaryKey, DeriveRelation, EnumIter,
     PrimaryKeyTrait, QueryResult, TryGetError, TryGetable, Value,
     entity::prelude::DeriveEntityModel,
     prelude::StringLen,
-    sea_query::{ArrayType, ValueType, ValueTypeErr},
+    sea_query::{ArrayType, Nullable, ValueType, ValueTypeErr},
 };
 use serde::{Deserialize, Serialize};
 
@@ -24,6 +25,61 @@ pub struct Model {
     pub gender: Gender,
     pub age_group: AgeGroup,
     pub voice_type: String,
+    // 当前版本号，每次修改加一
+    #[sea_orm(default_value = 1)]
+    pub version: i32,
+    // 头像取自维基共享资源时的署名和许可协议，JSON格式
+    #[sea_orm(column_type = "Text", nullable)]
+    pub image_attribution: Option<String>,
+    // 示例对话，JSON格式
+    #[sea_orm(column_type = "Text", nullable)]
+    pub example_dialogues: Option<String>,
+    // 开场白，可以有多个版本，JSON格式
+    #[sea_orm(column_type = "Text", nullable)]
+    pub greetings: Option<String>,
+    // 规范的英文维基百科标题，原创角色为空
+    pub canonical_title: Option<String>,
+    // 生成时找到的维基百科条目
+    pub wiki_lang: Option<String>,
+    pub wiki_page_id: Option<i64>,
+    // 公开、仅凭链接访问或仅创建者可见
+    #[sea_orm(default_value = "public")]
+    pub visibility: Visibility,
+    // 固定分类之一，旧角色为空
+    pub category: Option<Category>,
+    // 分叉自哪个角色，用于署名，原角色删除后为空
+    pub parent_id: Option<i32>,
+    // 是否允许其他用户分叉
+    #[sea_orm(default_value = true)]
+    pub allow_fork: bool,
+    // 被审核隐藏，只有创建者可以查看
+    #[sea_orm(default_value = false)]
+    pub hidden: bool,
+}
+
+// 每个角色最多保存的示例对话组数
+pub const MAX_EXAMPLE_DIALOGUES: usize = 5;
+pub const MAX_GREETINGS: usize = 5;
+// 对话记录达到这么多条后不再加入示例对话
+pub const EXAMPLE_MAX_DIALOGS: i64 = 10;
+pub const EXAMPLE_TOKEN_BUDGET: usize = 400;
+// 每个角色最多的标签数和单个标签的最大长度（字符）
+pub const MAX_TAGS: usize = 10;
+pub const MAX_TAG_CHARS: usize = 16;
+
+// 示例对话，一问一答
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct ExampleDialogue {
+    pub user: String,
+    pub role: String,
+}
+
+// 角色对应的真实人物或作品角色，用于识别重复的角色
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct RoleIdentity {
+    pub canonical_title: Option<String>,
+    pub wiki_lang: Option<String>,
+    pub wiki_page_id: Option<i64>,
 }
 
 #[derive(Clone, Debug, EnumIter, DeriveRelation)]
@@ -38,6 +94,137 @@ impl Model {
             self.description, self.traits
         )
     }
+
+    // 对话开始时示例对话作为few-shot加入系统提示词，超出预算的部分舍去；真实对话足够长后不再需要
+    pub fn prompt_with_examples(&self, dialog_count: i64) -> String {
+        with_examples(&self.prompt(), &self.example_dialogues(), dialog_count)
+    }
+
+    pub fn example_dialogues(&self) -> Vec<ExampleDialogue> {
+        ExampleDialogue::from_json(self.example_dialogues.as_deref())
+    }
+
+    pub fn greetings(&self) -> Vec<String> {
+        greetings_from_json(self.greetings.as_deref())
+    }
+
+    // 私有和被隐藏的角色只有创建者可以查看、对话和辩论
+    pub fn is_visible_to(&self, user_id: Option<i32>) -> bool {
+        (self.visibility != Visibility::Private && !self.hidden) || user_id == Some(self.user_id)
+    }
+
+    pub fn identity(&self) -> RoleIdentity {
+        RoleIdentity {
+            canonical_title: self.canonical_title.clone(),
+            wiki_lang: self.wiki_lang.clone(),
+            wiki_page_id: self.wiki_page_id,
+        }
+    }
+}
+
+impl RoleIdentity {
+    // 优先以维基条目区分，没有条目时比较规范标题，都没有则无法识别
+    pub fn key(&self) -> Option<String> {
+        if let (Some(lang), Some(page_id)) = (&self.wiki_lang, self.wiki_page_id) {
+            return Some(format!("wiki:{}:{}", lang, page_id));
+        }
+
+        self.canonical_title
+            .as_deref()
+            .map(str::trim)
+            .filter(|title| !title.is_empty())
+            .map(|title| format!("title:{}", title.to_lowercase()))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.key().is_none()
+    }
+}
+
+// 去掉空白、#前缀和重复的标签
+pub fn normalize_tags<S: AsRef<str>>(tags: &[S]) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::new();
+
+    for tag in tags {
+        let tag = tag.as_ref().trim().trim_start_matches('#').trim();
+
+        if !tag.is_empty() && !normalized.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+            normalized.push(tag.to_string());
+        }
+    }
+
+    normalized
+}
+
+// 用户填写的标签，超出数量或长度时报错
+pub fn validate_tags(tags: &[String]) -> anyhow::Result<Vec<String>> {
+    let tags = normalize_tags(tags);
+
+    if tags.len() > MAX_TAGS {
+        return Err(anyhow::anyhow!("最多{}个标签", MAX_TAGS));
+    }
+
+    if tags.iter().any(|tag| tag.chars().count() > MAX_TAG_CHARS) {
+        return Err(anyhow::anyhow!("标签不能超过{}个字", MAX_TAG_CHARS));
+    }
+
+    Ok(tags)
+}
+
+fn with_examples(system: &str, examples: &[ExampleDialogue], dialog_count: i64) -> String {
+    if dialog_count >= EXAMPLE_MAX_DIALOGS {
+        return system.to_string();
+    }
+
+    let mut remaining = EXAMPLE_TOKEN_BUDGET;
+    let mut shots = Vec::new();
+
+    for example in examples {
+        let shot = format!("用户：{}\n你：{}", example.user, example.role);
+        let tokens = estimate_tokens(&shot);
+
+        if tokens > remaining {
+            break;
+        }
+
+        remaining -= tokens;
+        shots.push(shot);
+    }
+
+    if shots.is_empty() {
+        return system.to_string();
+    }
+
+    format!(
+        "{}\n\n以下是示例对话，用于展示你的说话方式，并非真实发生的对话：\n{}",
+        system,
+        shots.join("\n\n")
+    )
+}
+
+pub fn greetings_from_json(json: Option<&str>) -> Vec<String> {
+    json.and_then(|json| serde_json::from_str(json).ok())
+        .unwrap_or_default()
+}
+
+impl ExampleDialogue {
+    pub fn from_json(json: Option<&str>) -> Vec<Self> {
+        json.and_then(|json| serde_json::from_str(json).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn to_json(dialogues: &[Self]) -> String {
+        serde_json::to_string(dialogues).unwrap_or_default()
+    }
+
+    // 用于对比版本差异
+    pub fn to_text(dialogues: &[Self]) -> String {
+        dialogues
+            .iter()
+            .map(|dialogue| format!("用户：{}\n角色：{}", dialogue.user, dialogue.role))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
@@ -82,6 +269,12 @@ impl From<Gender> for Value {
     }
 }
 
+impl Nullable for Gender {
+    fn null() -> Value {
+        Value::String(None)
+    }
+}
+
 impl TryGetable for Gender {
     fn try_get_by<I: ColIdx>(res: &QueryResult, index: I) -> Result<Self, TryGetError> {
         let value: String = res.try_get_by(index)?;
@@ -148,6 +341,12 @@ impl From<AgeGroup> for Value {
     }
 }
 
+impl Nullable for AgeGroup {
+    fn null() -> Value {
+        Value::String(None)
+    }
+}
+
 impl TryGetable for AgeGroup {
     fn try_get_by<I: ColIdx>(res: &QueryResult, index: I) -> Result<Self, TryGetError> {
         let value: String = res.try_get_by(index)?;
@@ -171,3 +370,235 @@ impl Display for AgeGroup {
         }
     }
 }
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub enum Visibility {
+    // 出现在角色列表和搜索结果中
+    #[default]
+    #[serde(rename = "public")]
+    Public,
+    // 不出现在列表和搜索中，知道角色ID即可访问
+    #[serde(rename = "unlisted")]
+    Unlisted,
+    #[serde(rename = "private")]
+    Private,
+}
+
+impl Visibility {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Public => "public",
+            Self::Unlisted => "unlisted",
+            Self::Private => "private",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "public" => Some(Self::Public),
+            "unlisted" => Some(Self::Unlisted),
+            "private" => Some(Self::Private),
+            _ => None,
+        }
+    }
+}
+
+impl ValueType for Visibility {
+    fn try_from(v: Value) -> Result<Self, ValueTypeErr> {
+        match v {
+            Value::String(Some(value)) => Self::parse(&value).ok_or(ValueTypeErr),
+            _ => Err(ValueTypeErr),
+        }
+    }
+
+    fn type_name() -> String {
+        "Visibility".to_string()
+    }
+
+    fn array_type() -> ArrayType {
+        ArrayType::String
+    }
+
+    fn column_type() -> ColumnType {
+        ColumnType::String(StringLen::N(16))
+    }
+}
+
+impl From<Visibility> for Value {
+    fn from(value: Visibility) -> Self {
+        Self::String(Some(Box::new(value.as_str().to_string())))
+    }
+}
+
+impl TryGetable for Visibility {
+    fn try_get_by<I: ColIdx>(res: &QueryResult, index: I) -> Result<Self, TryGetError> {
+        let value: String = res.try_get_by(index)?;
+
+        Self::parse(&value).ok_or_else(|| {
+            TryGetError::DbErr(DbErr::Type(format!(
+                "visibility value should be one of public, unlisted and private: {}",
+                value
+            )))
+        })
+    }
+}
+
+impl Display for Visibility {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+// 固定的角色分类
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum Category {
+    #[serde(rename = "history")]
+    History,
+    #[serde(rename = "science")]
+    Science,
+    #[serde(rename = "literature")]
+    Literature,
+    #[serde(rename = "art")]
+    Art,
+    #[serde(rename = "philosophy")]
+    Philosophy,
+    #[serde(rename = "anime")]
+    Anime,
+    #[serde(rename = "film")]
+    Film,
+    #[serde(rename = "mythology")]
+    Mythology,
+    #[serde(rename = "original")]
+    Original,
+    #[serde(rename = "other")]
+    Other,
+}
+
+impl Category {
+    pub const ALL: [Self; 10] = [
+        Self::History,
+        Self::Science,
+        Self::Literature,
+        Self::Art,
+        Self::Philosophy,
+        Self::Anime,
+        Self::Film,
+        Self::Mythology,
+        Self::Original,
+        Self::Other,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::History => "history",
+            Self::Science => "science",
+            Self::Literature => "literature",
+            Self::Art => "art",
+            Self::Philosophy => "philosophy",
+            Self::Anime => "anime",
+            Self::Film => "film",
+            Self::Mythology => "mythology",
+            Self::Original => "original",
+            Self::Other => "other",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::History => "历史人物",
+            Self::Science => "科学技术",
+            Self::Literature => "文学",
+            Self::Art => "艺术",
+            Self::Philosophy => "哲学思想",
+            Self::Anime => "动漫游戏",
+            Self::Film => "影视",
+            Self::Mythology => "神话传说",
+            Self::Original => "原创",
+            Self::Other => "其他",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|category| category.as_str() == value)
+    }
+}
+
+impl ValueType for Category {
+    fn try_from(v: Value) -> Result<Self, ValueTypeErr> {
+        match v {
+            Value::String(Some(value)) => Self::parse(&value).ok_or(ValueTypeErr),
+            _ => Err(ValueTypeErr),
+        }
+    }
+
+    fn type_name() -> String {
+        "Category".to_string()
+    }
+
+    fn array_type() -> ArrayType {
+        ArrayType::String
+    }
+
+    fn column_type() -> ColumnType {
+        ColumnType::String(StringLen::N(16))
+    }
+}
+
+impl From<Category> for Value {
+    fn from(value: Category) -> Self {
+        Self::String(Some(Box::new(value.as_str().to_string())))
+    }
+}
+
+impl Nullable for Category {
+    fn null() -> Value {
+        Value::String(None)
+    }
+}
+
+impl TryGetable for Category {
+    fn try_get_by<I: ColIdx>(res: &QueryResult, index: I) -> Result<Self, TryGetError> {
+        let value: String = res.try_get_by(index)?;
+
+        Self::parse(&value).ok_or_else(|| {
+            TryGetError::DbErr(DbErr::Type(format!("unknown category value: {}", value)))
+        })
+    }
+}
+
+impl Display for Category {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_with_examples() {
+        let examples = vec![
+            ExampleDialogue {
+                user: "你好".to_string(),
+                role: "幸会。".to_string(),
+            },
+            ExampleDialogue {
+                user: "长".to_string(),
+                role: "长".repeat(EXAMPLE_TOKEN_BUDGET),
+            },
+        ];
+
+        assert_eq!(
+            with_examples("你是李白", &examples, 0),
+            "你是李白\n\n以下是示例对话，用于展示你的说话方式，并非真实发生的对话：\n用户：你好\n你：幸会。"
+        );
+        assert_eq!(
+            with_examples("你是李白", &examples, EXAMPLE_MAX_DIALOGS),
+            "你是李白"
+        );
+        assert_eq!(with_examples("你是李白", &[], 0), "你是李白");
+    }
+}
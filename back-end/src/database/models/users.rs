@@ -1,7 +1,12 @@
 use sea_orm::{
-    ActiveModelBehavior, DerivePrimaryKey, DeriveRelation, EnumIter, PrimaryKeyTrait,
+    ActiveModelBehavior, ColIdx, ColumnType, DbErr, DerivePrimaryKey, DeriveRelation, EnumIter,
+    PrimaryKeyTrait, QueryResult, TryGetError, TryGetable, Value,
     entity::prelude::DeriveEntityModel,
+    prelude::StringLen,
+    sea_query::{ArrayType, ValueType, ValueTypeErr},
 };
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
 
 #[derive(Clone, Debug, DeriveEntityModel)]
 #[sea_orm(table_name = "users")]
@@ -12,9 +17,101 @@ pub struct Model {
     pub password_hash: String,
     pub image: String,
     pub jwt_secret: String,
+    // 普通用户、审核员或管理员
+    #[sea_orm(default_value = "user")]
+    pub role: UserRole,
+    // 封禁到期时间，毫秒，为空时未封禁
+    pub suspended_until: Option<i64>,
 }
 
 #[derive(Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {}
 
 impl ActiveModelBehavior for ActiveModel {}
+
+impl Model {
+    pub fn is_suspended(&self, now: i64) -> bool {
+        self.suspended_until.is_some_and(|until| until > now)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum UserRole {
+    #[default]
+    #[serde(rename = "user")]
+    User,
+    // 处理举报，隐藏、删除内容，警告和封禁用户
+    #[serde(rename = "moderator")]
+    Moderator,
+    #[serde(rename = "admin")]
+    Admin,
+}
+
+impl UserRole {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::User => "user",
+            Self::Moderator => "moderator",
+            Self::Admin => "admin",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "user" => Some(Self::User),
+            "moderator" => Some(Self::Moderator),
+            "admin" => Some(Self::Admin),
+            _ => None,
+        }
+    }
+
+    pub fn can_moderate(&self) -> bool {
+        matches!(self, Self::Moderator | Self::Admin)
+    }
+}
+
+impl ValueType for UserRole {
+    fn try_from(v: Value) -> Result<Self, ValueTypeErr> {
+        match v {
+            Value::String(Some(value)) => Self::parse(&value).ok_or(ValueTypeErr),
+            _ => Err(ValueTypeErr),
+        }
+    }
+
+    fn type_name() -> String {
+        "UserRole".to_string()
+    }
+
+    fn array_type() -> ArrayType {
+        ArrayType::String
+    }
+
+    fn column_type() -> ColumnType {
+        ColumnType::String(StringLen::N(16))
+    }
+}
+
+impl From<UserRole> for Value {
+    fn from(value: UserRole) -> Self {
+        Self::String(Some(Box::new(value.as_str().to_string())))
+    }
+}
+
+impl TryGetable for UserRole {
+    fn try_get_by<I: ColIdx>(res: &QueryResult, index: I) -> Result<Self, TryGetError> {
+        let value: String = res.try_get_by(index)?;
+
+        Self::parse(&value).ok_or_else(|| {
+            TryGetError::DbErr(DbErr::Type(format!(
+                "user role value should be one of user, moderator and admin: {}",
+                value
+            )))
+        })
+    }
+}
+
+impl Display for UserRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
@@ -0,0 +1,215 @@
+use super::{Database, PaginatedResult};
+use crate::database::models::{
+    moderation_logs::{self, ModerationAction},
+    reports::{self, ReportStatus, TargetType},
+    roles,
+    users::{self, UserRole},
+};
+use anyhow::Result;
+use chrono::Utc;
+use sea_orm::{
+    ActiveValue::{self, Set},
+    ColumnTrait, EntityTrait, PaginatorTrait, QueryFilter, QueryOrder,
+    prelude::Expr,
+};
+
+impl Database {
+    // 同一用户对同一内容只保留一条待处理的举报
+    pub async fn add_report(
+        &self,
+        reporter_id: i32,
+        target_type: TargetType,
+        target_id: i32,
+        reason: &str,
+    ) -> Result<i32> {
+        let existing = reports::Entity::find()
+            .filter(reports::Column::ReporterId.eq(reporter_id))
+            .filter(reports::Column::TargetType.eq(target_type))
+            .filter(reports::Column::TargetId.eq(target_id))
+            .filter(reports::Column::Status.eq(ReportStatus::Pending))
+            .one(&self.connection)
+            .await?;
+
+        if let Some(existing) = existing {
+            return Ok(existing.id);
+        }
+
+        let res = reports::Entity::insert(reports::ActiveModel {
+            id: ActiveValue::default(),
+            reporter_id: Set(reporter_id),
+            target_type: Set(target_type),
+            target_id: Set(target_id),
+            reason: Set(reason.to_string()),
+            status: Set(ReportStatus::Pending),
+            created_at: Set(Utc::now().timestamp_millis()),
+            resolved_at: Set(None),
+            resolved_by: Set(None),
+        })
+        .exec(&self.connection)
+        .await?;
+
+        Ok(res.last_insert_id)
+    }
+
+    pub async fn count_pending_reports(
+        &self,
+        target_type: TargetType,
+        target_id: i32,
+    ) -> Result<u64> {
+        let count = reports::Entity::find()
+            .filter(reports::Column::TargetType.eq(target_type))
+            .filter(reports::Column::TargetId.eq(target_id))
+            .filter(reports::Column::Status.eq(ReportStatus::Pending))
+            .count(&self.connection)
+            .await?;
+
+        Ok(count)
+    }
+
+    // 所有待处理的举报，先举报的在前
+    pub async fn list_pending_reports(&self) -> Result<Vec<reports::Model>> {
+        let reports = reports::Entity::find()
+            .filter(reports::Column::Status.eq(ReportStatus::Pending))
+            .order_by_asc(reports::Column::CreatedAt)
+            .all(&self.connection)
+            .await?;
+
+        Ok(reports)
+    }
+
+    // 处理内容的所有待处理举报，返回处理的条数
+    pub async fn resolve_reports(
+        &self,
+        target_type: TargetType,
+        target_id: i32,
+        status: ReportStatus,
+        moderator_id: i32,
+    ) -> Result<u64> {
+        let res = reports::Entity::update_many()
+            .col_expr(reports::Column::Status, Expr::value(status))
+            .col_expr(
+                reports::Column::ResolvedAt,
+                Expr::value(Utc::now().timestamp_millis()),
+            )
+            .col_expr(reports::Column::ResolvedBy, Expr::value(moderator_id))
+            .filter(reports::Column::TargetType.eq(target_type))
+            .filter(reports::Column::TargetId.eq(target_id))
+            .filter(reports::Column::Status.eq(ReportStatus::Pending))
+            .exec(&self.connection)
+            .await?;
+
+        Ok(res.rows_affected)
+    }
+
+    pub async fn add_moderation_log(
+        &self,
+        moderator_id: i32,
+        action: ModerationAction,
+        target_type: TargetType,
+        target_id: i32,
+        reason: &str,
+        detail: Option<String>,
+    ) -> Result<moderation_logs::Model> {
+        let res = moderation_logs::Entity::insert(moderation_logs::ActiveModel {
+            id: ActiveValue::default(),
+            moderator_id: Set(moderator_id),
+            action: Set(action),
+            target_type: Set(target_type),
+            target_id: Set(target_id),
+            reason: Set(reason.to_string()),
+            detail: Set(detail),
+            created_at: Set(Utc::now().timestamp_millis()),
+        })
+        .exec(&self.connection)
+        .await?;
+
+        let log = moderation_logs::Entity::find_by_id(res.last_insert_id)
+            .one(&self.connection)
+            .await?;
+
+        log.ok_or_else(|| anyhow::anyhow!("Moderation log not found"))
+    }
+
+    // 审核记录，最新的在前，可只看某个内容或用户的记录
+    pub async fn list_moderation_logs_paginated(
+        &self,
+        offset: i64,
+        limit: i64,
+        target: Option<(TargetType, i32)>,
+    ) -> Result<PaginatedResult<moderation_logs::Model>> {
+        let mut query = moderation_logs::Entity::find();
+
+        if let Some((target_type, target_id)) = target {
+            query = query
+                .filter(moderation_logs::Column::TargetType.eq(target_type))
+                .filter(moderation_logs::Column::TargetId.eq(target_id));
+        }
+
+        let paginator = query
+            .order_by_desc(moderation_logs::Column::Id)
+            .paginate(&self.connection, limit as u64);
+
+        let num_pages = paginator.num_pages().await?;
+        let total = paginator.num_items().await?;
+
+        let page_number = (offset / limit) as u64;
+        let items = paginator.fetch_page(page_number).await?;
+        let has_more = (page_number + 1) < num_pages;
+
+        Ok(PaginatedResult {
+            items,
+            total: total as i64,
+            has_more,
+        })
+    }
+
+    // 用户收到的警告，最新的在前
+    pub async fn list_user_warnings(&self, user_id: i32) -> Result<Vec<moderation_logs::Model>> {
+        let warnings = moderation_logs::Entity::find()
+            .filter(moderation_logs::Column::TargetType.eq(TargetType::User))
+            .filter(moderation_logs::Column::TargetId.eq(user_id))
+            .filter(moderation_logs::Column::Action.eq(ModerationAction::Warn))
+            .order_by_desc(moderation_logs::Column::Id)
+            .all(&self.connection)
+            .await?;
+
+        Ok(warnings)
+    }
+
+    // 隐藏不属于角色设定，修改时不产生新版本
+    pub async fn set_role_hidden(&self, role_id: i32, hidden: bool) -> Result<()> {
+        roles::Entity::update_many()
+            .col_expr(roles::Column::Hidden, Expr::value(hidden))
+            .filter(roles::Column::Id.eq(role_id))
+            .exec(&self.connection)
+            .await?;
+
+        Ok(())
+    }
+
+    // 为空时解除封禁
+    pub async fn set_user_suspended_until(&self, user_id: i32, until: Option<i64>) -> Result<()> {
+        users::Entity::update_many()
+            .col_expr(users::Column::SuspendedUntil, Expr::value(until))
+            .filter(users::Column::Id.eq(user_id))
+            .exec(&self.connection)
+            .await?;
+
+        Ok(())
+    }
+
+    // 启动时把配置的用户设为管理员，不存在的用户忽略
+    pub async fn grant_admins(&self, usernames: &[String]) -> Result<()> {
+        if usernames.is_empty() {
+            return Ok(());
+        }
+
+        users::Entity::update_many()
+            .col_expr(users::Column::Role, Expr::value(UserRole::Admin))
+            .filter(users::Column::Username.is_in(usernames.iter().cloned()))
+            .exec(&self.connection)
+            .await?;
+
+        Ok(())
+    }
+}
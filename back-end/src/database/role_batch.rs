@@ -0,0 +1,129 @@
+use super::Database;
+use crate::{
+    database::models::{
+        role_batch_items::{self, ItemStatus},
+        role_batches,
+        role_jobs::Status,
+    },
+    import::RoleEntry,
+};
+use anyhow::Result;
+use chrono::Utc;
+use sea_orm::{
+    ActiveModelTrait,
+    ActiveValue::{self, Set},
+    ColumnTrait, EntityTrait, IntoActiveModel, PaginatorTrait, QueryFilter, QueryOrder,
+    TransactionTrait,
+};
+
+impl Database {
+    // 创建批量任务及其全部条目
+    pub async fn add_role_batch(&self, user_id: i32, entries: &[RoleEntry]) -> Result<i32> {
+        let now = Utc::now().timestamp_millis();
+        let txn = self.connection.begin().await?;
+
+        let batch = role_batches::ActiveModel {
+            id: ActiveValue::default(),
+            user_id: Set(user_id),
+            status: Set(Status::Pending),
+            total: Set(entries.len() as i32),
+            error: Set(None),
+            created_at: Set(now),
+            updated_at: Set(now),
+        };
+
+        let batch_id = role_batches::Entity::insert(batch)
+            .exec(&txn)
+            .await?
+            .last_insert_id;
+
+        let items =
+            entries
+                .iter()
+                .enumerate()
+                .map(|(position, entry)| role_batch_items::ActiveModel {
+                    id: ActiveValue::default(),
+                    batch_id: Set(batch_id),
+                    position: Set(position as i32),
+                    name: Set(entry.name.clone()),
+                    gender: Set(entry.gender.clone()),
+                    age_group: Set(entry.age_group.clone()),
+                    tags: Set((!entry.tags.is_empty()).then(|| entry.tags.join("\n"))),
+                    status: Set(ItemStatus::Pending),
+                    role_id: Set(None),
+                    error: Set(None),
+                    updated_at: Set(now),
+                });
+
+        role_batch_items::Entity::insert_many(items)
+            .exec(&txn)
+            .await?;
+
+        txn.commit().await?;
+
+        Ok(batch_id)
+    }
+
+    pub async fn get_role_batch(&self, batch_id: i32) -> Result<role_batches::Model> {
+        let batch = role_batches::Entity::find_by_id(batch_id)
+            .one(&self.connection)
+            .await?;
+
+        batch.ok_or_else(|| anyhow::anyhow!("Role batch not found"))
+    }
+
+    pub async fn list_role_batch_items(
+        &self,
+        batch_id: i32,
+    ) -> Result<Vec<role_batch_items::Model>> {
+        let items = role_batch_items::Entity::find()
+            .filter(role_batch_items::Column::BatchId.eq(batch_id))
+            .order_by_asc(role_batch_items::Column::Position)
+            .all(&self.connection)
+            .await?;
+
+        Ok(items)
+    }
+
+    pub async fn save_role_batch(&self, batch: &role_batches::Model) -> Result<()> {
+        let mut batch = batch.clone().into_active_model().reset_all();
+        batch.updated_at = Set(Utc::now().timestamp_millis());
+
+        role_batches::Entity::update(batch)
+            .exec(&self.connection)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn save_role_batch_item(&self, item: &role_batch_items::Model) -> Result<()> {
+        let mut item = item.clone().into_active_model().reset_all();
+        item.updated_at = Set(Utc::now().timestamp_millis());
+
+        role_batch_items::Entity::update(item)
+            .exec(&self.connection)
+            .await?;
+
+        Ok(())
+    }
+
+    // 服务重启前未完成的批量任务
+    pub async fn list_unfinished_role_batches(&self) -> Result<Vec<role_batches::Model>> {
+        let batches = role_batches::Entity::find()
+            .filter(role_batches::Column::Status.is_in([Status::Pending, Status::Running]))
+            .all(&self.connection)
+            .await?;
+
+        Ok(batches)
+    }
+
+    pub async fn has_unfinished_role_batch(&self, user_id: i32) -> Result<bool> {
+        let count = role_batches::Entity::find()
+            .filter(role_batches::Column::UserId.eq(user_id))
+            .filter(role_batches::Column::Status.is_in([Status::Pending, Status::Running]))
+            .count(&self.connection)
+            .await?;
+
+        Ok(count > 0)
+    }
+}
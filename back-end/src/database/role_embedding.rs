@@ -0,0 +1,53 @@
+use super::Database;
+use crate::database::models::{role_embeddings, roles};
+use anyhow::Result;
+use chrono::Utc;
+use sea_orm::{ActiveValue::Set, EntityTrait};
+
+impl Database {
+    pub async fn list_role_embeddings(&self) -> Result<Vec<role_embeddings::Model>> {
+        let embeddings = role_embeddings::Entity::find()
+            .all(&self.connection)
+            .await?;
+
+        Ok(embeddings)
+    }
+
+    pub async fn get_role_embedding(&self, role_id: i32) -> Result<Option<role_embeddings::Model>> {
+        let embedding = role_embeddings::Entity::find_by_id(role_id)
+            .one(&self.connection)
+            .await?;
+
+        Ok(embedding)
+    }
+
+    // 覆盖角色原有的向量
+    pub async fn save_role_embedding(
+        &self,
+        role: &roles::Model,
+        model: &str,
+        vector: &[f32],
+    ) -> Result<()> {
+        self.delete_role_embedding(role.id).await?;
+
+        role_embeddings::Entity::insert(role_embeddings::ActiveModel {
+            role_id: Set(role.id),
+            model: Set(model.to_string()),
+            version: Set(role.version),
+            vector: Set(serde_json::to_string(vector)?),
+            updated_at: Set(Utc::now().timestamp_millis()),
+        })
+        .exec(&self.connection)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn delete_role_embedding(&self, role_id: i32) -> Result<()> {
+        role_embeddings::Entity::delete_by_id(role_id)
+            .exec(&self.connection)
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,80 @@
+use super::Database;
+use crate::database::models::roles::{self, Visibility};
+use anyhow::Result;
+use sea_orm::{
+    ActiveValue::{self, Set},
+    ColumnTrait, EntityTrait, QueryFilter, QueryOrder,
+    prelude::Expr,
+};
+
+impl Database {
+    // 复制角色到用户名下，记录原角色用于署名；新角色仅自己可见，修改后再公开
+    pub async fn fork_role(&self, role: &roles::Model, user_id: i32) -> Result<i32> {
+        let forked = roles::ActiveModel {
+            id: ActiveValue::default(),
+            user_id: Set(user_id),
+            name: Set(role.name.clone()),
+            description: Set(role.description.clone()),
+            traits: Set(role.traits.clone()),
+            image: Set(role.image.clone()),
+            gender: Set(role.gender.clone()),
+            age_group: Set(role.age_group.clone()),
+            voice_type: Set(role.voice_type.clone()),
+            version: Set(1),
+            image_attribution: Set(role.image_attribution.clone()),
+            example_dialogues: Set(role.example_dialogues.clone()),
+            greetings: Set(role.greetings.clone()),
+            canonical_title: Set(role.canonical_title.clone()),
+            wiki_lang: Set(role.wiki_lang.clone()),
+            wiki_page_id: Set(role.wiki_page_id),
+            visibility: Set(Visibility::Private),
+            category: Set(role.category),
+            parent_id: Set(Some(role.id)),
+            allow_fork: Set(true),
+            hidden: Set(false),
+        };
+
+        let res = roles::Entity::insert(forked).exec(&self.connection).await?;
+        let forked = self.get_role(res.last_insert_id).await?;
+        self.add_role_version(&self.connection, &forked).await?;
+
+        // 复制标签时一并建立搜索索引
+        let tags = self.get_role_tags(role.id).await?;
+        self.set_role_tags(forked.id, &tags).await?;
+
+        Ok(forked.id)
+    }
+
+    // 是否允许分叉不属于角色设定，修改时不产生新版本
+    pub async fn set_role_allow_fork(&self, role_id: i32, allow_fork: bool) -> Result<()> {
+        roles::Entity::update_many()
+            .col_expr(roles::Column::AllowFork, Expr::value(allow_fork))
+            .filter(roles::Column::Id.eq(role_id))
+            .exec(&self.connection)
+            .await?;
+
+        Ok(())
+    }
+
+    // 直接分叉自该角色的角色，最新的在前，包括不公开和私有角色
+    pub async fn list_role_forks(&self, role_id: i32) -> Result<Vec<roles::Model>> {
+        let forks = roles::Entity::find()
+            .filter(roles::Column::ParentId.eq(role_id))
+            .order_by_desc(roles::Column::Id)
+            .all(&self.connection)
+            .await?;
+
+        Ok(forks)
+    }
+
+    // 原角色删除后，分叉的角色不再指向它
+    pub(super) async fn detach_role_forks(&self, role_id: i32) -> Result<()> {
+        roles::Entity::update_many()
+            .col_expr(roles::Column::ParentId, Expr::value(Option::<i32>::None))
+            .filter(roles::Column::ParentId.eq(role_id))
+            .exec(&self.connection)
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,144 @@
+use super::Database;
+use crate::database::models::{
+    role_jobs::{self, Status},
+    roles::{AgeGroup, Gender},
+};
+use anyhow::Result;
+use chrono::Utc;
+use sea_orm::{
+    ActiveModelTrait,
+    ActiveValue::{self, Set},
+    ColumnTrait, EntityTrait, IntoActiveModel, QueryFilter, QuerySelect, TransactionTrait,
+};
+
+impl Database {
+    pub async fn add_role_job(
+        &self,
+        user_id: i32,
+        name: &str,
+        description: &str,
+        traits: &str,
+        gender: Option<Gender>,
+        age_group: Option<AgeGroup>,
+    ) -> Result<i32> {
+        let now = Utc::now().timestamp_millis();
+
+        let job = role_jobs::ActiveModel {
+            id: ActiveValue::default(),
+            user_id: Set(user_id),
+            status: Set(Status::Pending),
+            name: Set(name.to_string()),
+            description: Set(description.to_string()),
+            traits: Set(traits.to_string()),
+            gender: Set(gender),
+            age_group: Set(age_group),
+            canonical_name: Set(None),
+            extract: Set(None),
+            wiki_lang: Set(None),
+            wiki_page_id: Set(None),
+            traits_draft: Set(None),
+            voice_type: Set(None),
+            quotes: Set(None),
+            image: Set(None),
+            image_attribution: Set(None),
+            result_description: Set(None),
+            result_traits: Set(None),
+            result_gender: Set(None),
+            result_age_group: Set(None),
+            result_example_dialogues: Set(None),
+            result_greetings: Set(None),
+            result_category: Set(None),
+            result_tags: Set(None),
+            error: Set(None),
+            created_at: Set(now),
+            updated_at: Set(now),
+        };
+
+        let res = role_jobs::Entity::insert(job)
+            .exec(&self.connection)
+            .await?;
+
+        Ok(res.last_insert_id)
+    }
+
+    pub async fn get_role_job(&self, job_id: i32) -> Result<role_jobs::Model> {
+        let job = role_jobs::Entity::find_by_id(job_id)
+            .one(&self.connection)
+            .await?;
+
+        job.ok_or_else(|| anyhow::anyhow!("Role job not found"))
+    }
+
+    // 保存任务的状态和全部中间结果；在任务行锁内检查当前状态，不在expected中时不保存并返回false，避免覆盖并发写入的取消状态
+    pub async fn save_role_job(&self, job: &role_jobs::Model, expected: &[Status]) -> Result<bool> {
+        let txn = self.connection.begin().await?;
+
+        let current = role_jobs::Entity::find_by_id(job.id)
+            .lock_exclusive()
+            .one(&txn)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Role job not found"))?;
+
+        if !expected.contains(&current.status) {
+            return Ok(false);
+        }
+
+        let mut job = job.clone().into_active_model().reset_all();
+        job.updated_at = Set(Utc::now().timestamp_millis());
+
+        role_jobs::Entity::update(job).exec(&txn).await?;
+
+        txn.commit().await?;
+
+        Ok(true)
+    }
+
+    // 只修改任务状态，不影响中间结果；当前状态不在expected中时不修改，返回空
+    pub async fn set_role_job_status(
+        &self,
+        job_id: i32,
+        status: Status,
+        error: Option<String>,
+        expected: &[Status],
+    ) -> Result<Option<role_jobs::Model>> {
+        let txn = self.connection.begin().await?;
+
+        let mut job = role_jobs::Entity::find_by_id(job_id)
+            .lock_exclusive()
+            .one(&txn)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Role job not found"))?;
+
+        if !expected.contains(&job.status) {
+            return Ok(None);
+        }
+
+        job.status = status;
+        job.error = error;
+        job.updated_at = Utc::now().timestamp_millis();
+
+        role_jobs::ActiveModel {
+            id: Set(job.id),
+            status: Set(job.status.clone()),
+            error: Set(job.error.clone()),
+            updated_at: Set(job.updated_at),
+            ..Default::default()
+        }
+        .update(&txn)
+        .await?;
+
+        txn.commit().await?;
+
+        Ok(Some(job))
+    }
+
+    // 服务重启前未完成的任务
+    pub async fn list_unfinished_role_jobs(&self) -> Result<Vec<role_jobs::Model>> {
+        let jobs = role_jobs::Entity::find()
+            .filter(role_jobs::Column::Status.is_in([Status::Pending, Status::Running]))
+            .all(&self.connection)
+            .await?;
+
+        Ok(jobs)
+    }
+}
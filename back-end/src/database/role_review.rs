@@ -0,0 +1,259 @@
+use super::{Database, PaginatedResult};
+use crate::{
+    database::models::{
+        conversations,
+        moderation_logs::{ModerationAction, SYSTEM_MODERATOR_ID},
+        reports::TargetType,
+        role_reviews,
+    },
+    stats::Rating,
+};
+use anyhow::Result;
+use chrono::Utc;
+use sea_orm::{
+    ActiveValue::{self, Set},
+    ColumnTrait, ConnectionTrait, EntityTrait, PaginatorTrait, QueryFilter, QueryOrder,
+    QuerySelect,
+    prelude::Expr,
+};
+use std::collections::HashMap;
+
+// 被这么多用户举报后自动隐藏
+const REPORTS_TO_HIDE: u64 = 3;
+
+impl Database {
+    // 用户与角色的对话中，真实对话轮数最多的一个的轮数，包括分支；
+    // 只统计模型生成的回复（带角色版本号），导入的记录没有版本号，用户第一条消息之前的开场白也不计
+    pub async fn count_role_replies(&self, user_id: i32, role_id: i32) -> Result<i64> {
+        let conversations = conversations::Entity::find()
+            .filter(conversations::Column::UserId.eq(user_id))
+            .filter(conversations::Column::RoleId.eq(role_id))
+            .all(&self.connection)
+            .await?;
+
+        let mut max = 0;
+
+        for conversation in conversations {
+            let sql = format!(
+                "SELECT COUNT(*) AS count FROM `{0}` WHERE is_user = 0 AND role_version IS NOT NULL AND id > (SELECT MIN(id) FROM `{0}` WHERE is_user = 1)",
+                conversation.table_name
+            );
+
+            let res = self
+                .connection
+                .query_one(sea_orm::Statement::from_string(
+                    self.connection.get_database_backend(),
+                    sql,
+                ))
+                .await?;
+
+            let count: i64 = res
+                .and_then(|row| row.try_get("", "count").ok())
+                .unwrap_or(0);
+            max = max.max(count);
+        }
+
+        Ok(max)
+    }
+
+    // 新建或覆盖用户对角色的评价，保留作者的回复
+    pub async fn save_role_review(
+        &self,
+        user_id: i32,
+        role_id: i32,
+        rating: i32,
+        content: &str,
+    ) -> Result<role_reviews::Model> {
+        let now = Utc::now().timestamp_millis();
+
+        let existing = role_reviews::Entity::find()
+            .filter(role_reviews::Column::UserId.eq(user_id))
+            .filter(role_reviews::Column::RoleId.eq(role_id))
+            .one(&self.connection)
+            .await?;
+
+        let review_id = match existing {
+            Some(existing) => {
+                role_reviews::Entity::update_many()
+                    .col_expr(role_reviews::Column::Rating, Expr::value(rating))
+                    .col_expr(role_reviews::Column::Content, Expr::value(content))
+                    .col_expr(role_reviews::Column::UpdatedAt, Expr::value(now))
+                    .filter(role_reviews::Column::Id.eq(existing.id))
+                    .exec(&self.connection)
+                    .await?;
+
+                existing.id
+            }
+            None => {
+                let res = role_reviews::Entity::insert(role_reviews::ActiveModel {
+                    id: ActiveValue::default(),
+                    role_id: Set(role_id),
+                    user_id: Set(user_id),
+                    rating: Set(rating),
+                    content: Set(content.to_string()),
+                    reply: Set(None),
+                    replied_at: Set(None),
+                    hidden: Set(false),
+                    created_at: Set(now),
+                    updated_at: Set(now),
+                })
+                .exec(&self.connection)
+                .await?;
+
+                res.last_insert_id
+            }
+        };
+
+        self.get_role_review(review_id).await
+    }
+
+    pub async fn get_role_review(&self, review_id: i32) -> Result<role_reviews::Model> {
+        let review = role_reviews::Entity::find_by_id(review_id)
+            .one(&self.connection)
+            .await?;
+
+        review.ok_or_else(|| anyhow::anyhow!("Review not found"))
+    }
+
+    // 未隐藏的评价，最近修改的在前
+    pub async fn list_role_reviews_paginated(
+        &self,
+        role_id: i32,
+        offset: i64,
+        limit: i64,
+    ) -> Result<PaginatedResult<role_reviews::Model>> {
+        let paginator = role_reviews::Entity::find()
+            .filter(role_reviews::Column::RoleId.eq(role_id))
+            .filter(role_reviews::Column::Hidden.eq(false))
+            .order_by_desc(role_reviews::Column::UpdatedAt)
+            .order_by_desc(role_reviews::Column::Id)
+            .paginate(&self.connection, limit as u64);
+
+        let num_pages = paginator.num_pages().await?;
+        let total = paginator.num_items().await?;
+
+        let page_number = (offset / limit) as u64;
+        let items = paginator.fetch_page(page_number).await?;
+        let has_more = (page_number + 1) < num_pages;
+
+        Ok(PaginatedResult {
+            items,
+            total: total as i64,
+            has_more,
+        })
+    }
+
+    // 角色ID -> 评分分布，不含隐藏的评价
+    pub async fn get_roles_ratings(&self, role_ids: &[i32]) -> Result<HashMap<i32, Rating>> {
+        let mut ratings: HashMap<i32, Rating> = HashMap::new();
+
+        if role_ids.is_empty() {
+            return Ok(ratings);
+        }
+
+        let rows: Vec<(i32, i32)> = role_reviews::Entity::find()
+            .select_only()
+            .column(role_reviews::Column::RoleId)
+            .column(role_reviews::Column::Rating)
+            .filter(role_reviews::Column::RoleId.is_in(role_ids.iter().copied()))
+            .filter(role_reviews::Column::Hidden.eq(false))
+            .into_tuple()
+            .all(&self.connection)
+            .await?;
+
+        for (role_id, rating) in rows {
+            ratings.entry(role_id).or_default().add(rating);
+        }
+
+        Ok(ratings)
+    }
+
+    pub async fn get_role_rating(&self, role_id: i32) -> Result<Rating> {
+        let mut ratings = self.get_roles_ratings(&[role_id]).await?;
+
+        Ok(ratings.remove(&role_id).unwrap_or_default())
+    }
+
+    // 作者回复评价，为空时删除回复
+    pub async fn reply_role_review(&self, review_id: i32, reply: Option<&str>) -> Result<()> {
+        let replied_at = reply.map(|_| Utc::now().timestamp_millis());
+
+        role_reviews::Entity::update_many()
+            .col_expr(
+                role_reviews::Column::Reply,
+                Expr::value(reply.map(str::to_string)),
+            )
+            .col_expr(role_reviews::Column::RepliedAt, Expr::value(replied_at))
+            .filter(role_reviews::Column::Id.eq(review_id))
+            .exec(&self.connection)
+            .await?;
+
+        Ok(())
+    }
+
+    // 举报评价，同一用户重复举报不重复计数，待处理的举报人数达到阈值时隐藏评价并记为系统审核操作；
+    // 返回评价是否被隐藏
+    pub async fn report_role_review(
+        &self,
+        review_id: i32,
+        user_id: i32,
+        reason: &str,
+    ) -> Result<bool> {
+        self.add_report(user_id, TargetType::Review, review_id, reason)
+            .await?;
+
+        if self
+            .count_pending_reports(TargetType::Review, review_id)
+            .await?
+            < REPORTS_TO_HIDE
+        {
+            return Ok(false);
+        }
+
+        if self.get_role_review(review_id).await?.hidden {
+            return Ok(true);
+        }
+
+        self.set_review_hidden(review_id, true).await?;
+
+        self.add_moderation_log(
+            SYSTEM_MODERATOR_ID,
+            ModerationAction::Hide,
+            TargetType::Review,
+            review_id,
+            &format!("被{}个用户举报，自动隐藏", REPORTS_TO_HIDE),
+            None,
+        )
+        .await?;
+
+        Ok(true)
+    }
+
+    pub async fn set_review_hidden(&self, review_id: i32, hidden: bool) -> Result<()> {
+        role_reviews::Entity::update_many()
+            .col_expr(role_reviews::Column::Hidden, Expr::value(hidden))
+            .filter(role_reviews::Column::Id.eq(review_id))
+            .exec(&self.connection)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn delete_role_review(&self, review_id: i32) -> Result<()> {
+        role_reviews::Entity::delete_by_id(review_id)
+            .exec(&self.connection)
+            .await?;
+
+        Ok(())
+    }
+
+    // 删除角色时清除其评价，举报作为审核记录保留
+    pub(super) async fn delete_role_reviews(&self, role_id: i32) -> Result<()> {
+        role_reviews::Entity::delete_many()
+            .filter(role_reviews::Column::RoleId.eq(role_id))
+            .exec(&self.connection)
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,94 @@
+use super::{Database, RoleEvent};
+use crate::{
+    database::models::roles::{self, Column, Entity},
+    search::{Document, RoleFilter, RoleHit},
+    stats,
+};
+use anyhow::Result;
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, QuerySelect};
+use std::collections::HashMap;
+
+impl Database {
+    // 启动时从数据库重建搜索索引
+    pub async fn build_search_index(&self) -> Result<()> {
+        let roles = self.list_all_roles().await?;
+        let ids: Vec<i32> = roles.iter().map(|role| role.id).collect();
+        let tags = self.get_roles_tags(&ids).await?;
+
+        self.search_index.clear();
+
+        for role in &roles {
+            let role_tags = tags.get(&role.id).map(Vec::as_slice).unwrap_or_default();
+            self.search_index
+                .insert(role.id, &Document::new(role, role_tags));
+        }
+
+        Ok(())
+    }
+
+    // 角色或其标签变化后更新索引，并通知订阅者
+    pub(super) async fn index_role(&self, role_id: i32) -> Result<()> {
+        let role = self.get_role(role_id).await?;
+        let tags = self.get_role_tags(role_id).await?;
+
+        self.search_index
+            .insert(role_id, &Document::new(&role, &tags));
+        self.notify_role_event(RoleEvent::Saved(role_id));
+
+        Ok(())
+    }
+
+    // 全文搜索满足筛选条件的角色，按相关度和评分排列；只读取分面和分组需要的列
+    pub async fn search_roles(&self, keyword: &str, filter: &RoleFilter) -> Result<Vec<RoleHit>> {
+        let scores = self.search_index.search(keyword);
+        let role_ids: Vec<i32> = scores.iter().map(|(role_id, _)| *role_id).collect();
+        let ratings = self.get_roles_ratings(&role_ids).await?;
+        let role_ids = stats::rank_with_ratings(scores, &ratings);
+
+        if role_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut hits: HashMap<i32, RoleHit> = Entity::find()
+            .select_only()
+            .columns(RoleHit::COLUMNS)
+            .filter(filter.condition())
+            .filter(Column::Id.is_in(role_ids.iter().copied()))
+            .into_model::<RoleHit>()
+            .all(&self.connection)
+            .await?
+            .into_iter()
+            .map(|hit| (hit.id, hit))
+            .collect();
+
+        Ok(role_ids
+            .iter()
+            .filter_map(|role_id| hits.remove(role_id))
+            .collect())
+    }
+
+    // 按给定顺序返回满足筛选条件的角色
+    pub async fn find_roles_ranked(
+        &self,
+        role_ids: &[i32],
+        filter: &RoleFilter,
+    ) -> Result<Vec<roles::Model>> {
+        if role_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut roles: HashMap<i32, roles::Model> = Entity::find()
+            .filter(filter.condition())
+            .filter(Column::Id.is_in(role_ids.iter().copied()))
+            .all(&self.connection)
+            .await?
+            .into_iter()
+            .map(|role| (role.id, role))
+            .collect();
+
+        Ok(role_ids
+            .iter()
+            .filter_map(|role_id| roles.remove(role_id))
+            .collect())
+    }
+}
@@ -0,0 +1,265 @@
+use super::Database;
+use crate::{
+    database::models::{
+        role_daily_stats,
+        role_reactions::{self, ReactionKind},
+        role_users,
+    },
+    stats::{self, DAY_MS, RoleStats, TRENDING_WINDOW_DAYS, Usage},
+};
+use anyhow::Result;
+use chrono::Utc;
+use sea_orm::{
+    ActiveValue::Set, ColumnTrait, EntityTrait, QueryFilter, QueryOrder, QuerySelect,
+    prelude::Expr, sea_query::OnConflict,
+};
+use std::collections::HashMap;
+
+impl Database {
+    // 累加角色当天的使用量，并记录使用过角色的用户
+    pub(super) async fn record_role_usage(
+        &self,
+        role_id: i32,
+        user_id: i32,
+        usage: Usage,
+    ) -> Result<()> {
+        let now = Utc::now().timestamp_millis();
+
+        role_daily_stats::Entity::insert(role_daily_stats::ActiveModel {
+            role_id: Set(role_id),
+            day: Set(stats::day_of(now)),
+            conversations: Set(usage.conversations),
+            messages: Set(usage.messages),
+            debates: Set(usage.debates),
+        })
+        .on_conflict(
+            OnConflict::columns([
+                role_daily_stats::Column::RoleId,
+                role_daily_stats::Column::Day,
+            ])
+            .value(
+                role_daily_stats::Column::Conversations,
+                Expr::col(role_daily_stats::Column::Conversations).add(usage.conversations),
+            )
+            .value(
+                role_daily_stats::Column::Messages,
+                Expr::col(role_daily_stats::Column::Messages).add(usage.messages),
+            )
+            .value(
+                role_daily_stats::Column::Debates,
+                Expr::col(role_daily_stats::Column::Debates).add(usage.debates),
+            )
+            .to_owned(),
+        )
+        .exec_without_returning(&self.connection)
+        .await?;
+
+        role_users::Entity::insert(role_users::ActiveModel {
+            role_id: Set(role_id),
+            user_id: Set(user_id),
+            created_at: Set(now),
+        })
+        .on_conflict(
+            OnConflict::columns([role_users::Column::RoleId, role_users::Column::UserId])
+                .do_nothing_on([role_users::Column::RoleId])
+                .to_owned(),
+        )
+        .exec_without_returning(&self.connection)
+        .await?;
+
+        Ok(())
+    }
+
+    // 角色ID -> 累计使用量，没有记录的角色为0
+    pub async fn get_roles_stats(&self, role_ids: &[i32]) -> Result<HashMap<i32, RoleStats>> {
+        let mut stats: HashMap<i32, RoleStats> = role_ids
+            .iter()
+            .map(|role_id| (*role_id, RoleStats::default()))
+            .collect();
+
+        if role_ids.is_empty() {
+            return Ok(stats);
+        }
+
+        for daily in role_daily_stats::Entity::find()
+            .filter(role_daily_stats::Column::RoleId.is_in(role_ids.iter().copied()))
+            .all(&self.connection)
+            .await?
+        {
+            stats.entry(daily.role_id).or_default().add(&daily);
+        }
+
+        let users: Vec<(i32, i64)> = role_users::Entity::find()
+            .select_only()
+            .column(role_users::Column::RoleId)
+            .column_as(Expr::col(role_users::Column::UserId).count(), "users")
+            .filter(role_users::Column::RoleId.is_in(role_ids.iter().copied()))
+            .group_by(role_users::Column::RoleId)
+            .into_tuple()
+            .all(&self.connection)
+            .await?;
+
+        for (role_id, count) in users {
+            stats.entry(role_id).or_default().users = count;
+        }
+
+        let reactions: Vec<(i32, ReactionKind, i64)> = role_reactions::Entity::find()
+            .select_only()
+            .column(role_reactions::Column::RoleId)
+            .column(role_reactions::Column::Kind)
+            .column_as(Expr::col(role_reactions::Column::UserId).count(), "count")
+            .filter(role_reactions::Column::RoleId.is_in(role_ids.iter().copied()))
+            .group_by(role_reactions::Column::RoleId)
+            .group_by(role_reactions::Column::Kind)
+            .into_tuple()
+            .all(&self.connection)
+            .await?;
+
+        for (role_id, kind, count) in reactions {
+            stats.entry(role_id).or_default().add_reactions(kind, count);
+        }
+
+        for (role_id, rating) in self.get_roles_ratings(role_ids).await? {
+            stats.entry(role_id).or_default().set_rating(&rating);
+        }
+
+        Ok(stats)
+    }
+
+    pub async fn get_role_stats(&self, role_id: i32) -> Result<RoleStats> {
+        let mut stats = self.get_roles_stats(&[role_id]).await?;
+
+        Ok(stats.remove(&role_id).unwrap_or_default())
+    }
+
+    // 角色从since这天起每天的使用量，按天排列
+    pub async fn list_role_daily_stats(
+        &self,
+        role_ids: &[i32],
+        since: i32,
+    ) -> Result<Vec<role_daily_stats::Model>> {
+        if role_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let daily = role_daily_stats::Entity::find()
+            .filter(role_daily_stats::Column::RoleId.is_in(role_ids.iter().copied()))
+            .filter(role_daily_stats::Column::Day.gte(since))
+            .order_by_asc(role_daily_stats::Column::Day)
+            .all(&self.connection)
+            .await?;
+
+        Ok(daily)
+    }
+
+    // 所有角色按最近的热度从高到低排列，包括不公开的角色
+    pub async fn trending_roles(&self) -> Result<Vec<(i32, f32)>> {
+        let now = Utc::now().timestamp_millis();
+        let today = stats::day_of(now);
+        let since = today - TRENDING_WINDOW_DAYS;
+
+        let daily = role_daily_stats::Entity::find()
+            .filter(role_daily_stats::Column::Day.gte(since))
+            .all(&self.connection)
+            .await?;
+
+        let reactions = role_reactions::Entity::find()
+            .filter(role_reactions::Column::CreatedAt.gte(since as i64 * DAY_MS))
+            .all(&self.connection)
+            .await?;
+
+        Ok(stats::trending_scores(&daily, &reactions, today))
+    }
+
+    // 收藏或点赞，重复操作不产生多条记录
+    pub async fn set_role_reaction(
+        &self,
+        user_id: i32,
+        role_id: i32,
+        kind: ReactionKind,
+        active: bool,
+    ) -> Result<()> {
+        if active {
+            role_reactions::Entity::insert(role_reactions::ActiveModel {
+                user_id: Set(user_id),
+                role_id: Set(role_id),
+                kind: Set(kind),
+                created_at: Set(Utc::now().timestamp_millis()),
+            })
+            .on_conflict(
+                OnConflict::columns([
+                    role_reactions::Column::UserId,
+                    role_reactions::Column::RoleId,
+                    role_reactions::Column::Kind,
+                ])
+                .do_nothing_on([role_reactions::Column::UserId])
+                .to_owned(),
+            )
+            .exec_without_returning(&self.connection)
+            .await?;
+        } else {
+            role_reactions::Entity::delete_many()
+                .filter(role_reactions::Column::UserId.eq(user_id))
+                .filter(role_reactions::Column::RoleId.eq(role_id))
+                .filter(role_reactions::Column::Kind.eq(kind))
+                .exec(&self.connection)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    // 用户对角色做过的收藏和点赞
+    pub async fn get_user_reactions(
+        &self,
+        user_id: i32,
+        role_id: i32,
+    ) -> Result<Vec<ReactionKind>> {
+        let reactions = role_reactions::Entity::find()
+            .filter(role_reactions::Column::UserId.eq(user_id))
+            .filter(role_reactions::Column::RoleId.eq(role_id))
+            .all(&self.connection)
+            .await?;
+
+        Ok(reactions
+            .into_iter()
+            .map(|reaction| reaction.kind)
+            .collect())
+    }
+
+    // 用户收藏或点赞的角色，最近的在前
+    pub async fn list_user_reactions(
+        &self,
+        user_id: i32,
+        kind: ReactionKind,
+    ) -> Result<Vec<role_reactions::Model>> {
+        let reactions = role_reactions::Entity::find()
+            .filter(role_reactions::Column::UserId.eq(user_id))
+            .filter(role_reactions::Column::Kind.eq(kind))
+            .order_by_desc(role_reactions::Column::CreatedAt)
+            .all(&self.connection)
+            .await?;
+
+        Ok(reactions)
+    }
+
+    // 删除角色时清除其统计和收藏点赞
+    pub(super) async fn delete_role_stats(&self, role_id: i32) -> Result<()> {
+        role_daily_stats::Entity::delete_many()
+            .filter(role_daily_stats::Column::RoleId.eq(role_id))
+            .exec(&self.connection)
+            .await?;
+
+        role_users::Entity::delete_many()
+            .filter(role_users::Column::RoleId.eq(role_id))
+            .exec(&self.connection)
+            .await?;
+
+        role_reactions::Entity::delete_many()
+            .filter(role_reactions::Column::RoleId.eq(role_id))
+            .exec(&self.connection)
+            .await?;
+
+        Ok(())
+    }
+}
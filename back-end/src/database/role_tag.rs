@@ -0,0 +1,100 @@
+use super::Database;
+use crate::{
+    database::models::{
+        role_tags,
+        roles::{self, Category},
+    },
+    search::{Facets, RoleFilter, RoleHit},
+};
+use anyhow::Result;
+use sea_orm::{
+    ActiveValue::{self, Set},
+    ColumnTrait, EntityTrait, QueryFilter, QueryOrder, QuerySelect, TransactionTrait,
+    prelude::Expr,
+};
+use std::collections::HashMap;
+
+impl Database {
+    // 整体替换角色的标签
+    pub async fn set_role_tags(&self, role_id: i32, tags: &[String]) -> Result<()> {
+        let txn = self.connection.begin().await?;
+
+        role_tags::Entity::delete_many()
+            .filter(role_tags::Column::RoleId.eq(role_id))
+            .exec(&txn)
+            .await?;
+
+        if !tags.is_empty() {
+            role_tags::Entity::insert_many(tags.iter().map(|tag| role_tags::ActiveModel {
+                id: ActiveValue::default(),
+                role_id: Set(role_id),
+                tag: Set(tag.clone()),
+            }))
+            .exec(&txn)
+            .await?;
+        }
+
+        txn.commit().await?;
+
+        self.index_role(role_id).await?;
+
+        Ok(())
+    }
+
+    pub async fn set_role_category(&self, role_id: i32, category: Option<Category>) -> Result<()> {
+        roles::Entity::update_many()
+            .col_expr(roles::Column::Category, Expr::value(category))
+            .filter(roles::Column::Id.eq(role_id))
+            .exec(&self.connection)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_role_tags(&self, role_id: i32) -> Result<Vec<String>> {
+        let mut tags = self.get_roles_tags(&[role_id]).await?;
+
+        Ok(tags.remove(&role_id).unwrap_or_default())
+    }
+
+    // 角色ID -> 标签，按添加顺序排列
+    pub async fn get_roles_tags(&self, role_ids: &[i32]) -> Result<HashMap<i32, Vec<String>>> {
+        let mut tags: HashMap<i32, Vec<String>> = HashMap::new();
+
+        if role_ids.is_empty() {
+            return Ok(tags);
+        }
+
+        let rows = role_tags::Entity::find()
+            .filter(role_tags::Column::RoleId.is_in(role_ids.iter().copied()))
+            .order_by_asc(role_tags::Column::Id)
+            .all(&self.connection)
+            .await?;
+
+        for row in rows {
+            tags.entry(row.role_id).or_default().push(row.tag);
+        }
+
+        Ok(tags)
+    }
+
+    // 符合筛选条件的全部公开角色的分面统计
+    pub async fn role_facets(&self, filter: &RoleFilter) -> Result<Facets> {
+        let roles = roles::Entity::find()
+            .select_only()
+            .columns(RoleHit::COLUMNS)
+            .filter(filter.condition())
+            .into_model::<RoleHit>()
+            .all(&self.connection)
+            .await?;
+
+        self.count_role_facets(&roles).await
+    }
+
+    pub async fn count_role_facets(&self, roles: &[RoleHit]) -> Result<Facets> {
+        let ids: Vec<i32> = roles.iter().map(|role| role.id).collect();
+        let tags = self.get_roles_tags(&ids).await?;
+
+        Ok(Facets::count(roles, &tags))
+    }
+}
@@ -0,0 +1,134 @@
+use super::Database;
+use crate::database::models::{
+    role_versions,
+    roles::{self, AgeGroup, ExampleDialogue, Gender},
+};
+use anyhow::Result;
+use chrono::Utc;
+use sea_orm::{
+    ActiveValue::{self, Set},
+    ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter, QueryOrder, QuerySelect,
+    TransactionTrait,
+    prelude::Expr,
+};
+
+impl Database {
+    // 记录角色当前定义为一个版本
+    pub(super) async fn add_role_version<C>(&self, db: &C, role: &roles::Model) -> Result<()>
+    where
+        C: ConnectionTrait,
+    {
+        let version = role_versions::ActiveModel {
+            id: ActiveValue::default(),
+            role_id: Set(role.id),
+            version: Set(role.version),
+            name: Set(role.name.clone()),
+            description: Set(role.description.clone()),
+            traits: Set(role.traits.clone()),
+            image: Set(role.image.clone()),
+            gender: Set(role.gender.clone()),
+            age_group: Set(role.age_group.clone()),
+            voice_type: Set(role.voice_type.clone()),
+            example_dialogues: Set(role.example_dialogues.clone()),
+            greetings: Set(role.greetings.clone()),
+            created_at: Set(Utc::now().timestamp_millis()),
+        };
+
+        role_versions::Entity::insert(version).exec(db).await?;
+
+        Ok(())
+    }
+
+    // 修改角色设定并生成新版本，返回新版本号；可见性、分类等不属于设定的列保持不变。
+    // 版本号在数据库中自增，更新和记录版本在同一事务中，并发修改时行锁保证版本号不重复
+    pub async fn update_role(
+        &self,
+        role_id: i32,
+        name: &str,
+        description: &str,
+        traits: &str,
+        image: &str,
+        gender: Gender,
+        age_group: AgeGroup,
+        voice_type: &str,
+        example_dialogues: &[ExampleDialogue],
+        greetings: &[String],
+    ) -> Result<i32> {
+        let txn = self.connection.begin().await?;
+
+        let role = roles::Entity::find_by_id(role_id)
+            .lock_exclusive()
+            .one(&txn)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Role not found"))?;
+
+        let mut update = roles::Entity::update_many()
+            .col_expr(roles::Column::Name, Expr::value(name))
+            .col_expr(roles::Column::Description, Expr::value(description))
+            .col_expr(roles::Column::Traits, Expr::value(traits))
+            .col_expr(roles::Column::Image, Expr::value(image))
+            .col_expr(roles::Column::Gender, Expr::value(gender))
+            .col_expr(roles::Column::AgeGroup, Expr::value(age_group))
+            .col_expr(roles::Column::VoiceType, Expr::value(voice_type))
+            .col_expr(
+                roles::Column::ExampleDialogues,
+                Expr::value(ExampleDialogue::to_json(example_dialogues)),
+            )
+            .col_expr(
+                roles::Column::Greetings,
+                Expr::value(serde_json::to_string(greetings)?),
+            )
+            .col_expr(
+                roles::Column::Version,
+                Expr::col(roles::Column::Version).add(1),
+            )
+            .filter(roles::Column::Id.eq(role_id));
+
+        // 换了头像，原来的署名不再适用
+        if role.image != image {
+            update = update.col_expr(
+                roles::Column::ImageAttribution,
+                Expr::value(Option::<String>::None),
+            );
+        }
+
+        update.exec(&txn).await?;
+
+        let updated = roles::Entity::find_by_id(role_id)
+            .one(&txn)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Role not found"))?;
+
+        self.add_role_version(&txn, &updated).await?;
+
+        txn.commit().await?;
+
+        self.index_role(role_id).await?;
+
+        Ok(updated.version)
+    }
+
+    pub async fn list_role_versions(&self, role_id: i32) -> Result<Vec<role_versions::Model>> {
+        let versions = role_versions::Entity::find()
+            .filter(role_versions::Column::RoleId.eq(role_id))
+            .order_by_desc(role_versions::Column::Version)
+            .all(&self.connection)
+            .await?;
+
+        Ok(versions)
+    }
+
+    pub async fn get_role_version(
+        &self,
+        role_id: i32,
+        version: i32,
+    ) -> Result<role_versions::Model> {
+        let version = role_versions::Entity::find()
+            .filter(role_versions::Column::RoleId.eq(role_id))
+            .filter(role_versions::Column::Version.eq(version))
+            .one(&self.connection)
+            .await?;
+
+        version.ok_or_else(|| anyhow::anyhow!("Role version not found"))
+    }
+}
@@ -0,0 +1,92 @@
+use serde::Serialize;
+
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Op {
+    Equal,
+    Insert,
+    Delete,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Line {
+    pub op: Op,
+    pub text: String,
+}
+
+// 基于最长公共子序列的逐行对比
+pub fn diff_lines(old: &str, new: &str) -> Vec<Line> {
+    let old = old.lines().collect::<Vec<&str>>();
+    let new = new.lines().collect::<Vec<&str>>();
+
+    // lcs[i][j]为old[i..]和new[j..]的最长公共子序列长度
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut lines = Vec::new();
+    let (mut i, mut j) = (0, 0);
+
+    while i < old.len() || j < new.len() {
+        let (op, text) = if i < old.len() && j < new.len() && old[i] == new[j] {
+            i += 1;
+            j += 1;
+            (Op::Equal, old[i - 1])
+        } else if j < new.len() && (i == old.len() || lcs[i][j + 1] >= lcs[i + 1][j]) {
+            j += 1;
+            (Op::Insert, new[j - 1])
+        } else {
+            i += 1;
+            (Op::Delete, old[i - 1])
+        };
+
+        lines.push(Line {
+            op,
+            text: text.to_string(),
+        });
+    }
+
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_lines() {
+        let lines = diff_lines("a\nb\nc", "a\nx\nc\nd");
+
+        let ops = lines
+            .iter()
+            .map(|line| (&line.op, line.text.as_str()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            ops,
+            vec![
+                (&Op::Equal, "a"),
+                (&Op::Insert, "x"),
+                (&Op::Delete, "b"),
+                (&Op::Equal, "c"),
+                (&Op::Insert, "d"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_identical() {
+        assert!(
+            diff_lines("a\nb", "a\nb")
+                .iter()
+                .all(|line| line.op == Op::Equal)
+        );
+    }
+}
@@ -17,6 +17,9 @@ pub struct Env {
     pub mysql_endpoint: String,
     pub qiniu_llm_model: String,
     pub qiniu_llm_thinking_model: String,
+    pub wiki_endpoints: String,
+    pub qiniu_embedding_model: String,
+    pub admin_usernames: Vec<String>,
 }
 
 impl Env {
@@ -38,6 +41,21 @@ impl Env {
         );
         let qiniu_llm_thinking_model =
             get_env_value_option("QINIU_LLM_THINKING_MODEL", "deepseek-r1-0528".to_string());
+        let wiki_endpoints = get_env_value_option(
+            "WIKI_ENDPOINTS",
+            crate::knowledge::DEFAULT_ENDPOINTS.to_string(),
+        );
+
+        // 为空时关闭语义搜索
+        let qiniu_embedding_model = get_env_value_option("QINIU_EMBEDDING_MODEL", String::new());
+
+        // 逗号分隔，启动时设为管理员
+        let admin_usernames = get_env_value_option("ADMIN_USERNAMES", String::new())
+            .split(',')
+            .map(str::trim)
+            .filter(|username| !username.is_empty())
+            .map(str::to_string)
+            .collect();
 
         Self {
             port,
@@ -50,6 +68,9 @@ impl Env {
             mysql_endpoint,
             qiniu_llm_model,
             qiniu_llm_thinking_model,
+            wiki_endpoints,
+            qiniu_embedding_model,
+            admin_usernames,
         }
     }
 }
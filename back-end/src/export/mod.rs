@@ -0,0 +1,403 @@
+use crate::storage::StorageClient;
+use anyhow::Result;
+use base64::{Engine, engine::general_purpose};
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+
+pub const FORMAT_VERSION: u32 = 1;
+
+// 内联媒体时单个文件和全部文件的大小上限
+const MAX_MEDIA_BYTES: usize = 10 * 1024 * 1024;
+const MAX_EMBED_BYTES: usize = 50 * 1024 * 1024;
+
+#[derive(Clone, Copy, Deserialize)]
+pub enum ExportFormat {
+    #[serde(rename = "markdown")]
+    Markdown,
+    #[serde(rename = "json")]
+    Json,
+    #[serde(rename = "html")]
+    Html,
+}
+
+impl ExportFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Markdown => "text/markdown; charset=utf-8",
+            Self::Json => "application/json; charset=utf-8",
+            Self::Html => "text/html; charset=utf-8",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Markdown => "md",
+            Self::Json => "json",
+            Self::Html => "html",
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct Transcript {
+    pub format_version: u32,
+    pub title: String,
+    pub topic: Option<String>,
+    pub exported_at: i64,
+    pub summary: String,
+    pub speakers: Vec<Speaker>,
+    pub messages: Vec<Message>,
+}
+
+#[derive(Serialize)]
+pub struct Speaker {
+    pub key: String,
+    pub name: String,
+    pub avatar: String,
+    pub role: Option<RoleDefinition>,
+}
+
+#[derive(Serialize)]
+pub struct RoleDefinition {
+    pub role_id: i32,
+    pub description: String,
+    pub traits: String,
+    pub voice_type: String,
+}
+
+#[derive(Serialize)]
+pub struct Message {
+    pub speaker: String,
+    pub timestamp: i64, // 毫秒
+    pub text: String,
+    pub voice: Option<String>,
+}
+
+impl Transcript {
+    pub fn render(&self, format: ExportFormat) -> Result<String> {
+        match format {
+            ExportFormat::Markdown => Ok(self.to_markdown()),
+            ExportFormat::Json => Ok(serde_json::to_string_pretty(self)?),
+            ExportFormat::Html => Ok(self.to_html()),
+        }
+    }
+
+    fn speaker(&self, key: &str) -> Option<&Speaker> {
+        self.speakers.iter().find(|s| s.key == key)
+    }
+
+    fn speaker_name<'a>(&'a self, key: &'a str) -> &'a str {
+        self.speaker(key).map(|s| s.name.as_str()).unwrap_or(key)
+    }
+
+    fn summary_or_default(&self) -> &str {
+        if self.summary.trim().is_empty() {
+            "无"
+        } else {
+            &self.summary
+        }
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut md = format!("# {}\n\n", self.title);
+
+        if let Some(topic) = &self.topic {
+            md.push_str(&format!("**辩题**：{}\n\n", topic));
+        }
+        md.push_str(&format!(
+            "> 导出时间：{}\n\n",
+            format_timestamp(self.exported_at)
+        ));
+
+        md.push_str("## 角色设定\n\n");
+        for speaker in &self.speakers {
+            if let Some(role) = &speaker.role {
+                md.push_str(&format!(
+                    "### {}\n\n{}\n\n{}\n\n",
+                    speaker.name, role.description, role.traits
+                ));
+            }
+        }
+
+        md.push_str(&format!("## 记忆摘要\n\n{}\n\n", self.summary_or_default()));
+
+        md.push_str("## 对话记录\n\n");
+        for message in &self.messages {
+            md.push_str(&format!(
+                "**{}** · {}\n\n{}\n\n",
+                self.speaker_name(&message.speaker),
+                format_timestamp(message.timestamp),
+                message.text
+            ));
+            if let Some(voice) = &message.voice {
+                md.push_str(&format!("[🔊 语音]({})\n\n", voice));
+            }
+        }
+
+        md
+    }
+
+    pub fn to_html(&self) -> String {
+        let mut roles = String::new();
+        for speaker in &self.speakers {
+            if let Some(role) = &speaker.role {
+                roles.push_str(&format!(
+                    r#"<div class="role"><img class="avatar" src="{}" alt=""><div><h3>{}</h3><p>{}</p><p>{}</p></div></div>"#,
+                    escape_html(&speaker.avatar),
+                    escape_html(&speaker.name),
+                    escape_html(&role.description),
+                    escape_html(&role.traits)
+                ));
+            }
+        }
+
+        let mut messages = String::new();
+        for message in &self.messages {
+            let (name, avatar, is_role) = match self.speaker(&message.speaker) {
+                Some(speaker) => (
+                    speaker.name.as_str(),
+                    speaker.avatar.as_str(),
+                    speaker.role.is_some(),
+                ),
+                None => (message.speaker.as_str(), "", true),
+            };
+
+            let voice = message
+                .voice
+                .as_ref()
+                .map(|voice| {
+                    format!(
+                        r#"<audio controls preload="none" src="{}"></audio>"#,
+                        escape_html(voice)
+                    )
+                })
+                .unwrap_or_default();
+
+            messages.push_str(&format!(
+                r#"<div class="message {}"><img class="avatar" src="{}" alt=""><div class="bubble"><div class="meta">{} · {}</div><p>{}</p>{}</div></div>"#,
+                if is_role { "role-message" } else { "user-message" },
+                escape_html(avatar),
+                escape_html(name),
+                format_timestamp(message.timestamp),
+                escape_html(&message.text),
+                voice
+            ));
+        }
+
+        let topic = self
+            .topic
+            .as_ref()
+            .map(|topic| format!("<p><strong>辩题</strong>：{}</p>", escape_html(topic)))
+            .unwrap_or_default();
+
+        format!(
+            r#"<!DOCTYPE html>
+<html lang="zh-CN">
+<head>
+<meta charset="utf-8">
+<title>{title}</title>
+<style>
+body {{ font-family: -apple-system, "PingFang SC", "Microsoft YaHei", sans-serif; max-width: 860px; margin: 0 auto; padding: 24px; color: #222; background: #f6f7f9; }}
+.avatar {{ width: 48px; height: 48px; border-radius: 50%; object-fit: cover; flex-shrink: 0; background: #ddd; }}
+.role, .message {{ display: flex; gap: 12px; margin: 16px 0; }}
+.user-message {{ flex-direction: row-reverse; }}
+.bubble {{ background: #fff; border-radius: 12px; padding: 8px 14px; max-width: 75%; box-shadow: 0 1px 2px rgba(0, 0, 0, .08); }}
+.user-message .bubble {{ background: #dcf3ff; }}
+.meta {{ font-size: 12px; color: #888; }}
+p {{ white-space: pre-wrap; }}
+audio {{ width: 100%; margin-top: 4px; }}
+</style>
+</head>
+<body>
+<h1>{title}</h1>
+{topic}
+<p class="meta">导出时间：{exported_at}</p>
+<h2>角色设定</h2>
+{roles}
+<h2>记忆摘要</h2>
+<p>{summary}</p>
+<h2>对话记录</h2>
+{messages}
+</body>
+</html>
+"#,
+            title = escape_html(&self.title),
+            topic = topic,
+            exported_at = format_timestamp(self.exported_at),
+            roles = roles,
+            summary = escape_html(self.summary_or_default()),
+            messages = messages,
+        )
+    }
+
+    // 将头像和语音下载后内联为data URI，使导出的页面可以离线查看；
+    // 只内联存储空间中的文件，总量超过限制后其余的保留链接
+    pub async fn embed_media(&mut self, storage_client: &StorageClient) {
+        let mut budget = MAX_EMBED_BYTES;
+
+        for speaker in &mut self.speakers {
+            if let Some(data_uri) =
+                fetch_data_uri(storage_client, &speaker.avatar, &mut budget).await
+            {
+                speaker.avatar = data_uri;
+            }
+        }
+
+        for message in &mut self.messages {
+            if let Some(voice) = &message.voice
+                && let Some(data_uri) = fetch_data_uri(storage_client, voice, &mut budget).await
+            {
+                message.voice = Some(data_uri);
+            }
+        }
+    }
+}
+
+async fn fetch_data_uri(
+    storage_client: &StorageClient,
+    url: &str,
+    budget: &mut usize,
+) -> Option<String> {
+    if url.is_empty() || url.starts_with("data:") || *budget == 0 {
+        return None;
+    }
+
+    match storage_client
+        .fetch_object(url, MAX_MEDIA_BYTES.min(*budget))
+        .await
+    {
+        Ok((content_type, data)) => {
+            *budget -= data.len();
+
+            Some(format!(
+                "data:{};base64,{}",
+                content_type.unwrap_or_else(|| guess_mime_type(url).to_string()),
+                general_purpose::STANDARD.encode(data)
+            ))
+        }
+        Err(e) => {
+            tracing::warn!("Failed to embed media {}: {}", url, e);
+            None
+        }
+    }
+}
+
+fn guess_mime_type(url: &str) -> &'static str {
+    match url.rsplit('.').next().unwrap_or_default() {
+        "mp3" => "audio/mpeg",
+        "wav" => "audio/wav",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        _ => "application/octet-stream",
+    }
+}
+
+pub fn format_timestamp(timestamp_millis: i64) -> String {
+    // 统一按北京时间展示
+    let offset = FixedOffset::east_opt(8 * 3600).unwrap();
+
+    DateTime::from_timestamp_millis(timestamp_millis)
+        .map(|dt| {
+            dt.with_timezone(&offset)
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string()
+        })
+        .unwrap_or_default()
+}
+
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Transcript {
+        Transcript {
+            format_version: FORMAT_VERSION,
+            title: "与爱因斯坦的对话".to_string(),
+            topic: None,
+            exported_at: 0,
+            summary: String::new(),
+            speakers: vec![
+                Speaker {
+                    key: "user".to_string(),
+                    name: "alice".to_string(),
+                    avatar: "http://example.com/alice.png".to_string(),
+                    role: None,
+                },
+                Speaker {
+                    key: "role_1".to_string(),
+                    name: "爱因斯坦".to_string(),
+                    avatar: "http://example.com/einstein.png".to_string(),
+                    role: Some(RoleDefinition {
+                        role_id: 1,
+                        description: "物理学家".to_string(),
+                        traits: "幽默".to_string(),
+                        voice_type: "qiniu_zh_male_ljfdxz".to_string(),
+                    }),
+                },
+            ],
+            messages: vec![
+                Message {
+                    speaker: "user".to_string(),
+                    timestamp: 1_700_000_000_000,
+                    text: "<script>alert(1)</script>".to_string(),
+                    voice: None,
+                },
+                Message {
+                    speaker: "role_1".to_string(),
+                    timestamp: 1_700_000_001_000,
+                    text: "嗯……让我想想".to_string(),
+                    voice: Some("http://example.com/a.mp3".to_string()),
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_markdown() {
+        let md = sample().to_markdown();
+
+        assert!(md.starts_with("# 与爱因斯坦的对话"));
+        assert!(md.contains("### 爱因斯坦\n\n物理学家\n\n幽默"));
+        assert!(md.contains("## 记忆摘要\n\n无"));
+        assert!(md.contains("**爱因斯坦** · 2023-11-15 06:13:21"));
+        assert!(md.contains("[🔊 语音](http://example.com/a.mp3)"));
+    }
+
+    #[test]
+    fn test_html_escapes_text() {
+        let html = sample().to_html();
+
+        assert!(!html.contains("<script>"));
+        assert!(html.contains("&lt;script&gt;alert(1)&lt;/script&gt;"));
+        assert!(html.contains(r#"<audio controls preload="none" src="http://example.com/a.mp3">"#));
+    }
+
+    #[test]
+    fn test_json() {
+        let json = sample().render(ExportFormat::Json).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(value["format_version"], FORMAT_VERSION);
+        assert_eq!(value["speakers"][1]["role"]["role_id"], 1);
+        assert_eq!(value["messages"].as_array().unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,344 @@
+use crate::database::models::roles::{AgeGroup, Gender};
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, NaiveDateTime};
+use serde::Deserialize;
+use serde_json::Value;
+
+pub struct ImportedDialog {
+    pub is_user: bool,
+    pub timestamp: i64, // 毫秒
+    pub text: String,
+}
+
+#[derive(Deserialize)]
+struct ChatLine {
+    #[serde(default)]
+    is_user: bool,
+    #[serde(default)]
+    is_system: bool,
+    #[serde(default)]
+    send_date: Option<Value>,
+    mes: String,
+}
+
+// 解析SillyTavern的.jsonl聊天记录，第一行为元数据，之后每行一条消息
+// 无法解析时间的消息依次排在前一条消息之后，首条消息使用fallback_start
+pub fn parse_sillytavern_chat(text: &str, fallback_start: i64) -> Result<Vec<ImportedDialog>> {
+    let mut dialogs: Vec<ImportedDialog> = Vec::new();
+
+    for line in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+        let value: Value = serde_json::from_str(line)?;
+
+        // 元数据行没有mes字段
+        if value.get("mes").is_none() {
+            continue;
+        }
+
+        let chat_line: ChatLine = serde_json::from_value(value)?;
+
+        if chat_line.is_system || chat_line.mes.trim().is_empty() {
+            continue;
+        }
+
+        let previous = dialogs.last().map(|d| d.timestamp);
+        let timestamp = match (
+            chat_line.send_date.as_ref().and_then(parse_send_date),
+            previous,
+        ) {
+            // 保证时间戳严格递增，以维持对话顺序
+            (Some(timestamp), Some(previous)) if timestamp <= previous => previous + 1,
+            (Some(timestamp), _) => timestamp,
+            (None, Some(previous)) => previous + 1000,
+            (None, None) => fallback_start,
+        };
+
+        dialogs.push(ImportedDialog {
+            is_user: chat_line.is_user,
+            timestamp,
+            text: chat_line.mes.trim().to_string(),
+        });
+    }
+
+    if dialogs.is_empty() {
+        return Err(anyhow!("No messages found in chat log"));
+    }
+
+    Ok(dialogs)
+}
+
+fn parse_send_date(value: &Value) -> Option<i64> {
+    if let Some(timestamp) = value.as_i64() {
+        return Some(timestamp);
+    }
+
+    let text = value.as_str()?.trim();
+
+    if let Ok(timestamp) = text.parse::<i64>() {
+        return Some(timestamp);
+    }
+
+    if let Ok(dt) = DateTime::parse_from_rfc3339(text) {
+        return Some(dt.timestamp_millis());
+    }
+
+    // SillyTavern不同版本使用的时间格式，如"July 2, 2023 1:23pm"和"2024-5-12 @18h 30m 12s 123ms"
+    const FORMATS: [&str; 5] = [
+        "%B %d, %Y %I:%M%P",
+        "%B %d, %Y %I:%M %P",
+        "%Y-%m-%d@%Hh%Mm%Ss",
+        "%Y-%m-%d @%Hh %Mm %Ss",
+        "%Y-%m-%d %H:%M:%S",
+    ];
+
+    // 去掉末尾的毫秒部分
+    let text = match text.rsplit_once(' ') {
+        Some((head, tail)) if tail.ends_with("ms") => head,
+        _ => text,
+    };
+
+    FORMATS.iter().find_map(|format| {
+        NaiveDateTime::parse_from_str(text, format)
+            .ok()
+            .map(|dt| dt.and_utc().timestamp_millis())
+    })
+}
+
+// 批量生成角色的名单中的一项
+#[derive(Debug, PartialEq, Deserialize)]
+pub struct RoleEntry {
+    pub name: String,
+    #[serde(default, deserialize_with = "deserialize_gender")]
+    pub gender: Option<Gender>,
+    #[serde(default, deserialize_with = "deserialize_age_group")]
+    pub age_group: Option<AgeGroup>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum JsonEntry {
+    Name(String),
+    Entry(RoleEntry),
+}
+
+// 解析批量生成的名单，以"["开头时按JSON数组解析，否则按CSV解析
+// JSON数组的元素可以是名字字符串，或包含name、gender、age_group、tags的对象
+// CSV的列依次为name、gender、age_group、tags，首行为表头时跳过，多个标签以";"或"|"分隔
+pub fn parse_role_list(text: &str) -> Result<Vec<RoleEntry>> {
+    let text = text.trim_start_matches('\u{feff}').trim();
+
+    let entries = if text.starts_with('[') {
+        let entries: Vec<JsonEntry> = serde_json::from_str(text)?;
+
+        entries
+            .into_iter()
+            .map(|entry| match entry {
+                JsonEntry::Name(name) => RoleEntry {
+                    name,
+                    gender: None,
+                    age_group: None,
+                    tags: Vec::new(),
+                },
+                JsonEntry::Entry(entry) => entry,
+            })
+            .map(|entry| RoleEntry {
+                name: entry.name.trim().to_string(),
+                tags: clean_tags(entry.tags.iter().map(String::as_str)),
+                ..entry
+            })
+            .filter(|entry| !entry.name.is_empty())
+            .collect()
+    } else {
+        parse_role_csv(text)?
+    };
+
+    if entries.is_empty() {
+        return Err(anyhow!("No names found in role list"));
+    }
+
+    Ok(entries)
+}
+
+fn parse_role_csv(text: &str) -> Result<Vec<RoleEntry>> {
+    let mut entries = Vec::new();
+
+    for (index, line) in text.lines().enumerate() {
+        let fields = split_csv_line(line);
+        let field = |i: usize| fields.get(i).map(|f| f.trim()).unwrap_or_default();
+
+        if index == 0 && field(0).eq_ignore_ascii_case("name") {
+            continue;
+        }
+
+        if field(0).is_empty() {
+            continue;
+        }
+
+        let line_number = index + 1;
+
+        entries.push(RoleEntry {
+            name: field(0).to_string(),
+            gender: parse_gender(field(1)).map_err(|e| anyhow!("Line {}: {}", line_number, e))?,
+            age_group: parse_age_group(field(2))
+                .map_err(|e| anyhow!("Line {}: {}", line_number, e))?,
+            tags: clean_tags(field(3).split([';', '|'])),
+        });
+    }
+
+    Ok(entries)
+}
+
+// 按逗号切分一行，支持双引号包裹的字段和""转义
+fn split_csv_line(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+
+    fields.push(field);
+
+    fields
+}
+
+fn clean_tags<'a>(tags: impl Iterator<Item = &'a str>) -> Vec<String> {
+    let mut cleaned: Vec<String> = Vec::new();
+
+    for tag in tags.map(str::trim).filter(|tag| !tag.is_empty()) {
+        if !cleaned.iter().any(|t| t == tag) {
+            cleaned.push(tag.to_string());
+        }
+    }
+
+    cleaned
+}
+
+fn parse_gender(value: &str) -> Result<Option<Gender>> {
+    match value.to_lowercase().as_str() {
+        "" => Ok(None),
+        "male" | "男" => Ok(Some(Gender::Male)),
+        "female" | "女" => Ok(Some(Gender::Female)),
+        _ => Err(anyhow!("Unknown gender: {}", value)),
+    }
+}
+
+fn parse_age_group(value: &str) -> Result<Option<AgeGroup>> {
+    match value.to_lowercase().as_str() {
+        "" => Ok(None),
+        "mature" | "成熟" => Ok(Some(AgeGroup::Mature)),
+        "young" | "年轻" => Ok(Some(AgeGroup::Young)),
+        _ => Err(anyhow!("Unknown age group: {}", value)),
+    }
+}
+
+fn deserialize_gender<'de, D>(deserializer: D) -> Result<Option<Gender>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let value = Option::<String>::deserialize(deserializer)?.unwrap_or_default();
+
+    parse_gender(value.trim()).map_err(serde::de::Error::custom)
+}
+
+fn deserialize_age_group<'de, D>(deserializer: D) -> Result<Option<AgeGroup>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let value = Option::<String>::deserialize(deserializer)?.unwrap_or_default();
+
+    parse_age_group(value.trim()).map_err(serde::de::Error::custom)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_sillytavern_chat() {
+        let chat = r#"{"user_name":"You","character_name":"Sherlock","create_date":"2023-7-2@13h23m45s","chat_metadata":{}}
+{"name":"Sherlock","is_user":false,"send_date":"July 2, 2023 1:23pm","mes":"Hello."}
+{"name":"You","is_user":true,"send_date":"July 2, 2023 1:23pm","mes":"Hi!"}
+{"name":"System","is_user":false,"is_system":true,"send_date":"July 2, 2023 1:24pm","mes":"note"}
+{"name":"Sherlock","is_user":false,"send_date":"unknown","mes":"Elementary."}
+"#;
+
+        let dialogs = parse_sillytavern_chat(chat, 0).unwrap();
+
+        assert_eq!(dialogs.len(), 3);
+        assert!(!dialogs[0].is_user);
+        assert_eq!(dialogs[0].timestamp, 1688304180000);
+        assert!(dialogs[1].is_user);
+        assert_eq!(dialogs[1].timestamp, 1688304180001);
+        assert_eq!(dialogs[2].text, "Elementary.");
+        assert_eq!(dialogs[2].timestamp, 1688304181001);
+    }
+
+    #[test]
+    fn test_parse_send_date() {
+        assert_eq!(
+            parse_send_date(&Value::from(1700000000000i64)),
+            Some(1700000000000)
+        );
+        assert_eq!(
+            parse_send_date(&Value::from("2023-11-14T22:13:20Z")),
+            Some(1700000000000)
+        );
+        assert_eq!(
+            parse_send_date(&Value::from("2023-11-14@22h13m20s")),
+            Some(1700000000000)
+        );
+        assert_eq!(
+            parse_send_date(&Value::from("2023-11-14 @22h 13m 20s 512ms")),
+            Some(1700000000000)
+        );
+        assert_eq!(parse_send_date(&Value::from("yesterday")), None);
+    }
+
+    #[test]
+    fn test_parse_empty_chat() {
+        assert!(parse_sillytavern_chat(r#"{"user_name":"You"}"#, 0).is_err());
+    }
+
+    #[test]
+    fn test_parse_role_list_csv() {
+        let text = "name,gender,age_group,tags\n李白,男,,诗人;唐朝\n\"Curie, Marie\",female,mature,\n\n杜甫\n";
+        let entries = parse_role_list(text).unwrap();
+
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].name, "李白");
+        assert_eq!(entries[0].gender, Some(Gender::Male));
+        assert_eq!(entries[0].age_group, None);
+        assert_eq!(entries[0].tags, vec!["诗人", "唐朝"]);
+        assert_eq!(entries[1].name, "Curie, Marie");
+        assert_eq!(entries[1].age_group, Some(AgeGroup::Mature));
+        assert!(entries[2].tags.is_empty());
+
+        assert!(parse_role_list("李白,unknown").is_err());
+    }
+
+    #[test]
+    fn test_parse_role_list_json() {
+        let text = r#"["李白", {"name": "居里夫人", "gender": "female", "tags": ["科学家", "科学家"]}, " "]"#;
+        let entries = parse_role_list(text).unwrap();
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].name, "李白");
+        assert_eq!(entries[0].gender, None);
+        assert_eq!(entries[1].gender, Some(Gender::Female));
+        assert_eq!(entries[1].tags, vec!["科学家"]);
+
+        assert!(parse_role_list("[]").is_err());
+    }
+}
@@ -0,0 +1,801 @@
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+// 默认按中文、英文、日文的顺序查询
+pub const DEFAULT_ENDPOINTS: &str = "zh=https://zh.wikipedia.org/w/api.php,en=https://en.wikipedia.org/w/api.php,ja=https://ja.wikipedia.org/w/api.php";
+
+// 参考资料类章节对角色扮演没有帮助
+const SKIPPED_SECTIONS: [&str; 14] = [
+    "References",
+    "See also",
+    "External links",
+    "Notes",
+    "Further reading",
+    "Bibliography",
+    "参考文献",
+    "参考资料",
+    "参见",
+    "外部链接",
+    "注释",
+    "脚注",
+    "関連項目",
+    "外部リンク",
+];
+
+// 语录中的这些章节是他人的评价或误传，不是本人的话
+const SKIPPED_QUOTE_SECTIONS: [&str; 10] = [
+    "About",
+    "Misattributed",
+    "Disputed",
+    "Quotes about",
+    "External links",
+    "See also",
+    "关于",
+    "误传",
+    "外部链接",
+    "参见",
+];
+
+// 单条语录的长度范围（字符）和最多采用的条数
+const QUOTE_MIN_CHARS: usize = 4;
+const QUOTE_MAX_CHARS: usize = 200;
+const MAX_QUOTES: usize = 5;
+
+pub struct Article {
+    pub lang: String,
+    // 条目在该语言维基百科中的页面ID
+    pub page_id: i64,
+    pub title: String,
+    pub intro: String,
+    pub sections: Vec<Section>,
+}
+
+pub struct Section {
+    pub title: String,
+    pub text: String,
+}
+
+impl Article {
+    // 导言加上正文章节，总长度不超过max_chars个字符
+    pub fn to_context(&self, max_chars: usize) -> String {
+        let mut context = self.intro.clone();
+
+        for section in &self.sections {
+            let part = format!("\n\n## {}\n{}", section.title, section.text);
+
+            if context.chars().count() + part.chars().count() > max_chars {
+                break;
+            }
+
+            context.push_str(&part);
+        }
+
+        context.chars().take(max_chars).collect()
+    }
+}
+
+// 条目首图，来自维基共享资源，需要保留署名和许可协议
+pub struct PageImage {
+    pub url: String,
+    pub attribution: ImageAttribution,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct ImageAttribution {
+    // 图片的文件描述页
+    pub source_url: String,
+    pub artist: String,
+    pub license: String,
+    pub license_url: String,
+}
+
+struct Page {
+    id: i64,
+    title: String,
+    extract: String,
+    // 语言代码 -> 该语言的条目标题
+    langlinks: HashMap<String, String>,
+}
+
+enum Backend {
+    Wiki(String),
+    #[cfg(test)]
+    Fixture(HashMap<String, Value>),
+}
+
+enum Site {
+    Wikipedia,
+    Wikiquote,
+}
+
+// 一个语言版本的维基百科，以及同语言的维基语录
+pub struct Source {
+    lang: String,
+    backend: Backend,
+}
+
+impl Source {
+    pub fn wiki(lang: &str, endpoint: &str) -> Self {
+        Self {
+            lang: lang.to_string(),
+            backend: Backend::Wiki(endpoint.to_string()),
+        }
+    }
+
+    // 使用本地保存的API响应，键为“类型:标题”
+    #[cfg(test)]
+    pub fn fixture(lang: &str, json: &str) -> Self {
+        let responses: HashMap<String, Value> = serde_json::from_str(json).unwrap();
+
+        Self {
+            lang: lang.to_string(),
+            backend: Backend::Fixture(responses),
+        }
+    }
+
+    async fn query(
+        &self,
+        site: Site,
+        kind: &str,
+        title: &str,
+        params: &[(&str, &str)],
+    ) -> Result<Option<Value>> {
+        match &self.backend {
+            Backend::Wiki(endpoint) => {
+                // 维基语录与维基百科的API地址只有域名不同，自定义的地址没有对应的语录站点
+                let endpoint = match site {
+                    Site::Wikipedia => endpoint.clone(),
+                    Site::Wikiquote if endpoint.contains("wikipedia.org") => {
+                        endpoint.replace("wikipedia.org", "wikiquote.org")
+                    }
+                    Site::Wikiquote => return Ok(None),
+                };
+
+                tracing::debug!("Querying {} for {}: {}", endpoint, kind, title);
+
+                let api = mediawiki::api::Api::new(&endpoint).await?;
+                let params = api.params_into(params);
+
+                Ok(Some(api.get_query_api_json_all(&params).await?))
+            }
+            #[cfg(test)]
+            Backend::Fixture(responses) => {
+                Ok(responses.get(&format!("{}:{}", kind, title)).cloned())
+            }
+        }
+    }
+
+    async fn page(&self, title: &str) -> Result<Option<Page>> {
+        // 不使用exintro，获取全文
+        let response = self
+            .query(
+                Site::Wikipedia,
+                "page",
+                title,
+                &[
+                    ("action", "query"),
+                    ("prop", "extracts|langlinks"),
+                    ("explaintext", ""),
+                    ("redirects", "1"),
+                    ("lllimit", "max"),
+                    ("titles", title),
+                    ("format", "json"),
+                ],
+            )
+            .await?;
+
+        match response {
+            Some(response) => parse_page(&response),
+            None => Ok(None),
+        }
+    }
+
+    async fn quotes(&self, title: &str) -> Result<Vec<String>> {
+        let response = self
+            .query(
+                Site::Wikiquote,
+                "quotes",
+                title,
+                &[
+                    ("action", "query"),
+                    ("prop", "revisions"),
+                    ("rvprop", "content"),
+                    ("rvslots", "main"),
+                    ("redirects", "1"),
+                    ("titles", title),
+                    ("format", "json"),
+                ],
+            )
+            .await?;
+
+        let wikitext = response
+            .as_ref()
+            .and_then(first_page)
+            .and_then(|page| page["revisions"][0]["slots"]["main"]["*"].as_str())
+            .unwrap_or_default();
+
+        Ok(parse_quotes(wikitext))
+    }
+
+    async fn page_image(&self, title: &str) -> Result<Option<PageImage>> {
+        let response = self
+            .query(
+                Site::Wikipedia,
+                "image",
+                title,
+                &[
+                    ("action", "query"),
+                    ("prop", "pageimages"),
+                    ("piprop", "original|name"),
+                    ("redirects", "1"),
+                    ("titles", title),
+                    ("format", "json"),
+                ],
+            )
+            .await?;
+
+        let Some(page) = response.as_ref().and_then(first_page) else {
+            return Ok(None);
+        };
+
+        let (Some(url), Some(name)) = (
+            page["original"]["source"].as_str(),
+            page["pageimage"].as_str(),
+        ) else {
+            return Ok(None);
+        };
+
+        let file = format!("File:{}", name);
+        let response = self
+            .query(
+                Site::Wikipedia,
+                "imageinfo",
+                &file,
+                &[
+                    ("action", "query"),
+                    ("prop", "imageinfo"),
+                    ("iiprop", "url|extmetadata"),
+                    ("titles", &file),
+                    ("format", "json"),
+                ],
+            )
+            .await?;
+
+        Ok(response
+            .as_ref()
+            .and_then(parse_attribution)
+            .map(|attribution| PageImage {
+                url: url.to_string(),
+                attribution,
+            }))
+    }
+}
+
+// 按顺序查询的知识来源链
+pub struct Knowledge {
+    sources: Vec<Source>,
+}
+
+impl Default for Knowledge {
+    fn default() -> Self {
+        Self::from_config(DEFAULT_ENDPOINTS).unwrap()
+    }
+}
+
+impl Knowledge {
+    pub fn new(sources: Vec<Source>) -> Self {
+        Self { sources }
+    }
+
+    // 配置格式：lang=endpoint，逗号分隔，顺序即优先级
+    pub fn from_config(config: &str) -> Result<Self> {
+        let sources = config
+            .split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .map(|item| {
+                let (lang, endpoint) = item
+                    .split_once('=')
+                    .ok_or_else(|| anyhow!("Invalid wiki endpoint: {}", item))?;
+
+                Ok(Source::wiki(lang.trim(), endpoint.trim()))
+            })
+            .collect::<Result<Vec<Source>>>()?;
+
+        Ok(Self::new(sources))
+    }
+
+    // 依次用各个标题查询各个来源。命中的条目若有更优先语言的版本，则通过语言链接改用该版本；
+    // 某个来源查询失败时记录日志，继续查询其他标题和来源
+    pub async fn lookup(&self, titles: &[&str]) -> Result<Option<Article>> {
+        for (index, source) in self.sources.iter().enumerate() {
+            for title in titles.iter().filter(|title| !title.trim().is_empty()) {
+                let page = match source.page(title).await {
+                    Ok(Some(page)) => page,
+                    Ok(None) => continue,
+                    Err(e) => {
+                        tracing::warn!("Failed to query {} wiki for {}: {}", source.lang, title, e);
+                        continue;
+                    }
+                };
+
+                for preferred in &self.sources[..index] {
+                    let Some(linked_title) = page.langlinks.get(&preferred.lang) else {
+                        continue;
+                    };
+
+                    match preferred.page(linked_title).await {
+                        Ok(Some(linked)) => return Ok(Some(to_article(&preferred.lang, linked))),
+                        Ok(None) => {}
+                        Err(e) => tracing::warn!(
+                            "Failed to query {} wiki for {}: {}",
+                            preferred.lang,
+                            linked_title,
+                            e
+                        ),
+                    }
+                }
+
+                return Ok(Some(to_article(&source.lang, page)));
+            }
+        }
+
+        Ok(None)
+    }
+
+    // 从条目所在语言的维基语录获取本人的语录
+    pub async fn quotes(&self, article: &Article) -> Result<Vec<String>> {
+        match self.source(&article.lang) {
+            Some(source) => source.quotes(&article.title).await,
+            None => Ok(Vec::new()),
+        }
+    }
+
+    // 条目首图，没有可用的许可信息时不使用
+    pub async fn page_image(&self, article: &Article) -> Result<Option<PageImage>> {
+        match self.source(&article.lang) {
+            Some(source) => source.page_image(&article.title).await,
+            None => Ok(None),
+        }
+    }
+
+    fn source(&self, lang: &str) -> Option<&Source> {
+        self.sources.iter().find(|source| source.lang == lang)
+    }
+}
+
+// 查询结果中第一个存在的页面，不存在的条目page编号为-1，或带有missing字段
+fn first_page(response: &Value) -> Option<&Value> {
+    response["query"]["pages"]
+        .as_object()?
+        .iter()
+        .find(|(id, page)| id.as_str() != "-1" && page.get("missing").is_none())
+        .map(|(_, page)| page)
+}
+
+fn parse_page(response: &Value) -> Result<Option<Page>> {
+    if !response["query"]["pages"].is_object() {
+        return Err(anyhow!("Invalid wiki response"));
+    }
+
+    let Some(page) = first_page(response) else {
+        return Ok(None);
+    };
+
+    let extract = page["extract"].as_str().unwrap_or_default().trim();
+
+    if extract.is_empty() {
+        return Ok(None);
+    }
+
+    let langlinks = page["langlinks"]
+        .as_array()
+        .map(|links| {
+            links
+                .iter()
+                .filter_map(|link| {
+                    Some((
+                        link["lang"].as_str()?.to_string(),
+                        link["*"].as_str()?.to_string(),
+                    ))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Ok(Some(Page {
+        id: page["pageid"].as_i64().unwrap_or_default(),
+        title: page["title"].as_str().unwrap_or_default().to_string(),
+        extract: extract.to_string(),
+        langlinks,
+    }))
+}
+
+// 维基共享资源中的文件在本地维基上page编号为负数，因此按imageinfo查找
+fn parse_attribution(response: &Value) -> Option<ImageAttribution> {
+    let info = response["query"]["pages"]
+        .as_object()?
+        .values()
+        .find_map(|page| page["imageinfo"].get(0))?;
+    let metadata = &info["extmetadata"];
+
+    let license = metadata["LicenseShortName"]["value"].as_str()?.trim();
+
+    if license.is_empty() {
+        return None;
+    }
+
+    Some(ImageAttribution {
+        source_url: info["descriptionurl"].as_str()?.to_string(),
+        artist: strip_markup(metadata["Artist"]["value"].as_str().unwrap_or_default()),
+        license: license.to_string(),
+        license_url: metadata["LicenseUrl"]["value"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string(),
+    })
+}
+
+// 语录条目中一级列表项是语录，二级列表项是出处
+fn parse_quotes(wikitext: &str) -> Vec<String> {
+    let mut quotes = Vec::new();
+    let mut skipped = false;
+
+    for line in wikitext.lines() {
+        let line = line.trim();
+
+        if line.starts_with("==") && line.ends_with("==") {
+            let title = line.trim_matches('=').trim();
+            skipped = SKIPPED_QUOTE_SECTIONS
+                .iter()
+                .any(|section| title.starts_with(section));
+            continue;
+        }
+
+        let Some(quote) = line.strip_prefix('*') else {
+            continue;
+        };
+
+        if skipped || quote.starts_with(['*', ':']) {
+            continue;
+        }
+
+        let quote = strip_markup(quote);
+        let chars = quote.chars().count();
+
+        if (QUOTE_MIN_CHARS..=QUOTE_MAX_CHARS).contains(&chars) && !quotes.contains(&quote) {
+            quotes.push(quote);
+        }
+
+        if quotes.len() >= MAX_QUOTES {
+            break;
+        }
+    }
+
+    quotes
+}
+
+// 去掉wikitext和HTML标记，只保留文字
+fn strip_markup(text: &str) -> String {
+    let text = remove_refs(text);
+    let mut result = String::new();
+    let mut chars = text.chars().peekable();
+    let mut template_depth = 0;
+    let mut in_tag = false;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                template_depth += 1;
+            }
+            '}' if template_depth > 0 && chars.peek() == Some(&'}') => {
+                chars.next();
+                template_depth -= 1;
+            }
+            _ if template_depth > 0 => {}
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            _ if in_tag => {}
+            '[' if chars.peek() == Some(&'[') => {
+                chars.next();
+                // [[目标|显示文字]]只保留显示文字
+                let mut link = String::new();
+                while let Some(c) = chars.next() {
+                    if c == ']' && chars.peek() == Some(&']') {
+                        chars.next();
+                        break;
+                    }
+                    link.push(c);
+                }
+                result.push_str(link.rsplit('|').next().unwrap_or_default());
+            }
+            '[' => {
+                // [网址 显示文字]只保留显示文字
+                let link: String = chars.by_ref().take_while(|&c| c != ']').collect();
+                result.push_str(
+                    link.split_once(' ')
+                        .map(|(_, text)| text)
+                        .unwrap_or_default(),
+                );
+            }
+            '\'' if chars.peek() == Some(&'\'') => {
+                while chars.peek() == Some(&'\'') {
+                    chars.next();
+                }
+            }
+            _ => result.push(c),
+        }
+    }
+
+    result.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+// <ref>…</ref>是脚注，连同内容一起去掉
+fn remove_refs(text: &str) -> String {
+    let mut text = text.to_string();
+
+    while let Some(start) = text.find("<ref") {
+        let tag_end = text[start..]
+            .find('>')
+            .map(|index| start + index + 1)
+            .unwrap_or(text.len());
+
+        let end = if text[..tag_end].ends_with("/>") {
+            tag_end
+        } else {
+            text[tag_end..]
+                .find("</ref>")
+                .map(|index| tag_end + index + "</ref>".len())
+                .unwrap_or(text.len())
+        };
+
+        text.replace_range(start..end, "");
+    }
+
+    text
+}
+
+fn to_article(lang: &str, page: Page) -> Article {
+    let (intro, sections) = split_sections(&page.extract);
+
+    Article {
+        lang: lang.to_string(),
+        page_id: page.id,
+        title: page.title,
+        intro,
+        sections,
+    }
+}
+
+// explaintext中的章节标题形如“== History ==”，子章节合并到所在章节
+fn split_sections(extract: &str) -> (String, Vec<Section>) {
+    let mut intro = Vec::new();
+    let mut sections: Vec<Section> = Vec::new();
+
+    for line in extract.lines() {
+        let trimmed = line.trim();
+
+        if trimmed.starts_with("==") && trimmed.ends_with("==") && trimmed.len() > 4 {
+            let title = trimmed.trim_matches('=').trim().to_string();
+            let level = trimmed.chars().take_while(|&c| c == '=').count();
+
+            if level == 2 {
+                sections.push(Section {
+                    title,
+                    text: String::new(),
+                });
+            } else if let Some(section) = sections.last_mut() {
+                section.text.push_str(&format!("\n{}\n", title));
+            }
+            continue;
+        }
+
+        match sections.last_mut() {
+            Some(section) => {
+                section.text.push_str(line);
+                section.text.push('\n');
+            }
+            None => intro.push(line),
+        }
+    }
+
+    let sections = sections
+        .into_iter()
+        .map(|section| Section {
+            title: section.title,
+            text: section.text.trim().to_string(),
+        })
+        .filter(|section| !section.text.is_empty())
+        .filter(|section| !SKIPPED_SECTIONS.contains(&section.title.as_str()))
+        .collect();
+
+    (intro.join("\n").trim().to_string(), sections)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn knowledge(langs: &[&str]) -> Knowledge {
+        Knowledge::new(
+            langs
+                .iter()
+                .map(|lang| match *lang {
+                    "zh" => Source::fixture("zh", include_str!("fixtures/zh.json")),
+                    "en" => Source::fixture("en", include_str!("fixtures/en.json")),
+                    _ => Source::fixture(lang, "{}"),
+                })
+                .collect(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_lookup_direct() {
+        let article = knowledge(&["zh", "en"])
+            .lookup(&["李白", "Li Bai"])
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(article.lang, "zh");
+        assert_eq!(article.page_id, 9514);
+        assert_eq!(article.title, "李白");
+        assert_eq!(article.intro, "李白，字太白，号青莲居士，唐朝诗人。");
+        assert_eq!(article.sections.len(), 1);
+        assert_eq!(article.sections[0].title, "生平");
+    }
+
+    #[tokio::test]
+    async fn test_lookup_langlinks() {
+        // 中文名查不到，通过英文条目的语言链接找到中文条目
+        let article = knowledge(&["zh", "en"])
+            .lookup(&["鸣人", "Naruto Uzumaki"])
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(article.lang, "zh");
+        assert_eq!(article.title, "漩涡鸣人");
+    }
+
+    #[tokio::test]
+    async fn test_lookup_fallback() {
+        let article = knowledge(&["ja", "en"])
+            .lookup(&["Naruto Uzumaki"])
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(article.lang, "en");
+        assert!(
+            knowledge(&["zh", "en"])
+                .lookup(&["Nobody"])
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_lookup_skips_failed_source() {
+        // 日文来源的响应无效，跳过它继续用其他来源查询和按语言链接切换
+        let broken = r#"{"page:Naruto Uzumaki": {}, "page:うずまきナルト": {}}"#;
+        let article = Knowledge::new(vec![
+            Source::fixture("ja", broken),
+            Source::fixture("zh", include_str!("fixtures/zh.json")),
+            Source::fixture("en", include_str!("fixtures/en.json")),
+        ])
+        .lookup(&["Naruto Uzumaki"])
+        .await
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(article.lang, "zh");
+        assert_eq!(article.title, "漩涡鸣人");
+    }
+
+    #[tokio::test]
+    async fn test_quotes() {
+        let zh = knowledge(&["zh", "en"]);
+        let article = zh.lookup(&["李白"]).await.unwrap().unwrap();
+
+        // 出处、重复的语录和他人的评价都不算
+        assert_eq!(
+            zh.quotes(&article).await.unwrap(),
+            vec![
+                "天生我材必有用，千金散尽还复来。",
+                "人生得意须尽欢，莫使金樽空对月。",
+                "举杯邀明月，对影成三人。",
+            ]
+        );
+
+        let article = knowledge(&["en"])
+            .lookup(&["Li Bai"])
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(zh.quotes(&article).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_page_image() {
+        let zh = knowledge(&["zh", "en"]);
+        let article = zh.lookup(&["李白"]).await.unwrap().unwrap();
+
+        let image = zh.page_image(&article).await.unwrap().unwrap();
+
+        assert_eq!(
+            image.url,
+            "https://upload.wikimedia.org/wikipedia/commons/a/a6/Li_Bai.jpg"
+        );
+        assert_eq!(
+            image.attribution,
+            ImageAttribution {
+                source_url: "https://commons.wikimedia.org/wiki/File:Li_Bai.jpg".to_string(),
+                artist: "Liang Kai".to_string(),
+                license: "Public domain".to_string(),
+                license_url: String::new(),
+            }
+        );
+
+        let article = zh.lookup(&["漩涡鸣人"]).await.unwrap().unwrap();
+        assert!(zh.page_image(&article).await.unwrap().is_none());
+    }
+
+    #[test]
+    fn test_strip_markup() {
+        assert_eq!(
+            strip_markup(
+                "{{cite|x={{y}}}}'''Hello''' [[World|world]]<ref name=\"a\">note</ref> [https://example.com site]<ref name=\"b\" /><br/>!"
+            ),
+            "Hello world site!"
+        );
+    }
+
+    #[test]
+    fn test_split_sections() {
+        let (intro, sections) = split_sections(
+            "Intro.\n\n\n== Life ==\nBorn.\n\n=== Youth ===\nPlayed.\n\n== See also ==\nOther\n",
+        );
+
+        assert_eq!(intro, "Intro.");
+        assert_eq!(sections.len(), 1);
+        assert_eq!(sections[0].text, "Born.\n\n\nYouth\nPlayed.");
+    }
+
+    #[test]
+    fn test_to_context() {
+        let article = Article {
+            lang: "en".to_string(),
+            page_id: 1,
+            title: "A".to_string(),
+            intro: "Intro".to_string(),
+            sections: vec![
+                Section {
+                    title: "B".to_string(),
+                    text: "b".to_string(),
+                },
+                Section {
+                    title: "C".to_string(),
+                    text: "c".repeat(100),
+                },
+            ],
+        };
+
+        assert_eq!(article.to_context(50), "Intro\n\n## B\nb");
+    }
+
+    #[test]
+    fn test_from_config() {
+        let knowledge =
+            Knowledge::from_config("ja=https://ja.example/api.php, en=https://en.example/api.php")
+                .unwrap();
+
+        assert_eq!(knowledge.sources.len(), 2);
+        assert_eq!(knowledge.sources[0].lang, "ja");
+        assert!(Knowledge::from_config("broken").is_err());
+    }
+}
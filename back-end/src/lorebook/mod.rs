@@ -0,0 +1,187 @@
+use crate::database::{
+    Database,
+    models::{
+        lorebook_entries::{Model as Entry, Position},
+        roles,
+    },
+};
+use anyhow::Result;
+
+// 扫描最近几条消息中的关键词
+pub const SCAN_DEPTH: i64 = 4;
+pub const DEFAULT_TOKEN_BUDGET: i32 = 512;
+
+pub struct Lorebook {
+    pub token_budget: i32,
+    pub entries: Vec<Entry>,
+}
+
+// 根据最近的消息触发世界书，返回注入后的系统提示词和记忆摘要
+pub async fn build_context(
+    database: &Database,
+    role: &roles::Model,
+    conversation_id: i32,
+    system: &str,
+    history: &str,
+) -> Result<(String, String)> {
+    let lorebooks = database
+        .get_active_lorebooks(role.id, conversation_id)
+        .await?;
+
+    if lorebooks.is_empty() {
+        return Ok((system.to_string(), history.to_string()));
+    }
+
+    let recent_text = database
+        .get_recent_dialogs_by_id(conversation_id, SCAN_DEPTH)
+        .await?
+        .into_iter()
+        .map(|dialog| dialog.text)
+        .collect::<Vec<String>>()
+        .join("\n");
+
+    let entries = select(&lorebooks, &recent_text);
+
+    Ok(inject(system, history, &entries))
+}
+
+// 按优先级从高到低选出被触发的条目，超出预算的条目跳过
+pub fn select<'a>(lorebooks: &'a [Lorebook], recent_text: &str) -> Vec<&'a Entry> {
+    let recent_text = recent_text.to_lowercase();
+    let mut selected = Vec::new();
+
+    for lorebook in lorebooks {
+        let mut triggered = lorebook
+            .entries
+            .iter()
+            .filter(|entry| entry.enabled)
+            .filter(|entry| {
+                entry
+                    .keyword_list()
+                    .iter()
+                    .any(|keyword| recent_text.contains(&keyword.to_lowercase()))
+            })
+            .collect::<Vec<&Entry>>();
+
+        triggered.sort_by(|a, b| b.priority.cmp(&a.priority).then(a.id.cmp(&b.id)));
+
+        let mut remaining = lorebook.token_budget.max(0) as usize;
+
+        for entry in triggered {
+            let tokens = estimate_tokens(&entry.content);
+
+            if tokens <= remaining {
+                remaining -= tokens;
+                selected.push(entry);
+            }
+        }
+    }
+
+    selected
+}
+
+// 将条目按插入位置拼入系统提示词和记忆摘要
+pub fn inject(system: &str, history: &str, entries: &[&Entry]) -> (String, String) {
+    let join = |position: Position| {
+        entries
+            .iter()
+            .filter(|entry| entry.position == position)
+            .map(|entry| entry.content.trim())
+            .collect::<Vec<&str>>()
+            .join("\n")
+    };
+
+    let before_role = join(Position::BeforeRole);
+    let after_role = join(Position::AfterRole);
+    let after_history = join(Position::AfterHistory);
+
+    let mut system = system.to_string();
+    if !before_role.is_empty() {
+        system = format!("以下是世界设定：\n{}\n\n{}", before_role, system);
+    }
+    if !after_role.is_empty() {
+        system = format!("{}\n\n以下是相关设定：\n{}", system, after_role);
+    }
+
+    let mut history = history.to_string();
+    if !after_history.is_empty() {
+        history = format!("{}\n\n以下是相关设定：\n{}", history, after_history)
+            .trim()
+            .to_string();
+    }
+
+    (system, history)
+}
+
+// 粗略估算token数：汉字等非ASCII字符按1个计，ASCII字符每4个计1个
+pub fn estimate_tokens(text: &str) -> usize {
+    let ascii = text.chars().filter(|c| c.is_ascii()).count();
+    let others = text.chars().count() - ascii;
+
+    others + ascii.div_ceil(4)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: i32, keywords: &str, content: &str, priority: i32, position: Position) -> Entry {
+        Entry {
+            id,
+            lorebook_id: 1,
+            keywords: keywords.to_string(),
+            content: content.to_string(),
+            priority,
+            position,
+            enabled: true,
+        }
+    }
+
+    #[test]
+    fn test_select() {
+        let lorebooks = vec![Lorebook {
+            token_budget: 10,
+            entries: vec![
+                entry(1, "霍格沃茨\nHogwarts", "魔法学校", 1, Position::AfterRole),
+                entry(2, "魔杖", "施法的工具", 5, Position::AfterRole),
+                entry(
+                    3,
+                    "hogwarts",
+                    "位于苏格兰高地的城堡",
+                    3,
+                    Position::BeforeRole,
+                ),
+                entry(4, "龙", "一种魔法生物", 9, Position::AfterRole),
+            ],
+        }];
+
+        let selected = select(&lorebooks, "我想去HOGWARTS买一根魔杖")
+            .iter()
+            .map(|entry| entry.id)
+            .collect::<Vec<i32>>();
+
+        // 条目3超出剩余预算被跳过，条目1仍可放入
+        assert_eq!(selected, vec![2, 1]);
+    }
+
+    #[test]
+    fn test_inject() {
+        let before = entry(1, "a", "世界观", 0, Position::BeforeRole);
+        let after = entry(2, "a", "地点", 0, Position::AfterRole);
+        let history = entry(3, "a", "事件", 0, Position::AfterHistory);
+
+        let (system, summary) = inject("角色", "", &[&before, &after, &history]);
+
+        assert_eq!(
+            system,
+            "以下是世界设定：\n世界观\n\n角色\n\n以下是相关设定：\n地点"
+        );
+        assert_eq!(summary, "以下是相关设定：\n事件");
+    }
+
+    #[test]
+    fn test_estimate_tokens() {
+        assert_eq!(estimate_tokens("魔法学校"), 4);
+        assert_eq!(estimate_tokens("magic"), 2);
+    }
+}
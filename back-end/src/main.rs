@@ -1,10 +1,19 @@
 use crate::env::{ENV, Env};
 
 mod agents;
+mod card;
 mod database;
+mod diff;
 mod env;
 mod error;
+mod export;
+mod import;
+mod knowledge;
+mod lorebook;
+mod moderation;
+mod search;
 mod server;
+mod stats;
 mod storage;
 mod trace;
 
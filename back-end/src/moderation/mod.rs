@@ -0,0 +1,126 @@
+use crate::{
+    database::models::reports::{self, TargetType},
+    stats::DAY_MS,
+};
+use serde::Serialize;
+use std::collections::HashMap;
+
+// 永久封禁的到期时间
+pub const PERMANENT: i64 = i64::MAX;
+
+// 同一内容的待处理举报
+#[derive(Debug, Serialize)]
+pub struct ReportGroup {
+    pub target_type: TargetType,
+    pub target_id: i32,
+    pub reports: Vec<ReportData>,
+    // 最早的举报时间，毫秒
+    pub first_reported_at: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReportData {
+    pub report_id: i32,
+    pub reporter_id: i32,
+    pub reason: String,
+    pub created_at: i64,
+}
+
+// 按内容合并举报，举报多的在前，同样多时先被举报的在前
+pub fn group_reports(reports: Vec<reports::Model>) -> Vec<ReportGroup> {
+    let mut groups: HashMap<(TargetType, i32), ReportGroup> = HashMap::new();
+
+    for report in reports {
+        let group = groups
+            .entry((report.target_type, report.target_id))
+            .or_insert_with(|| ReportGroup {
+                target_type: report.target_type,
+                target_id: report.target_id,
+                reports: Vec::new(),
+                first_reported_at: report.created_at,
+            });
+
+        group.first_reported_at = group.first_reported_at.min(report.created_at);
+        group.reports.push(ReportData {
+            report_id: report.id,
+            reporter_id: report.reporter_id,
+            reason: report.reason,
+            created_at: report.created_at,
+        });
+    }
+
+    let mut groups: Vec<ReportGroup> = groups.into_values().collect();
+    groups.sort_by(|a, b| {
+        b.reports
+            .len()
+            .cmp(&a.reports.len())
+            .then_with(|| a.first_reported_at.cmp(&b.first_reported_at))
+            .then_with(|| a.target_id.cmp(&b.target_id))
+    });
+
+    groups
+}
+
+// 封禁到期时间，天数为空时永久封禁
+pub fn suspension_end(now: i64, days: Option<i64>) -> i64 {
+    match days {
+        Some(days) => now.saturating_add(days.max(1).saturating_mul(DAY_MS)),
+        None => PERMANENT,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::models::reports::ReportStatus;
+
+    fn report(id: i32, target_type: TargetType, target_id: i32, created_at: i64) -> reports::Model {
+        reports::Model {
+            id,
+            reporter_id: id,
+            target_type,
+            target_id,
+            reason: String::new(),
+            status: ReportStatus::Pending,
+            created_at,
+            resolved_at: None,
+            resolved_by: None,
+        }
+    }
+
+    #[test]
+    fn test_group_reports() {
+        let reports = vec![
+            report(1, TargetType::Role, 1, 10),
+            report(2, TargetType::Review, 1, 5),
+            report(3, TargetType::Review, 2, 20),
+            report(4, TargetType::Review, 2, 30),
+            report(5, TargetType::Role, 1, 40),
+        ];
+
+        let groups = group_reports(reports);
+        let targets: Vec<(TargetType, i32, usize)> = groups
+            .iter()
+            .map(|group| (group.target_type, group.target_id, group.reports.len()))
+            .collect();
+
+        // 角色1与评价2同样被举报2次，角色1先被举报
+        assert_eq!(
+            targets,
+            vec![
+                (TargetType::Role, 1, 2),
+                (TargetType::Review, 2, 2),
+                (TargetType::Review, 1, 1),
+            ]
+        );
+        assert_eq!(groups[0].first_reported_at, 10);
+    }
+
+    #[test]
+    fn test_suspension_end() {
+        assert_eq!(suspension_end(0, Some(2)), 2 * DAY_MS);
+        assert_eq!(suspension_end(0, Some(0)), DAY_MS);
+        assert_eq!(suspension_end(0, None), PERMANENT);
+        assert_eq!(suspension_end(1, Some(i64::MAX)), PERMANENT);
+    }
+}
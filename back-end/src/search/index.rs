@@ -0,0 +1,441 @@
+use crate::database::models::roles;
+use std::{collections::HashMap, sync::RwLock};
+
+// 各字段的权重
+const NAME_WEIGHT: f32 = 3.0;
+const TITLE_WEIGHT: f32 = 3.0;
+const TAG_WEIGHT: f32 = 2.0;
+const TRAITS_WEIGHT: f32 = 1.0;
+const DESCRIPTION_WEIGHT: f32 = 1.0;
+
+// 词频饱和参数
+const TF_SATURATION: f32 = 1.2;
+
+// 至少匹配一半的查询词
+const MIN_COVERAGE: f32 = 0.5;
+
+// 拼写相近和前缀匹配的折扣
+const FUZZY_FACTOR: f32 = 0.6;
+const PREFIX_FACTOR: f32 = 0.8;
+
+// 查询只取开头的字符和词，模糊匹配要遍历整个词表
+const MAX_QUERY_CHARS: usize = 100;
+const MAX_QUERY_TERMS: usize = 10;
+// 超过这个长度的词只做精确和前缀匹配
+const MAX_FUZZY_CHARS: usize = 32;
+
+// 角色名与查询完全相同或包含查询时的加成
+const EXACT_NAME_BOOST: f32 = 2.0;
+const PARTIAL_NAME_BOOST: f32 = 1.5;
+
+// 被索引的角色字段
+pub struct Document<'a> {
+    pub name: &'a str,
+    pub canonical_title: Option<&'a str>,
+    pub description: &'a str,
+    pub traits: &'a str,
+    pub tags: &'a [String],
+}
+
+impl<'a> Document<'a> {
+    pub fn new(role: &'a roles::Model, tags: &'a [String]) -> Self {
+        Self {
+            name: &role.name,
+            canonical_title: role.canonical_title.as_deref(),
+            description: &role.description,
+            traits: &role.traits,
+            tags,
+        }
+    }
+}
+
+// 内存中的倒排索引，启动时从数据库构建，角色增删改时同步
+#[derive(Default)]
+pub struct SearchIndex {
+    inner: RwLock<Inner>,
+}
+
+#[derive(Default)]
+struct Inner {
+    // 词 -> 角色ID -> 加权词频
+    postings: HashMap<String, HashMap<i32, f32>>,
+    // 角色包含的词，删除时使用
+    terms: HashMap<i32, Vec<String>>,
+    // 小写的角色名
+    names: HashMap<i32, String>,
+}
+
+impl SearchIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // 已存在的角色先移除再重新索引
+    pub fn insert(&self, role_id: i32, document: &Document) {
+        let mut frequencies: HashMap<String, f32> = HashMap::new();
+
+        let mut fields = vec![
+            (document.name, NAME_WEIGHT),
+            (document.description, DESCRIPTION_WEIGHT),
+            (document.traits, TRAITS_WEIGHT),
+        ];
+        if let Some(title) = document.canonical_title {
+            fields.push((title, TITLE_WEIGHT));
+        }
+        for tag in document.tags {
+            fields.push((tag.as_str(), TAG_WEIGHT));
+        }
+
+        for (text, weight) in fields {
+            for term in tokenize(text) {
+                *frequencies.entry(term).or_default() += weight;
+            }
+        }
+
+        let mut inner = self.inner.write().unwrap();
+        inner.remove(role_id);
+
+        for (term, frequency) in &frequencies {
+            inner
+                .postings
+                .entry(term.clone())
+                .or_default()
+                .insert(role_id, *frequency);
+        }
+        inner
+            .terms
+            .insert(role_id, frequencies.into_keys().collect());
+        inner.names.insert(role_id, document.name.to_lowercase());
+    }
+
+    pub fn remove(&self, role_id: i32) {
+        self.inner.write().unwrap().remove(role_id);
+    }
+
+    pub fn clear(&self) {
+        *self.inner.write().unwrap() = Inner::default();
+    }
+
+    // 按相关度从高到低返回角色ID
+    pub fn search(&self, query: &str) -> Vec<(i32, f32)> {
+        let query: String = query.chars().take(MAX_QUERY_CHARS).collect();
+        let query_terms = query_terms(&query);
+
+        if query_terms.is_empty() {
+            return Vec::new();
+        }
+
+        let inner = self.inner.read().unwrap();
+        let total = inner.terms.len() as f32;
+
+        // 角色ID -> (得分, 匹配的查询词数)
+        let mut scores: HashMap<i32, (f32, usize)> = HashMap::new();
+
+        for query_term in &query_terms {
+            // 同一查询词只取每个角色得分最高的匹配
+            let mut best: HashMap<i32, f32> = HashMap::new();
+
+            for (term, factor) in inner.expand(query_term) {
+                let postings = &inner.postings[term];
+                let df = postings.len() as f32;
+                let idf = (1.0 + (total - df + 0.5) / (df + 0.5)).ln();
+
+                for (role_id, frequency) in postings {
+                    let score = factor * idf * frequency / (frequency + TF_SATURATION);
+                    let entry = best.entry(*role_id).or_default();
+                    *entry = entry.max(score);
+                }
+            }
+
+            for (role_id, score) in best {
+                let entry = scores.entry(role_id).or_default();
+                entry.0 += score;
+                entry.1 += 1;
+            }
+        }
+
+        let query = query.trim().to_lowercase();
+
+        let mut results: Vec<(i32, f32)> = scores
+            .into_iter()
+            .filter_map(|(role_id, (score, matched))| {
+                let coverage = matched as f32 / query_terms.len() as f32;
+
+                if coverage < MIN_COVERAGE {
+                    return None;
+                }
+
+                let name = &inner.names[&role_id];
+                let boost = if *name == query {
+                    EXACT_NAME_BOOST
+                } else if name.contains(&query) {
+                    PARTIAL_NAME_BOOST
+                } else {
+                    1.0
+                };
+
+                Some((role_id, score * coverage * boost))
+            })
+            .collect();
+
+        results.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+        results
+    }
+}
+
+impl Inner {
+    fn remove(&mut self, role_id: i32) {
+        for term in self.terms.remove(&role_id).unwrap_or_default() {
+            if let Some(postings) = self.postings.get_mut(&term) {
+                postings.remove(&role_id);
+
+                if postings.is_empty() {
+                    self.postings.remove(&term);
+                }
+            }
+        }
+        self.names.remove(&role_id);
+    }
+
+    // 查询词对应的索引词及折扣，拉丁字母的词容许拼写错误和前缀匹配
+    fn expand<'a>(&'a self, query_term: &str) -> Vec<(&'a str, f32)> {
+        if let Some((term, _)) = self.postings.get_key_value(query_term) {
+            return vec![(term.as_str(), 1.0)];
+        }
+
+        if query_term.chars().any(is_cjk) {
+            return Vec::new();
+        }
+
+        let length = query_term.chars().count();
+        let max_distance = match length {
+            0..4 => 0,
+            4..8 => 1,
+            8..=MAX_FUZZY_CHARS => 2,
+            _ => 0,
+        };
+
+        self.postings
+            .keys()
+            .filter(|term| !term.chars().any(is_cjk))
+            .filter_map(|term| {
+                if length >= 3 && term.starts_with(query_term) {
+                    Some((term.as_str(), PREFIX_FACTOR))
+                } else if max_distance > 0 && edit_distance(query_term, term) <= max_distance {
+                    Some((term.as_str(), FUZZY_FACTOR))
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+}
+
+// 中日文字切分为单字和相邻两字，其他文字按单词切分，相邻单词另外拼接为一个词，
+// 这样不带空格的拼音或英文名也能匹配
+pub fn tokenize(text: &str) -> Vec<String> {
+    let mut terms = Vec::new();
+
+    for run in runs(text) {
+        match run {
+            Run::Cjk(chars) => {
+                terms.extend(chars.iter().map(char::to_string));
+                terms.extend(chars.windows(2).map(|pair| pair.iter().collect()));
+            }
+            Run::Words(words) => {
+                terms.extend(words.windows(2).map(|pair| pair.concat()));
+                terms.extend(words);
+            }
+        }
+    }
+
+    terms
+}
+
+// 查询时中日文字只用相邻两字，单独一个字时才用单字，结果去重
+fn query_terms(query: &str) -> Vec<String> {
+    let mut terms: Vec<String> = Vec::new();
+
+    for run in runs(query) {
+        let run_terms: Vec<String> = match run {
+            Run::Cjk(chars) if chars.len() == 1 => vec![chars[0].to_string()],
+            Run::Cjk(chars) => chars.windows(2).map(|pair| pair.iter().collect()).collect(),
+            Run::Words(words) => words,
+        };
+
+        for term in run_terms {
+            if !terms.contains(&term) {
+                terms.push(term);
+            }
+        }
+    }
+
+    terms.truncate(MAX_QUERY_TERMS);
+    terms
+}
+
+enum Run {
+    Cjk(Vec<char>),
+    Words(Vec<String>),
+}
+
+// 把文本分为连续的中日文字和连续的单词
+fn runs(text: &str) -> Vec<Run> {
+    let mut runs = Vec::new();
+    let mut cjk: Vec<char> = Vec::new();
+    let mut words: Vec<String> = Vec::new();
+    let mut word = String::new();
+
+    for c in text.chars().flat_map(char::to_lowercase) {
+        if is_cjk(c) {
+            if !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+            if !words.is_empty() {
+                runs.push(Run::Words(std::mem::take(&mut words)));
+            }
+            cjk.push(c);
+            continue;
+        }
+
+        if !cjk.is_empty() {
+            runs.push(Run::Cjk(std::mem::take(&mut cjk)));
+        }
+
+        if c.is_alphanumeric() {
+            word.push(c);
+            continue;
+        }
+
+        if !word.is_empty() {
+            words.push(std::mem::take(&mut word));
+        }
+
+        // 空格、连字符等人名中常见的符号以外的符号分隔词组
+        if !words.is_empty() && !matches!(c, ' ' | '-' | '\'' | '.' | '·') {
+            runs.push(Run::Words(std::mem::take(&mut words)));
+        }
+    }
+
+    if !word.is_empty() {
+        words.push(word);
+    }
+    if !words.is_empty() {
+        runs.push(Run::Words(words));
+    }
+    if !cjk.is_empty() {
+        runs.push(Run::Cjk(cjk));
+    }
+
+    runs
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{3040}'..='\u{30FF}'
+        | '\u{3400}'..='\u{4DBF}'
+        | '\u{4E00}'..='\u{9FFF}'
+        | '\u{F900}'..='\u{FAFF}'
+    )
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+
+    if a.len().abs_diff(b.len()) > 2 {
+        return a.len().abs_diff(b.len());
+    }
+
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.iter().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != cb);
+            current[j + 1] = (previous[j] + cost)
+                .min(previous[j + 1] + 1)
+                .min(current[j] + 1);
+        }
+
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn document<'a>(name: &'a str, description: &'a str, tags: &'a [String]) -> Document<'a> {
+        Document {
+            name,
+            canonical_title: None,
+            description,
+            traits: "",
+            tags,
+        }
+    }
+
+    fn ids(results: Vec<(i32, f32)>) -> Vec<i32> {
+        results.into_iter().map(|(role_id, _)| role_id).collect()
+    }
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(tokenize("李白"), vec!["李", "白", "李白"]);
+        assert_eq!(
+            tokenize("Albert Einstein, 物理"),
+            vec!["alberteinstein", "albert", "einstein", "物", "理", "物理"]
+        );
+        assert_eq!(query_terms("爱因斯坦"), vec!["爱因", "因斯", "斯坦"]);
+        assert_eq!(query_terms("诗 诗"), vec!["诗"]);
+        assert_eq!(
+            query_terms("a b c d e f g h i j k l").len(),
+            MAX_QUERY_TERMS
+        );
+    }
+
+    #[test]
+    fn test_search() {
+        let index = SearchIndex::new();
+        let tags = ["物理学家".to_string()];
+
+        index.insert(1, &document("爱因斯坦", "提出相对论的物理学家", &[]));
+        index.insert(2, &document("玻尔", "和爱因斯坦争论量子力学", &tags));
+        index.insert(3, &document("弗兰肯斯坦", "科学怪人", &[]));
+        index.insert(
+            4,
+            &Document {
+                canonical_title: Some("Li Bai"),
+                ..document("李白", "唐代诗人", &[])
+            },
+        );
+
+        // 名称匹配排在描述匹配之前，只匹配"斯坦"的角色不够相关
+        assert_eq!(ids(index.search("爱因斯坦")), vec![1, 2]);
+        assert_eq!(ids(index.search("物理学家")), vec![2, 1]);
+
+        // 拼写错误、前缀和不带空格的拼音
+        assert_eq!(ids(index.search("libai")), vec![4]);
+        assert_eq!(ids(index.search("li bia")), vec![4]);
+        assert_eq!(ids(index.search("liba")), vec![4]);
+
+        index.remove(1);
+        assert_eq!(ids(index.search("爱因斯坦")), vec![2]);
+
+        index.insert(2, &document("玻尔", "丹麦物理学家", &[]));
+        assert!(index.search("爱因斯坦").is_empty());
+        assert!(index.search("  ").is_empty());
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("einstein", "einstien"), 2);
+        assert_eq!(edit_distance("newton", "newtn"), 1);
+        assert_eq!(edit_distance("abc", "abc"), 0);
+    }
+}
@@ -0,0 +1,179 @@
+mod index;
+mod vector;
+
+pub use index::{Document, SearchIndex};
+// 本地向量只用于测试
+#[cfg(test)]
+pub use index::tokenize;
+pub use vector::VectorIndex;
+
+use crate::database::models::{
+    role_tags,
+    roles::{AgeGroup, Category, Column, Gender, RoleIdentity, Visibility},
+};
+use sea_orm::{ColumnTrait, Condition, FromQueryResult, sea_query::Query};
+use serde::Serialize;
+use std::collections::HashMap;
+
+// 标签分面最多返回的个数
+const MAX_TAG_FACETS: usize = 20;
+
+// 角色列表和搜索的筛选条件，各项为空时不筛选
+#[derive(Default)]
+pub struct RoleFilter {
+    pub tag: Option<String>,
+    pub category: Option<Category>,
+    pub gender: Option<Gender>,
+    pub age_group: Option<AgeGroup>,
+}
+
+impl RoleFilter {
+    // 只包含公开且未被隐藏的角色
+    pub fn condition(&self) -> Condition {
+        let mut condition = Condition::all()
+            .add(Column::Visibility.eq(Visibility::Public))
+            .add(Column::Hidden.eq(false));
+
+        if let Some(tag) = self.tag.as_deref().map(str::trim).filter(|t| !t.is_empty()) {
+            condition = condition.add(
+                Column::Id.in_subquery(
+                    Query::select()
+                        .column(role_tags::Column::RoleId)
+                        .from(role_tags::Entity)
+                        .and_where(role_tags::Column::Tag.eq(tag))
+                        .to_owned(),
+                ),
+            );
+        }
+        if let Some(category) = self.category {
+            condition = condition.add(Column::Category.eq(category));
+        }
+        if let Some(gender) = &self.gender {
+            condition = condition.add(Column::Gender.eq(gender.clone()));
+        }
+        if let Some(age_group) = &self.age_group {
+            condition = condition.add(Column::AgeGroup.eq(age_group.clone()));
+        }
+
+        condition
+    }
+}
+
+// 只含分面统计和按人物分组需要的列，搜索时先对全部命中的角色统计和分页，再读取当前页的完整角色
+#[derive(FromQueryResult)]
+pub struct RoleHit {
+    pub id: i32,
+    pub category: Option<Category>,
+    pub gender: Gender,
+    pub age_group: AgeGroup,
+    pub canonical_title: Option<String>,
+    pub wiki_lang: Option<String>,
+    pub wiki_page_id: Option<i64>,
+}
+
+impl RoleHit {
+    pub const COLUMNS: [Column; 7] = [
+        Column::Id,
+        Column::Category,
+        Column::Gender,
+        Column::AgeGroup,
+        Column::CanonicalTitle,
+        Column::WikiLang,
+        Column::WikiPageId,
+    ];
+
+    pub fn identity(&self) -> RoleIdentity {
+        RoleIdentity {
+            canonical_title: self.canonical_title.clone(),
+            wiki_lang: self.wiki_lang.clone(),
+            wiki_page_id: self.wiki_page_id,
+        }
+    }
+}
+
+// 符合条件的角色在各个维度上的取值及数量
+#[derive(Debug, Default, Serialize)]
+pub struct Facets {
+    pub tags: Vec<FacetCount>,
+    pub categories: Vec<FacetCount>,
+    pub genders: Vec<FacetCount>,
+    pub age_groups: Vec<FacetCount>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct FacetCount {
+    pub value: String,
+    pub count: usize,
+}
+
+impl Facets {
+    pub fn count(roles: &[RoleHit], tags: &HashMap<i32, Vec<String>>) -> Self {
+        let mut tag_counts = tally(
+            roles
+                .iter()
+                .flat_map(|role| tags.get(&role.id).into_iter().flatten().cloned()),
+        );
+        tag_counts.truncate(MAX_TAG_FACETS);
+
+        Self {
+            tags: tag_counts,
+            categories: tally(
+                roles
+                    .iter()
+                    .filter_map(|role| role.category.map(|c| c.to_string())),
+            ),
+            genders: tally(roles.iter().map(|role| role.gender.to_string())),
+            age_groups: tally(roles.iter().map(|role| role.age_group.to_string())),
+        }
+    }
+}
+
+// 按数量从多到少排列，数量相同时按取值排列
+fn tally(values: impl Iterator<Item = String>) -> Vec<FacetCount> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+
+    for value in values {
+        *counts.entry(value).or_default() += 1;
+    }
+
+    let mut counts: Vec<FacetCount> = counts
+        .into_iter()
+        .map(|(value, count)| FacetCount { value, count })
+        .collect();
+    counts.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.value.cmp(&b.value)));
+
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tally() {
+        let counts = tally(
+            ["科学家", "诗人", "科学家", "唐朝", "诗人", "科学家"]
+                .into_iter()
+                .map(str::to_string),
+        );
+
+        assert_eq!(
+            counts,
+            vec![
+                FacetCount {
+                    value: "科学家".to_string(),
+                    count: 3
+                },
+                FacetCount {
+                    value: "诗人".to_string(),
+                    count: 2
+                },
+                FacetCount {
+                    value: "唐朝".to_string(),
+                    count: 1
+                },
+            ]
+        );
+        assert!(tally(std::iter::empty()).is_empty());
+    }
+}
@@ -0,0 +1,107 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::RwLock,
+};
+
+// 内存中的角色向量，按余弦相似度查找最近邻
+#[derive(Default)]
+pub struct VectorIndex {
+    vectors: RwLock<HashMap<i32, Vec<f32>>>,
+}
+
+impl VectorIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&self, role_id: i32, vector: Vec<f32>) {
+        self.vectors
+            .write()
+            .unwrap()
+            .insert(role_id, normalize(vector));
+    }
+
+    pub fn get(&self, role_id: i32) -> Option<Vec<f32>> {
+        self.vectors.read().unwrap().get(&role_id).cloned()
+    }
+
+    pub fn remove(&self, role_id: i32) {
+        self.vectors.write().unwrap().remove(&role_id);
+    }
+
+    // 只保留给定的角色
+    pub fn retain(&self, role_ids: &HashSet<i32>) {
+        self.vectors
+            .write()
+            .unwrap()
+            .retain(|role_id, _| role_ids.contains(role_id));
+    }
+
+    // 相似度从高到低返回最多limit个角色，跳过exclude
+    pub fn nearest(&self, query: &[f32], limit: usize, exclude: Option<i32>) -> Vec<(i32, f32)> {
+        let query = normalize(query.to_vec());
+
+        let mut results: Vec<(i32, f32)> = self
+            .vectors
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|(role_id, _)| Some(**role_id) != exclude)
+            .filter(|(_, vector)| vector.len() == query.len())
+            .map(|(role_id, vector)| (*role_id, dot(&query, vector)))
+            .collect();
+
+        results.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        results.truncate(limit);
+
+        results
+    }
+}
+
+fn dot(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+// 归一化后点积即余弦相似度
+pub fn normalize(mut vector: Vec<f32>) -> Vec<f32> {
+    let norm = dot(&vector, &vector).sqrt();
+
+    if norm > 0.0 {
+        for value in &mut vector {
+            *value /= norm;
+        }
+    }
+
+    vector
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nearest() {
+        let index = VectorIndex::new();
+
+        index.insert(1, vec![1.0, 0.0, 0.0]);
+        index.insert(2, vec![0.9, 0.1, 0.0]);
+        index.insert(3, vec![0.0, 1.0, 0.0]);
+        index.insert(4, vec![0.0, 0.0]);
+
+        let ids: Vec<i32> = index
+            .nearest(&[2.0, 0.0, 0.0], 10, None)
+            .into_iter()
+            .map(|(role_id, _)| role_id)
+            .collect();
+        assert_eq!(ids, vec![1, 2, 3]);
+
+        let nearest = index.nearest(&[1.0, 0.0, 0.0], 1, Some(1));
+        assert_eq!(nearest.len(), 1);
+        assert_eq!(nearest[0].0, 2);
+        assert!(nearest[0].1 > 0.99);
+
+        index.retain(&HashSet::from([3]));
+        assert!(index.get(1).is_none());
+        assert_eq!(index.get(3), Some(vec![0.0, 1.0, 0.0]));
+    }
+}
@@ -1,5 +1,17 @@
-use crate::database::{Database, models};
-use anyhow::Result;
+use crate::{
+    database::{
+        Database,
+        models::{self, users::UserRole},
+    },
+    error::HttpError,
+};
+use anyhow::{Result, anyhow};
+use axum::{
+    Extension,
+    extract::FromRequestParts,
+    http::{header::AUTHORIZATION, request::Parts},
+};
+use axum_auth::AuthBearer;
 use chrono::{Duration, Utc};
 use jsonwebtoken::{DecodingKey, EncodingKey, Header, Validation, decode, encode};
 use serde::{Deserialize, Serialize};
@@ -50,6 +62,10 @@ impl Auth {
         match unsafe_decode_token(token) {
             Ok(claims) => match self.database.get_user(&claims.username).await {
                 Ok(user) => match verify_token(token, &user.jwt_secret) {
+                    // 被封禁的用户已签发的令牌也不再有效
+                    Ok(_verified_claims) if user.is_suspended(Utc::now().timestamp_millis()) => {
+                        Err(anyhow::anyhow!("Account suspended"))
+                    }
                     Ok(_verified_claims) => Ok(user),
                     Err(_) => Err(anyhow::anyhow!("Invalid token signature")),
                 },
@@ -60,6 +76,76 @@ impl Auth {
     }
 }
 
+// 可不登录访问的接口的查看者，没有令牌时为空，令牌无效时返回401
+pub struct Viewer(pub Option<models::users::Model>);
+
+impl Viewer {
+    pub fn id(&self) -> Option<i32> {
+        self.0.as_ref().map(|user| user.id)
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for Viewer {
+    type Rejection = HttpError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        if !parts.headers.contains_key(AUTHORIZATION) {
+            return Ok(Self(None));
+        }
+
+        let user = verify_parts(parts, state).await?;
+
+        Ok(Self(Some(user)))
+    }
+}
+
+// 审核员或管理员，用作需要审核权限的接口的参数
+pub struct Moderator(pub models::users::Model);
+
+// 管理员，用作管理接口的参数
+pub struct Admin(pub models::users::Model);
+
+impl<S: Send + Sync> FromRequestParts<S> for Moderator {
+    type Rejection = HttpError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let user = verify_parts(parts, state).await?;
+
+        if !user.role.can_moderate() {
+            return Err(HttpError::Forbidden(anyhow!("需要审核权限")));
+        }
+
+        Ok(Self(user))
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for Admin {
+    type Rejection = HttpError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let user = verify_parts(parts, state).await?;
+
+        if user.role != UserRole::Admin {
+            return Err(HttpError::Forbidden(anyhow!("需要管理员权限")));
+        }
+
+        Ok(Self(user))
+    }
+}
+
+// 从请求头取令牌，用请求扩展中的Auth验证
+async fn verify_parts<S: Send + Sync>(
+    parts: &mut Parts,
+    state: &S,
+) -> Result<models::users::Model, HttpError> {
+    let AuthBearer(token) = AuthBearer::from_request_parts(parts, state)
+        .await
+        .map_err(|(_, message)| HttpError::Unauthorized(anyhow!(message)))?;
+    let Extension(auth) = Extension::<Auth>::from_request_parts(parts, state).await?;
+
+    auth.verify(&token).await.map_err(HttpError::Unauthorized)
+}
+
 fn verify_token(token: &str, user_secret: &str) -> Result<Claims, jsonwebtoken::errors::Error> {
     let token_data = decode::<Claims>(
         token,
@@ -0,0 +1,3 @@
+pub mod roles;
+pub mod stats;
+pub mod users;
@@ -0,0 +1,97 @@
+use crate::{
+    database::{
+        Database,
+        models::{
+            moderation_logs::ModerationAction,
+            reports::{ReportStatus, TargetType},
+        },
+    },
+    error::HttpResult,
+    server::auth::Admin,
+};
+use anyhow::anyhow;
+use axum::{Extension, Json};
+use serde::Deserialize;
+use std::sync::Arc;
+
+pub const TRANSFER_PATH: &str = "/api/admin/role/transfer";
+pub const DELETE_PATH: &str = "/api/admin/role/delete";
+
+// 把角色转给其他用户，如原作者注销或申请转交
+#[axum::debug_handler]
+pub async fn transfer_handler(
+    Admin(admin): Admin,
+    Extension(database): Extension<Arc<Database>>,
+    Json(TransferParams {
+        role_id,
+        user_id,
+        reason,
+    }): Json<TransferParams>,
+) -> HttpResult<()> {
+    let role = database.get_role(role_id).await?;
+
+    if role.user_id == user_id {
+        return Err(anyhow!("角色已属于该用户").into());
+    }
+
+    // 确认新作者存在
+    database.get_user_by_id(user_id).await?;
+    database.transfer_role(role_id, user_id).await?;
+
+    database
+        .add_moderation_log(
+            admin.id,
+            ModerationAction::Transfer,
+            TargetType::Role,
+            role_id,
+            reason.trim(),
+            Some(format!("{}->{}", role.user_id, user_id)),
+        )
+        .await?;
+
+    Ok(())
+}
+
+// 删除任意角色及其对话，未处理的举报一并标记为已处理
+#[axum::debug_handler]
+pub async fn delete_handler(
+    Admin(admin): Admin,
+    Extension(database): Extension<Arc<Database>>,
+    Json(DeleteParams { role_id, reason }): Json<DeleteParams>,
+) -> HttpResult<()> {
+    let role = database.get_role(role_id).await?;
+
+    database.delete_role_and_conversations(role_id).await?;
+    database
+        .resolve_reports(TargetType::Role, role_id, ReportStatus::Resolved, admin.id)
+        .await?;
+
+    database
+        .add_moderation_log(
+            admin.id,
+            ModerationAction::Delete,
+            TargetType::Role,
+            role_id,
+            reason.trim(),
+            Some(format!("{} (user {})", role.name, role.user_id)),
+        )
+        .await?;
+
+    Ok(())
+}
+
+#[derive(Deserialize)]
+pub struct TransferParams {
+    pub role_id: i32,
+    // 新作者
+    pub user_id: i32,
+    #[serde(default)]
+    pub reason: String,
+}
+
+#[derive(Deserialize)]
+pub struct DeleteParams {
+    pub role_id: i32,
+    #[serde(default)]
+    pub reason: String,
+}
@@ -0,0 +1,37 @@
+use crate::{
+    agents::{AI, Reciter},
+    database::Database,
+    error::HttpResult,
+    server::auth::Admin,
+    stats::GlobalStats,
+};
+use axum::{Extension, Json};
+use serde::Serialize;
+use std::sync::Arc;
+
+pub const PATH: &str = "/api/admin/stats";
+
+#[axum::debug_handler]
+pub async fn handler(
+    _: Admin,
+    Extension(database): Extension<Arc<Database>>,
+    Extension(ai): Extension<Arc<AI>>,
+    Extension(reciter): Extension<Reciter>,
+) -> HttpResult<Json<ResponseData>> {
+    let stats = database.get_global_stats().await?;
+
+    Ok(Json(ResponseData {
+        stats,
+        llm_calls: ai.llm_calls(),
+        tts_calls: reciter.tts_calls(),
+    }))
+}
+
+#[derive(Serialize)]
+pub struct ResponseData {
+    #[serde(flatten)]
+    pub stats: GlobalStats,
+    // 进程启动以来的调用次数，包括失败重试
+    pub llm_calls: u64,
+    pub tts_calls: u64,
+}
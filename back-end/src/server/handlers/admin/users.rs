@@ -0,0 +1,123 @@
+use crate::{
+    database::{
+        Database,
+        models::{moderation_logs::ModerationAction, reports::TargetType, users::UserRole},
+    },
+    error::HttpResult,
+    server::auth::Admin,
+};
+use anyhow::anyhow;
+use axum::{Extension, Json, extract::Query};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+pub const LIST_PATH: &str = "/api/admin/users";
+pub const ROLE_PATH: &str = "/api/admin/user/role";
+
+#[axum::debug_handler]
+pub async fn list_handler(
+    _: Admin,
+    Extension(database): Extension<Arc<Database>>,
+    Query(ListParams {
+        query,
+        offset,
+        limit,
+    }): Query<ListParams>,
+) -> HttpResult<Json<ListResponse>> {
+    let offset = offset.unwrap_or(0).max(0);
+    let limit = limit.unwrap_or(15).max(1);
+
+    let result = database
+        .search_users_paginated(query.unwrap_or_default().trim(), offset, limit)
+        .await?;
+    let now = Utc::now().timestamp_millis();
+
+    let users = result
+        .items
+        .into_iter()
+        .map(|user| UserData {
+            id: user.id,
+            suspended: user.is_suspended(now),
+            username: user.username,
+            avatar: user.image,
+            role: user.role,
+            suspended_until: user.suspended_until,
+        })
+        .collect();
+
+    Ok(Json(ListResponse {
+        users,
+        total: result.total,
+        has_more: result.has_more,
+    }))
+}
+
+// 修改用户权限，不能修改自己，避免没有管理员
+#[axum::debug_handler]
+pub async fn role_handler(
+    Admin(admin): Admin,
+    Extension(database): Extension<Arc<Database>>,
+    Json(RoleParams {
+        user_id,
+        role,
+        reason,
+    }): Json<RoleParams>,
+) -> HttpResult<Json<UserRole>> {
+    if user_id == admin.id {
+        return Err(anyhow!("不能修改自己的权限").into());
+    }
+
+    let user = database.get_user_by_id(user_id).await?;
+
+    if user.role != role {
+        database.set_user_role(user_id, role).await?;
+        database
+            .add_moderation_log(
+                admin.id,
+                ModerationAction::ChangeRole,
+                TargetType::User,
+                user_id,
+                reason.trim(),
+                Some(format!("{}->{}", user.role, role)),
+            )
+            .await?;
+    }
+
+    Ok(Json(role))
+}
+
+#[derive(Deserialize)]
+pub struct ListParams {
+    // 按用户名包含搜索，为空时列出所有用户
+    pub query: Option<String>,
+    pub offset: Option<i64>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Serialize)]
+pub struct ListResponse {
+    pub users: Vec<UserData>,
+    pub total: i64,
+    pub has_more: bool,
+}
+
+#[derive(Serialize)]
+pub struct UserData {
+    pub id: i32,
+    pub username: String,
+    pub avatar: String,
+    pub role: UserRole,
+    pub suspended: bool,
+    // 毫秒，过期后保留原值
+    pub suspended_until: Option<i64>,
+}
+
+#[derive(Deserialize)]
+pub struct RoleParams {
+    pub user_id: i32,
+    // user、moderator或admin
+    pub role: UserRole,
+    #[serde(default)]
+    pub reason: String,
+}
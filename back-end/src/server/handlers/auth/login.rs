@@ -5,6 +5,7 @@ use crate::{
 };
 use anyhow::anyhow;
 use axum::{Extension, Json};
+use chrono::Utc;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
@@ -17,6 +18,11 @@ pub async fn handler(
 ) -> HttpResult<Json<ResponseData>> {
     if database.verify_user(&data.username, &data.password).await? {
         let user = database.get_user(&data.username).await.unwrap();
+
+        if user.is_suspended(Utc::now().timestamp_millis()) {
+            return Err(HttpError::Forbidden(anyhow!("账号已被封禁")));
+        }
+
         let token = Auth::create_token(user.id, user.username.clone(), &user.jwt_secret)?;
 
         Ok(Json(ResponseData {
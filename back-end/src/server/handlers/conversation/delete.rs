@@ -1,14 +1,26 @@
-use crate::{database::Database, error::HttpResult};
+use crate::{
+    database::Database,
+    error::{HttpError, HttpResult},
+    server::auth::Auth,
+};
 use axum::{Extension, extract::Path};
+use axum_auth::AuthBearer;
 use std::sync::Arc;
 
-pub const PATH: &str = "/api/conversation/delete/{user_id}/{role_id}";
+pub const PATH: &str = "/api/conversation/delete/{role_id}";
 
 #[axum::debug_handler]
 pub async fn handler(
+    Extension(auth): Extension<Auth>,
     Extension(database): Extension<Arc<Database>>,
-    Path((user_id, role_id)): Path<(i32, i32)>,
+    AuthBearer(token): AuthBearer,
+    Path(role_id): Path<i32>,
 ) -> HttpResult<()> {
+    let user_id = auth
+        .verify(&token)
+        .await
+        .map_err(HttpError::Unauthorized)?
+        .id;
     database.delete_conversation(user_id, role_id).await?;
 
     Ok(())
@@ -1,5 +1,10 @@
-use crate::{database::Database, error::HttpResult};
+use crate::{
+    database::Database,
+    error::{HttpError, HttpResult},
+    server::auth::Auth,
+};
 use axum::{Extension, Json, extract::Query};
+use axum_auth::AuthBearer;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
@@ -7,13 +12,23 @@ pub const PATH: &str = "/api/conversation/dialogs";
 
 #[axum::debug_handler]
 pub async fn handler(
+    Extension(auth): Extension<Auth>,
     Extension(database): Extension<Arc<Database>>,
+    AuthBearer(token): AuthBearer,
     Query(params): Query<RequestParams>,
 ) -> HttpResult<Json<PaginatedResponse<ResponseDataItem>>> {
+    let user_id = auth
+        .verify(&token)
+        .await
+        .map_err(HttpError::Unauthorized)?
+        .id;
+    let conversation = database
+        .resolve_conversation(user_id, params.role_id, params.conversation_id)
+        .await?;
+
     let dialogs = database
-        .list_dialogs_paginated(
-            params.user_id,
-            params.role_id,
+        .list_dialogs_paginated_by_id(
+            conversation.id,
             params.offset.unwrap_or(0),
             params.limit.unwrap_or(20),
         )
@@ -23,14 +38,17 @@ pub async fn handler(
         .items
         .into_iter()
         .map(|dialog| ResponseDataItem {
+            id: dialog.id,
             is_user: dialog.is_user,
             timestamp: dialog.timestamp,
             text: dialog.text,
             voice: dialog.voice,
+            role_version: dialog.role_version,
         })
         .collect::<Vec<ResponseDataItem>>();
 
     Ok(Json(PaginatedResponse {
+        conversation_id: conversation.id,
         items: dialog_items,
         total: dialogs.total,
         has_more: dialogs.has_more,
@@ -39,14 +57,15 @@ pub async fn handler(
 
 #[derive(Deserialize)]
 pub struct RequestParams {
-    pub user_id: i32,
     pub role_id: i32,
+    pub conversation_id: Option<i32>, // 可选：查询分支对话
     pub offset: Option<i64>,
     pub limit: Option<i64>,
 }
 
 #[derive(Serialize)]
 pub struct PaginatedResponse<T> {
+    pub conversation_id: i32,
     pub items: Vec<T>,
     pub total: i64,
     pub has_more: bool,
@@ -54,8 +73,10 @@ pub struct PaginatedResponse<T> {
 
 #[derive(Serialize)]
 pub struct ResponseDataItem {
+    pub id: i32,
     pub is_user: bool,
     pub timestamp: i64,
     pub text: String,
     pub voice: Option<String>,
+    pub role_version: Option<i32>, // 生成该回复时的角色版本
 }
@@ -0,0 +1,108 @@
+use crate::{
+    database::Database,
+    error::{HttpError, HttpResult},
+    export::{ExportFormat, FORMAT_VERSION, Message, RoleDefinition, Speaker, Transcript},
+    server::auth::Auth,
+    storage::StorageClient,
+};
+use axum::{Extension, extract::Query, http::header};
+use axum_auth::AuthBearer;
+use serde::Deserialize;
+use std::sync::Arc;
+
+pub const PATH: &str = "/api/conversation/export";
+
+#[axum::debug_handler]
+pub async fn handler(
+    Extension(auth): Extension<Auth>,
+    Extension(database): Extension<Arc<Database>>,
+    Extension(storage_client): Extension<Arc<StorageClient>>,
+    AuthBearer(token): AuthBearer,
+    Query(RequestParams {
+        role_id,
+        conversation_id,
+        format,
+        embed_media,
+    }): Query<RequestParams>,
+) -> HttpResult<([(header::HeaderName, String); 2], String)> {
+    let user = auth.verify(&token).await.map_err(HttpError::Unauthorized)?;
+    let conversation = database
+        .resolve_conversation(user.id, role_id, conversation_id)
+        .await?;
+    let role = database.get_role(role_id).await?;
+    let dialogs = database.get_dialogs_by_id(conversation.id).await?;
+
+    let user_key = "user".to_string();
+    let role_key = format!("role_{}", role.id);
+
+    let messages = dialogs
+        .into_iter()
+        .map(|dialog| Message {
+            speaker: if dialog.is_user {
+                user_key.clone()
+            } else {
+                role_key.clone()
+            },
+            timestamp: dialog.timestamp,
+            text: dialog.text,
+            voice: dialog.voice,
+        })
+        .collect();
+
+    let mut transcript = Transcript {
+        format_version: FORMAT_VERSION,
+        title: format!("与{}的对话", role.name),
+        topic: None,
+        exported_at: chrono::Utc::now().timestamp_millis(),
+        summary: conversation.history,
+        speakers: vec![
+            Speaker {
+                key: user_key,
+                name: user.username,
+                avatar: user.image,
+                role: None,
+            },
+            Speaker {
+                key: role_key,
+                name: role.name,
+                avatar: role.image,
+                role: Some(RoleDefinition {
+                    role_id: role.id,
+                    description: role.description,
+                    traits: role.traits,
+                    voice_type: role.voice_type,
+                }),
+            },
+        ],
+        messages,
+    };
+
+    if embed_media.unwrap_or(false) {
+        transcript.embed_media(&storage_client).await;
+    }
+
+    let body = transcript.render(format)?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, format.content_type().to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!(
+                    "attachment; filename=\"conversation_{}.{}\"",
+                    conversation.id,
+                    format.extension()
+                ),
+            ),
+        ],
+        body,
+    ))
+}
+
+#[derive(Deserialize)]
+pub struct RequestParams {
+    pub role_id: i32,
+    pub conversation_id: Option<i32>, // 可选：导出分支对话
+    pub format: ExportFormat,
+    pub embed_media: Option<bool>, // 是否将头像和语音内联到导出文件中
+}
@@ -0,0 +1,69 @@
+use crate::{
+    agents::Summarizer,
+    database::Database,
+    error::{HttpError, HttpResult},
+    server::auth::Auth,
+};
+use anyhow::anyhow;
+use axum::{Extension, Json};
+use axum_auth::AuthBearer;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+pub const PATH: &str = "/api/conversation/fork";
+
+#[axum::debug_handler]
+pub async fn handler(
+    Extension(auth): Extension<Auth>,
+    Extension(database): Extension<Arc<Database>>,
+    Extension(summarizer): Extension<Arc<Summarizer>>,
+    AuthBearer(token): AuthBearer,
+    Json(RequestParams {
+        conversation_id,
+        dialog_id,
+    }): Json<RequestParams>,
+) -> HttpResult<Json<ResponseData>> {
+    let user_id = auth
+        .verify(&token)
+        .await
+        .map_err(HttpError::Unauthorized)?
+        .id;
+    let conversation = database
+        .get_conversation_by_id(conversation_id)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("Conversation not found"))?;
+
+    if conversation.user_id != user_id {
+        return Err(anyhow::anyhow!("Permission denied").into());
+    }
+
+    // 角色已对用户不可见时不再复制对话
+    if !database
+        .get_role(conversation.role_id)
+        .await?
+        .is_visible_to(Some(user_id))
+    {
+        return Err(HttpError::Forbidden(anyhow!("无权限与此角色对话")));
+    }
+
+    let branch_id = database
+        .fork_conversation(conversation_id, dialog_id)
+        .await?;
+
+    summarizer.trigger_rebuild(branch_id)?;
+
+    Ok(Json(ResponseData {
+        conversation_id: branch_id,
+    }))
+}
+
+#[derive(Deserialize)]
+pub struct RequestParams {
+    pub conversation_id: i32, // 被分叉的对话，可以是主对话或分支
+    pub dialog_id: i32,       // 分叉点，包含该条消息
+}
+
+#[derive(Serialize)]
+pub struct ResponseData {
+    pub conversation_id: i32,
+}
@@ -0,0 +1,68 @@
+use crate::{
+    agents::Summarizer,
+    database::Database,
+    error::{HttpError, HttpResult},
+    import,
+    server::auth::Auth,
+};
+use anyhow::anyhow;
+use axum::{Extension, Json, extract::Query};
+use axum_auth::AuthBearer;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+pub const PATH: &str = "/api/conversation/import";
+
+#[axum::debug_handler]
+pub async fn handler(
+    Extension(auth): Extension<Auth>,
+    Extension(database): Extension<Arc<Database>>,
+    Extension(summarizer): Extension<Arc<Summarizer>>,
+    AuthBearer(token): AuthBearer,
+    Query(RequestParams { role_id }): Query<RequestParams>,
+    body: String,
+) -> HttpResult<Json<ResponseData>> {
+    let user_id = auth
+        .verify(&token)
+        .await
+        .map_err(HttpError::Unauthorized)?
+        .id;
+
+    let dialogs = import::parse_sillytavern_chat(&body, chrono::Utc::now().timestamp_millis())?
+        .into_iter()
+        .map(|dialog| (dialog.is_user, dialog.timestamp, dialog.text))
+        .collect::<Vec<(bool, i64, String)>>();
+
+    // 导入到主对话中，已有记录时拒绝导入
+    if !database
+        .get_role(role_id)
+        .await?
+        .is_visible_to(Some(user_id))
+    {
+        return Err(HttpError::Forbidden(anyhow!("无权限与此角色对话")));
+    }
+
+    let conversation_id = database.create_conversation_table(user_id, role_id).await?;
+
+    database
+        .import_dialogs_by_id(conversation_id, &dialogs)
+        .await?;
+
+    summarizer.trigger_rebuild(conversation_id)?;
+
+    Ok(Json(ResponseData {
+        conversation_id,
+        imported_count: dialogs.len(),
+    }))
+}
+
+#[derive(Deserialize)]
+pub struct RequestParams {
+    pub role_id: i32,
+}
+
+#[derive(Serialize)]
+pub struct ResponseData {
+    pub conversation_id: i32,
+    pub imported_count: usize,
+}
@@ -1,5 +1,10 @@
-use crate::{database::Database, error::HttpResult};
+use crate::{
+    database::Database,
+    error::{HttpError, HttpResult},
+    server::auth::Auth,
+};
 use axum::{Extension, Json, extract::Query};
+use axum_auth::AuthBearer;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
@@ -7,12 +12,19 @@ pub const PATH: &str = "/api/conversation/list";
 
 #[axum::debug_handler]
 pub async fn handler(
+    Extension(auth): Extension<Auth>,
     Extension(database): Extension<Arc<Database>>,
+    AuthBearer(token): AuthBearer,
     Query(params): Query<RequestParams>,
 ) -> HttpResult<Json<PaginatedResponse>> {
+    let user_id = auth
+        .verify(&token)
+        .await
+        .map_err(HttpError::Unauthorized)?
+        .id;
     let conversations = database
         .list_conversations_paginated(
-            params.user_id,
+            user_id,
             params.offset.unwrap_or(0),
             params.limit.unwrap_or(15),
         )
@@ -33,7 +45,6 @@ pub async fn handler(
 
 #[derive(Deserialize)]
 pub struct RequestParams {
-    pub user_id: i32,
     pub offset: Option<i64>,
     pub limit: Option<i64>,
 }
@@ -1,4 +1,8 @@
 pub mod delete;
 pub mod dialogs;
+pub mod export;
+pub mod fork;
+pub mod import;
 pub mod list;
 pub mod new;
+pub mod tree;
@@ -1,22 +1,121 @@
-use crate::{database::Database, error::HttpResult};
+use crate::{
+    agents::{
+        reciter::{Reciter, remove_brackets},
+        role_jobs::user_room,
+    },
+    database::{Database, models::roles},
+    error::{HttpError, HttpResult},
+    server::{
+        auth::Auth,
+        sockets::message::{self, EmitData},
+    },
+};
+use anyhow::{Result, anyhow};
 use axum::{Extension, Json};
-use serde::Deserialize;
+use axum_auth::AuthBearer;
+use serde::{Deserialize, Serialize};
+use socketioxide::SocketIo;
 use std::sync::Arc;
 
 pub const PATH: &str = "/api/conversation/new";
 
 #[axum::debug_handler]
 pub async fn handler(
+    Extension(auth): Extension<Auth>,
     Extension(database): Extension<Arc<Database>>,
-    Json(RequestParams { user_id, role_id }): Json<RequestParams>,
-) -> HttpResult<()> {
-    database.create_conversation_table(user_id, role_id).await?;
+    Extension(reciter): Extension<Reciter>,
+    Extension(socket): Extension<Arc<SocketIo>>,
+    AuthBearer(token): AuthBearer,
+    Json(RequestParams { role_id }): Json<RequestParams>,
+) -> HttpResult<Json<ResponseData>> {
+    let user_id = auth
+        .verify(&token)
+        .await
+        .map_err(HttpError::Unauthorized)?
+        .id;
+    let role = database.get_role(role_id).await?;
 
-    Ok(())
+    if !role.is_visible_to(Some(user_id)) {
+        return Err(HttpError::Forbidden(anyhow!("无权限与此角色对话")));
+    }
+
+    let conversation_id = database.create_conversation_table(user_id, role_id).await?;
+
+    let greeting = greet(&database, &reciter, conversation_id, &role).await?;
+
+    // 同时推送给已连接的客户端
+    if let Some(greeting) = &greeting
+        && let Err(e) = socket
+            .to(user_room(user_id))
+            .emit(message::EVENT, greeting)
+            .await
+    {
+        tracing::warn!("Failed to emit greeting: {}", e);
+    }
+
+    Ok(Json(ResponseData {
+        conversation_id,
+        greeting,
+    }))
+}
+
+// 空对话由角色先说开场白，有多条时随机选一条
+async fn greet(
+    database: &Database,
+    reciter: &Reciter,
+    conversation_id: i32,
+    role: &roles::Model,
+) -> Result<Option<EmitData>> {
+    if database.get_dialog_count_by_id(conversation_id).await? > 0 {
+        return Ok(None);
+    }
+
+    let greetings = role.greetings();
+
+    if greetings.is_empty() {
+        return Ok(None);
+    }
+
+    let timestamp = chrono::Utc::now().timestamp_millis();
+    let text = greetings[timestamp as usize % greetings.len()].clone();
+
+    // 语音合成失败时仍保存文字开场白
+    let voice_url = match reciter.tts(&remove_brackets(&text), &role.voice_type).await {
+        Ok(audio_data) => Some(reciter.upload_audio(audio_data).await?),
+        Err(e) => {
+            tracing::warn!("Failed to synthesize greeting: {}", e);
+            None
+        }
+    };
+
+    database
+        .add_dialog_by_id(
+            conversation_id,
+            false,
+            timestamp,
+            &text,
+            voice_url.clone(),
+            Some(role.version),
+        )
+        .await?;
+
+    Ok(Some(EmitData {
+        role_id: role.id,
+        conversation_id,
+        timestamp,
+        text,
+        voice_url: voice_url.unwrap_or_default(),
+    }))
 }
 
 #[derive(Deserialize)]
 pub struct RequestParams {
-    pub user_id: i32,
     pub role_id: i32,
 }
+
+#[derive(Serialize)]
+pub struct ResponseData {
+    pub conversation_id: i32,
+    // 新对话的开场白，角色没有开场白或对话已有记录时为空
+    pub greeting: Option<EmitData>,
+}
@@ -0,0 +1,63 @@
+use crate::{
+    database::{Database, models::conversations},
+    error::{HttpError, HttpResult},
+    server::auth::Auth,
+};
+use axum::{Extension, Json, extract::Query};
+use axum_auth::AuthBearer;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+pub const PATH: &str = "/api/conversation/tree";
+
+#[axum::debug_handler]
+pub async fn handler(
+    Extension(auth): Extension<Auth>,
+    Extension(database): Extension<Arc<Database>>,
+    AuthBearer(token): AuthBearer,
+    Query(RequestParams { role_id }): Query<RequestParams>,
+) -> HttpResult<Json<TreeNode>> {
+    let user_id = auth
+        .verify(&token)
+        .await
+        .map_err(HttpError::Unauthorized)?
+        .id;
+    let conversations = database
+        .list_conversation_branches(user_id, role_id)
+        .await?;
+
+    let root = conversations
+        .iter()
+        .find(|conv| conv.parent_id.is_none())
+        .ok_or_else(|| anyhow::anyhow!("Conversation not found"))?;
+
+    Ok(Json(build_node(root, &conversations)))
+}
+
+fn build_node(conversation: &conversations::Model, all: &[conversations::Model]) -> TreeNode {
+    let children = all
+        .iter()
+        .filter(|conv| conv.parent_id == Some(conversation.id))
+        .map(|conv| build_node(conv, all))
+        .collect();
+
+    TreeNode {
+        conversation_id: conversation.id,
+        fork_dialog_id: conversation.fork_dialog_id,
+        last_dialog_timestamp: conversation.last_dialog_timestamp,
+        children,
+    }
+}
+
+#[derive(Deserialize)]
+pub struct RequestParams {
+    pub role_id: i32,
+}
+
+#[derive(Serialize)]
+pub struct TreeNode {
+    pub conversation_id: i32,
+    pub fork_dialog_id: Option<i32>, // 在父对话中的分叉点，主对话为空
+    pub last_dialog_timestamp: i64,
+    pub children: Vec<TreeNode>,
+}
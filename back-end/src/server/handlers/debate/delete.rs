@@ -1,5 +1,10 @@
-use crate::{database::Database, error::HttpResult};
+use crate::{
+    database::Database,
+    error::{HttpError, HttpResult},
+    server::auth::Auth,
+};
 use axum::{Extension, Json};
+use axum_auth::AuthBearer;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
@@ -7,9 +12,16 @@ pub const PATH: &str = "/api/debate/delete";
 
 #[axum::debug_handler]
 pub async fn handler(
+    Extension(auth): Extension<Auth>,
     Extension(database): Extension<Arc<Database>>,
-    Json(RequestParams { user_id, debate_id }): Json<RequestParams>,
+    AuthBearer(token): AuthBearer,
+    Json(RequestParams { debate_id }): Json<RequestParams>,
 ) -> HttpResult<Json<ResponseData>> {
+    let user_id = auth
+        .verify(&token)
+        .await
+        .map_err(HttpError::Unauthorized)?
+        .id;
     let debate = database
         .get_debate_by_id(debate_id)
         .await?
@@ -29,7 +41,6 @@ pub async fn handler(
 
 #[derive(Deserialize)]
 pub struct RequestParams {
-    pub user_id: i32,
     pub debate_id: i32,
 }
 
@@ -1,5 +1,10 @@
-use crate::{database::Database, error::HttpResult};
+use crate::{
+    database::Database,
+    error::{HttpError, HttpResult},
+    server::auth::Auth,
+};
 use axum::{Extension, Json};
+use axum_auth::AuthBearer;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
@@ -7,9 +12,16 @@ pub const PATH: &str = "/api/debate/dialogs";
 
 #[axum::debug_handler]
 pub async fn handler(
+    Extension(auth): Extension<Auth>,
     Extension(database): Extension<Arc<Database>>,
+    AuthBearer(token): AuthBearer,
     Json(params): Json<RequestParams>,
 ) -> HttpResult<Json<ResponseData>> {
+    let user_id = auth
+        .verify(&token)
+        .await
+        .map_err(HttpError::Unauthorized)?
+        .id;
     let debate = if let Some(debate_id) = params.debate_id {
         database
             .get_debate_by_id(debate_id)
@@ -17,7 +29,7 @@ pub async fn handler(
             .ok_or_else(|| anyhow::anyhow!("Debate not found"))?
     } else if let (Some(role1_id), Some(role2_id)) = (params.role1_id, params.role2_id) {
         database
-            .get_debate(params.user_id, role1_id, role2_id)
+            .get_debate(user_id, role1_id, role2_id)
             .await?
             .ok_or_else(|| anyhow::anyhow!("Debate not found"))?
     } else {
@@ -26,19 +38,17 @@ pub async fn handler(
         );
     };
 
+    if debate.user_id != user_id {
+        return Err(anyhow::anyhow!("Permission denied").into());
+    }
+
     let paginated_result = if let Some(debate_id) = params.debate_id {
         database
             .list_debate_dialogs_paginated_by_id(debate_id, params.offset, params.limit)
             .await?
     } else if let (Some(role1_id), Some(role2_id)) = (params.role1_id, params.role2_id) {
         database
-            .list_debate_dialogs_paginated(
-                params.user_id,
-                role1_id,
-                role2_id,
-                params.offset,
-                params.limit,
-            )
+            .list_debate_dialogs_paginated(user_id, role1_id, role2_id, params.offset, params.limit)
             .await?
     } else {
         return Err(
@@ -73,7 +83,6 @@ pub async fn handler(
 #[derive(Deserialize)]
 pub struct RequestParams {
     pub debate_id: Option<i32>, // 新增：通过debate_id查询
-    pub user_id: i32,           // 兼容性：通过用户和角色查询
     pub role1_id: Option<i32>,  // 可选：当使用debate_id时不需要
     pub role2_id: Option<i32>,  // 可选：当使用debate_id时不需要
     pub offset: i64,
@@ -0,0 +1,110 @@
+use crate::{
+    database::Database,
+    error::{HttpError, HttpResult},
+    export::{ExportFormat, FORMAT_VERSION, Message, RoleDefinition, Speaker, Transcript},
+    server::auth::Auth,
+    storage::StorageClient,
+};
+use axum::{Extension, extract::Query, http::header};
+use axum_auth::AuthBearer;
+use serde::Deserialize;
+use std::sync::Arc;
+
+pub const PATH: &str = "/api/debate/export";
+
+#[axum::debug_handler]
+pub async fn handler(
+    Extension(auth): Extension<Auth>,
+    Extension(database): Extension<Arc<Database>>,
+    Extension(storage_client): Extension<Arc<StorageClient>>,
+    AuthBearer(token): AuthBearer,
+    Query(RequestParams {
+        debate_id,
+        format,
+        embed_media,
+    }): Query<RequestParams>,
+) -> HttpResult<([(header::HeaderName, String); 2], String)> {
+    let user_id = auth
+        .verify(&token)
+        .await
+        .map_err(HttpError::Unauthorized)?
+        .id;
+    let debate = database
+        .get_debate_by_id(debate_id)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("Debate not found"))?;
+
+    if debate.user_id != user_id {
+        return Err(anyhow::anyhow!("Permission denied").into());
+    }
+
+    let role1 = database.get_role(debate.role1_id).await?;
+    let role2 = database.get_role(debate.role2_id).await?;
+    let dialogs = database
+        .get_debate_dialogs_by_id(debate.id, i64::MAX)
+        .await?;
+
+    let messages = dialogs
+        .into_iter()
+        .map(|dialog| Message {
+            speaker: format!("role_{}", dialog.role_id),
+            // 辩论记录的时间戳为秒
+            timestamp: dialog.timestamp * 1000,
+            text: dialog.text,
+            voice: dialog.voice,
+        })
+        .collect();
+
+    let speakers = [role1, role2]
+        .into_iter()
+        .map(|role| Speaker {
+            key: format!("role_{}", role.id),
+            name: role.name,
+            avatar: role.image,
+            role: Some(RoleDefinition {
+                role_id: role.id,
+                description: role.description,
+                traits: role.traits,
+                voice_type: role.voice_type,
+            }),
+        })
+        .collect::<Vec<Speaker>>();
+
+    let mut transcript = Transcript {
+        format_version: FORMAT_VERSION,
+        title: format!("{} vs {}", speakers[0].name, speakers[1].name),
+        topic: Some(debate.topic),
+        exported_at: chrono::Utc::now().timestamp_millis(),
+        summary: debate.history,
+        speakers,
+        messages,
+    };
+
+    if embed_media.unwrap_or(false) {
+        transcript.embed_media(&storage_client).await;
+    }
+
+    let body = transcript.render(format)?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, format.content_type().to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!(
+                    "attachment; filename=\"debate_{}.{}\"",
+                    debate.id,
+                    format.extension()
+                ),
+            ),
+        ],
+        body,
+    ))
+}
+
+#[derive(Deserialize)]
+pub struct RequestParams {
+    pub debate_id: i32,
+    pub format: ExportFormat,
+    pub embed_media: Option<bool>, // 是否将头像和语音内联到导出文件中
+}
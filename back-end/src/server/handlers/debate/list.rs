@@ -1,5 +1,10 @@
-use crate::{database::Database, error::HttpResult};
+use crate::{
+    database::Database,
+    error::{HttpError, HttpResult},
+    server::auth::Auth,
+};
 use axum::{Extension, Json};
+use axum_auth::AuthBearer;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
@@ -7,13 +12,16 @@ pub const PATH: &str = "/api/debate/list";
 
 #[axum::debug_handler]
 pub async fn handler(
+    Extension(auth): Extension<Auth>,
     Extension(database): Extension<Arc<Database>>,
-    Json(RequestParams {
-        user_id,
-        offset,
-        limit,
-    }): Json<RequestParams>,
+    AuthBearer(token): AuthBearer,
+    Json(RequestParams { offset, limit }): Json<RequestParams>,
 ) -> HttpResult<Json<ResponseData>> {
+    let user_id = auth
+        .verify(&token)
+        .await
+        .map_err(HttpError::Unauthorized)?
+        .id;
     let paginated_result = database
         .list_debates_paginated(user_id, offset, limit)
         .await?;
@@ -40,7 +48,6 @@ pub async fn handler(
 
 #[derive(Deserialize)]
 pub struct RequestParams {
-    pub user_id: i32,
     pub offset: i64,
     pub limit: i64,
 }
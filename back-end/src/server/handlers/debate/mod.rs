@@ -1,5 +1,6 @@
 pub mod delete;
 pub mod dialogs;
+pub mod export;
 pub mod list;
 pub mod new;
 pub mod start;
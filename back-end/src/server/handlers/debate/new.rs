@@ -1,5 +1,11 @@
-use crate::{database::Database, error::HttpResult};
+use crate::{
+    database::Database,
+    error::{HttpError, HttpResult},
+    server::auth::Auth,
+};
+use anyhow::anyhow;
 use axum::{Extension, Json};
+use axum_auth::AuthBearer;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
@@ -7,14 +13,29 @@ pub const PATH: &str = "/api/debate/new";
 
 #[axum::debug_handler]
 pub async fn handler(
+    Extension(auth): Extension<Auth>,
     Extension(database): Extension<Arc<Database>>,
+    AuthBearer(token): AuthBearer,
     Json(RequestParams {
-        user_id,
         role1_id,
         role2_id,
         topic,
     }): Json<RequestParams>,
 ) -> HttpResult<Json<ResponseData>> {
+    let user_id = auth
+        .verify(&token)
+        .await
+        .map_err(HttpError::Unauthorized)?
+        .id;
+
+    for role_id in [role1_id, role2_id] {
+        let role = database.get_role(role_id).await?;
+
+        if !role.is_visible_to(Some(user_id)) {
+            return Err(HttpError::Forbidden(anyhow!("无权限使用此角色辩论")));
+        }
+    }
+
     let debate_id = database
         .create_debate_table(user_id, role1_id, role2_id, &topic)
         .await?;
@@ -24,7 +45,6 @@ pub async fn handler(
 
 #[derive(Deserialize)]
 pub struct RequestParams {
-    pub user_id: i32,
     pub role1_id: i32,
     pub role2_id: i32,
     pub topic: String,
@@ -1,9 +1,12 @@
 use crate::{
     agents::{Debater, Reciter, reciter::remove_brackets},
     database::Database,
-    error::HttpResult,
+    error::{HttpError, HttpResult},
+    server::auth::Auth,
 };
+use anyhow::anyhow;
 use axum::{Extension, Json};
+use axum_auth::AuthBearer;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
@@ -11,11 +14,18 @@ pub const PATH: &str = "/api/debate/start";
 
 #[axum::debug_handler]
 pub async fn handler(
+    Extension(auth): Extension<Auth>,
     Extension(database): Extension<Arc<Database>>,
     Extension(debater): Extension<Debater>,
     Extension(reciter): Extension<Reciter>,
+    AuthBearer(token): AuthBearer,
     Json(params): Json<RequestParams>,
 ) -> HttpResult<Json<ResponseData>> {
+    let user_id = auth
+        .verify(&token)
+        .await
+        .map_err(HttpError::Unauthorized)?
+        .id;
     let debate = if let Some(debate_id) = params.debate_id {
         database
             .get_debate_by_id(debate_id)
@@ -23,7 +33,7 @@ pub async fn handler(
             .ok_or_else(|| anyhow::anyhow!("Debate not found"))?
     } else if let (Some(role1_id), Some(role2_id)) = (params.role1_id, params.role2_id) {
         database
-            .get_debate(params.user_id, role1_id, role2_id)
+            .get_debate(user_id, role1_id, role2_id)
             .await?
             .ok_or_else(|| anyhow::anyhow!("Debate not found"))?
     } else {
@@ -32,11 +42,22 @@ pub async fn handler(
         );
     };
 
+    if debate.user_id != user_id {
+        return Err(anyhow::anyhow!("Permission denied").into());
+    }
+
     let current_speaker_id = debate.current_speaker_id;
 
     let role1 = database.get_role(debate.role1_id).await?;
     let role2 = database.get_role(debate.role2_id).await?;
 
+    // 角色在辩论创建后被设为私有或被隐藏时不能继续
+    for role in [&role1, &role2] {
+        if !role.is_visible_to(Some(debate.user_id)) {
+            return Err(HttpError::Forbidden(anyhow!("无权限使用此角色辩论")));
+        }
+    }
+
     let (current_role, other_role) = if current_speaker_id == debate.role1_id {
         (role1, role2)
     } else {
@@ -90,7 +111,6 @@ pub async fn handler(
 #[derive(Deserialize)]
 pub struct RequestParams {
     pub debate_id: Option<i32>, // 新增：通过debate_id查询
-    pub user_id: i32,           // 兼容性：通过用户和角色查询
     pub role1_id: Option<i32>,  // 可选：当使用debate_id时不需要
     pub role2_id: Option<i32>,  // 可选：当使用debate_id时不需要
 }
@@ -0,0 +1,32 @@
+use crate::{
+    database::Database,
+    error::{HttpError, HttpResult},
+    server::auth::Auth,
+};
+use axum::{Extension, Json};
+use axum_auth::AuthBearer;
+use serde::Deserialize;
+use std::sync::Arc;
+
+pub const PATH: &str = "/api/lorebook/delete";
+
+#[axum::debug_handler]
+pub async fn handler(
+    Extension(auth): Extension<Auth>,
+    Extension(database): Extension<Arc<Database>>,
+    AuthBearer(token): AuthBearer,
+    Json(RequestParams { lorebook_id }): Json<RequestParams>,
+) -> HttpResult<()> {
+    let user = auth.verify(&token).await.map_err(HttpError::Unauthorized)?;
+
+    super::get_owned_lorebook(&database, user.id, lorebook_id).await?;
+
+    database.delete_lorebook(lorebook_id).await?;
+
+    Ok(())
+}
+
+#[derive(Deserialize)]
+pub struct RequestParams {
+    pub lorebook_id: i32,
+}
@@ -0,0 +1,34 @@
+use crate::{
+    database::Database,
+    error::{HttpError, HttpResult},
+    server::auth::Auth,
+};
+use axum::{Extension, Json};
+use axum_auth::AuthBearer;
+use serde::Deserialize;
+use std::sync::Arc;
+
+pub const PATH: &str = "/api/lorebook/entry/delete";
+
+#[axum::debug_handler]
+pub async fn handler(
+    Extension(auth): Extension<Auth>,
+    Extension(database): Extension<Arc<Database>>,
+    AuthBearer(token): AuthBearer,
+    Json(RequestParams { entry_id }): Json<RequestParams>,
+) -> HttpResult<()> {
+    let user = auth.verify(&token).await.map_err(HttpError::Unauthorized)?;
+
+    let entry = database.get_lorebook_entry(entry_id).await?;
+
+    super::get_owned_lorebook(&database, user.id, entry.lorebook_id).await?;
+
+    database.delete_lorebook_entry(entry_id).await?;
+
+    Ok(())
+}
+
+#[derive(Deserialize)]
+pub struct RequestParams {
+    pub entry_id: i32,
+}
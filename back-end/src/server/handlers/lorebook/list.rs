@@ -0,0 +1,92 @@
+use crate::{
+    database::{Database, models::lorebook_entries::Position},
+    error::{HttpError, HttpResult},
+    server::auth::Auth,
+};
+use axum::{Extension, Json, extract::Query};
+use axum_auth::AuthBearer;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+pub const PATH: &str = "/api/lorebook/list";
+
+#[axum::debug_handler]
+pub async fn handler(
+    Extension(auth): Extension<Auth>,
+    Extension(database): Extension<Arc<Database>>,
+    AuthBearer(token): AuthBearer,
+    Query(RequestParams {
+        role_id,
+        conversation_id,
+    }): Query<RequestParams>,
+) -> HttpResult<Json<ResponseData>> {
+    let user = auth.verify(&token).await.map_err(HttpError::Unauthorized)?;
+
+    let lorebooks = database
+        .list_lorebooks(user.id, role_id, conversation_id)
+        .await?;
+
+    let mut items = Vec::new();
+
+    for lorebook in lorebooks {
+        let entries = database
+            .list_lorebook_entries(lorebook.id)
+            .await?
+            .into_iter()
+            .map(|entry| EntryItem {
+                entry_id: entry.id,
+                keywords: entry
+                    .keyword_list()
+                    .into_iter()
+                    .map(|keyword| keyword.to_string())
+                    .collect(),
+                content: entry.content,
+                priority: entry.priority,
+                position: entry.position,
+                enabled: entry.enabled,
+            })
+            .collect();
+
+        items.push(LorebookItem {
+            lorebook_id: lorebook.id,
+            name: lorebook.name,
+            role_id: lorebook.role_id,
+            conversation_id: lorebook.conversation_id,
+            token_budget: lorebook.token_budget,
+            entries,
+        });
+    }
+
+    Ok(Json(ResponseData { lorebooks: items }))
+}
+
+#[derive(Deserialize)]
+pub struct RequestParams {
+    pub role_id: Option<i32>,
+    pub conversation_id: Option<i32>,
+}
+
+#[derive(Serialize)]
+pub struct ResponseData {
+    pub lorebooks: Vec<LorebookItem>,
+}
+
+#[derive(Serialize)]
+pub struct LorebookItem {
+    pub lorebook_id: i32,
+    pub name: String,
+    pub role_id: Option<i32>,
+    pub conversation_id: Option<i32>,
+    pub token_budget: i32,
+    pub entries: Vec<EntryItem>,
+}
+
+#[derive(Serialize)]
+pub struct EntryItem {
+    pub entry_id: i32,
+    pub keywords: Vec<String>,
+    pub content: String,
+    pub priority: i32,
+    pub position: Position,
+    pub enabled: bool,
+}
@@ -0,0 +1,23 @@
+pub mod delete;
+pub mod delete_entry;
+pub mod list;
+pub mod new;
+pub mod save_entry;
+
+use crate::database::{Database, models::lorebooks};
+use anyhow::Result;
+
+// 获取世界书并校验是否为该用户创建
+async fn get_owned_lorebook(
+    database: &Database,
+    user_id: i32,
+    lorebook_id: i32,
+) -> Result<lorebooks::Model> {
+    let lorebook = database.get_lorebook(lorebook_id).await?;
+
+    if lorebook.user_id != user_id {
+        return Err(anyhow::anyhow!("Permission denied"));
+    }
+
+    Ok(lorebook)
+}
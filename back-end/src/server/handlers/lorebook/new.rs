@@ -0,0 +1,73 @@
+use crate::{
+    database::Database,
+    error::{HttpError, HttpResult},
+    lorebook::DEFAULT_TOKEN_BUDGET,
+    server::auth::Auth,
+};
+use axum::{Extension, Json};
+use axum_auth::AuthBearer;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+pub const PATH: &str = "/api/lorebook/new";
+
+#[axum::debug_handler]
+pub async fn handler(
+    Extension(auth): Extension<Auth>,
+    Extension(database): Extension<Arc<Database>>,
+    AuthBearer(token): AuthBearer,
+    Json(RequestParams {
+        name,
+        role_id,
+        conversation_id,
+        token_budget,
+    }): Json<RequestParams>,
+) -> HttpResult<Json<ResponseData>> {
+    let user = auth.verify(&token).await.map_err(HttpError::Unauthorized)?;
+
+    // 角色上的世界书对所有用户生效，只有角色创建者可以添加
+    let owner_id = match (role_id, conversation_id) {
+        (Some(role_id), None) => database.get_role(role_id).await?.user_id,
+        (None, Some(conversation_id)) => {
+            database
+                .get_conversation_by_id(conversation_id)
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("Conversation not found"))?
+                .user_id
+        }
+        _ => {
+            return Err(
+                anyhow::anyhow!("Exactly one of role_id and conversation_id is required").into(),
+            );
+        }
+    };
+
+    if owner_id != user.id {
+        return Err(anyhow::anyhow!("Permission denied").into());
+    }
+
+    let lorebook_id = database
+        .add_lorebook(
+            user.id,
+            &name,
+            role_id,
+            conversation_id,
+            token_budget.unwrap_or(DEFAULT_TOKEN_BUDGET),
+        )
+        .await?;
+
+    Ok(Json(ResponseData { lorebook_id }))
+}
+
+#[derive(Deserialize)]
+pub struct RequestParams {
+    pub name: String,
+    pub role_id: Option<i32>,         // 挂在角色上，与conversation_id二选一
+    pub conversation_id: Option<i32>, // 挂在对话上，与role_id二选一
+    pub token_budget: Option<i32>,    // 可选：注入内容的token上限
+}
+
+#[derive(Serialize)]
+pub struct ResponseData {
+    pub lorebook_id: i32,
+}
@@ -0,0 +1,77 @@
+use crate::{
+    database::{Database, models::lorebook_entries::Position},
+    error::{HttpError, HttpResult},
+    server::auth::Auth,
+};
+use axum::{Extension, Json};
+use axum_auth::AuthBearer;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+pub const PATH: &str = "/api/lorebook/entry/save";
+
+#[axum::debug_handler]
+pub async fn handler(
+    Extension(auth): Extension<Auth>,
+    Extension(database): Extension<Arc<Database>>,
+    AuthBearer(token): AuthBearer,
+    Json(RequestParams {
+        lorebook_id,
+        entry_id,
+        keywords,
+        content,
+        priority,
+        position,
+        enabled,
+    }): Json<RequestParams>,
+) -> HttpResult<Json<ResponseData>> {
+    let user = auth.verify(&token).await.map_err(HttpError::Unauthorized)?;
+
+    super::get_owned_lorebook(&database, user.id, lorebook_id).await?;
+
+    if let Some(entry_id) = entry_id
+        && database.get_lorebook_entry(entry_id).await?.lorebook_id != lorebook_id
+    {
+        return Err(anyhow::anyhow!("Permission denied").into());
+    }
+
+    let keywords = keywords
+        .iter()
+        .map(|keyword| keyword.trim().to_string())
+        .filter(|keyword| !keyword.is_empty())
+        .collect::<Vec<String>>();
+
+    if keywords.is_empty() {
+        return Err(anyhow::anyhow!("At least one keyword is required").into());
+    }
+
+    let entry_id = database
+        .save_lorebook_entry(
+            entry_id,
+            lorebook_id,
+            &keywords,
+            &content,
+            priority.unwrap_or(0),
+            position.unwrap_or(Position::AfterRole),
+            enabled.unwrap_or(true),
+        )
+        .await?;
+
+    Ok(Json(ResponseData { entry_id }))
+}
+
+#[derive(Deserialize)]
+pub struct RequestParams {
+    pub lorebook_id: i32,
+    pub entry_id: Option<i32>, // 为空时新增条目
+    pub keywords: Vec<String>,
+    pub content: String,
+    pub priority: Option<i32>,      // 默认为0，越大越优先
+    pub position: Option<Position>, // 默认为after_role
+    pub enabled: Option<bool>,      // 默认为true
+}
+
+#[derive(Serialize)]
+pub struct ResponseData {
+    pub entry_id: i32,
+}
@@ -1,7 +1,11 @@
+pub mod admin;
 pub mod auth;
 pub mod conversation;
 pub mod debate;
 pub mod index;
+pub mod lorebook;
+pub mod moderation;
+pub mod report;
 pub mod role;
 pub mod upload;
 pub mod user;
@@ -0,0 +1,121 @@
+use crate::{
+    database::{
+        Database,
+        models::{
+            moderation_logs::ModerationAction,
+            reports::{ReportStatus, TargetType},
+        },
+    },
+    error::HttpResult,
+    server::auth::Moderator,
+};
+use anyhow::anyhow;
+use axum::{Extension, Json};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+pub const PATH: &str = "/api/moderation/content";
+
+#[axum::debug_handler]
+pub async fn handler(
+    Moderator(moderator): Moderator,
+    Extension(database): Extension<Arc<Database>>,
+    Json(RequestParams {
+        target_type,
+        target_id,
+        action,
+        reason,
+    }): Json<RequestParams>,
+) -> HttpResult<Json<ResponseData>> {
+    let status = match (action, target_type) {
+        (_, TargetType::User) => return Err(anyhow!("用户请使用用户审核接口").into()),
+        (ModerationAction::Hide, TargetType::Role) => {
+            database.set_role_hidden(target_id, true).await?;
+            ReportStatus::Resolved
+        }
+        (ModerationAction::Hide, TargetType::Review) => {
+            database.set_review_hidden(target_id, true).await?;
+            ReportStatus::Resolved
+        }
+        (ModerationAction::Restore, TargetType::Role) => {
+            database.set_role_hidden(target_id, false).await?;
+            ReportStatus::Dismissed
+        }
+        (ModerationAction::Restore, TargetType::Review) => {
+            database.set_review_hidden(target_id, false).await?;
+            ReportStatus::Dismissed
+        }
+        (ModerationAction::Hide | ModerationAction::Restore, TargetType::Conversation) => {
+            return Err(anyhow!("对话不能隐藏，只能删除").into());
+        }
+        (ModerationAction::Delete, TargetType::Role) => {
+            database.delete_role_and_conversations(target_id).await?;
+            ReportStatus::Resolved
+        }
+        (ModerationAction::Delete, TargetType::Review) => {
+            database.delete_role_review(target_id).await?;
+            ReportStatus::Resolved
+        }
+        // 连同对话的所有分支一起删除
+        (ModerationAction::Delete, TargetType::Conversation) => {
+            let conversation = database
+                .get_conversation_by_id(target_id)
+                .await?
+                .ok_or_else(|| anyhow!("Conversation not found"))?;
+
+            database
+                .delete_conversation(conversation.user_id, conversation.role_id)
+                .await?;
+            ReportStatus::Resolved
+        }
+        (ModerationAction::Dismiss, _) => ReportStatus::Dismissed,
+        (ModerationAction::Warn | ModerationAction::Suspend | ModerationAction::Reactivate, _) => {
+            return Err(anyhow!("用户请使用用户审核接口").into());
+        }
+        (ModerationAction::ChangeRole | ModerationAction::Transfer, _) => {
+            return Err(anyhow!("请使用管理接口").into());
+        }
+    };
+
+    let reports = database
+        .resolve_reports(target_type, target_id, status, moderator.id)
+        .await?;
+
+    database
+        .add_moderation_log(
+            moderator.id,
+            action,
+            target_type,
+            target_id,
+            reason.trim(),
+            None,
+        )
+        .await?;
+
+    Ok(Json(ResponseData {
+        target_type,
+        target_id,
+        action,
+        reports,
+    }))
+}
+
+#[derive(Deserialize)]
+pub struct RequestParams {
+    // role、review或conversation
+    pub target_type: TargetType,
+    pub target_id: i32,
+    // hide、delete、restore或dismiss
+    pub action: ModerationAction,
+    #[serde(default)]
+    pub reason: String,
+}
+
+#[derive(Serialize)]
+pub struct ResponseData {
+    pub target_type: TargetType,
+    pub target_id: i32,
+    pub action: ModerationAction,
+    // 随之处理的待处理举报数
+    pub reports: u64,
+}
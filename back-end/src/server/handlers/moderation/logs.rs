@@ -0,0 +1,87 @@
+use crate::{
+    database::{
+        Database,
+        models::{moderation_logs::ModerationAction, reports::TargetType},
+    },
+    error::HttpResult,
+    server::auth::Moderator,
+};
+use anyhow::anyhow;
+use axum::{Extension, Json, extract::Query};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+pub const PATH: &str = "/api/moderation/logs";
+
+#[axum::debug_handler]
+pub async fn handler(
+    _: Moderator,
+    Extension(database): Extension<Arc<Database>>,
+    Query(RequestParams {
+        offset,
+        limit,
+        target_type,
+        target_id,
+    }): Query<RequestParams>,
+) -> HttpResult<Json<ResponseData>> {
+    let offset = offset.unwrap_or(0).max(0);
+    let limit = limit.unwrap_or(15).max(1);
+
+    let target = match (target_type, target_id) {
+        (Some(target_type), Some(target_id)) => Some((target_type, target_id)),
+        (None, None) => None,
+        _ => return Err(anyhow!("target_type和target_id需要同时提供").into()),
+    };
+
+    let result = database
+        .list_moderation_logs_paginated(offset, limit, target)
+        .await?;
+
+    let logs = result
+        .items
+        .into_iter()
+        .map(|log| LogData {
+            id: log.id,
+            moderator_id: log.moderator_id,
+            action: log.action,
+            target_type: log.target_type,
+            target_id: log.target_id,
+            reason: log.reason,
+            detail: log.detail,
+            created_at: log.created_at,
+        })
+        .collect();
+
+    Ok(Json(ResponseData {
+        logs,
+        total: result.total,
+        has_more: result.has_more,
+    }))
+}
+
+#[derive(Deserialize)]
+pub struct RequestParams {
+    pub offset: Option<i64>,
+    pub limit: Option<i64>,
+    pub target_type: Option<TargetType>,
+    pub target_id: Option<i32>,
+}
+
+#[derive(Serialize)]
+pub struct ResponseData {
+    pub logs: Vec<LogData>,
+    pub total: i64,
+    pub has_more: bool,
+}
+
+#[derive(Serialize)]
+pub struct LogData {
+    pub id: i32,
+    pub moderator_id: i32,
+    pub action: ModerationAction,
+    pub target_type: TargetType,
+    pub target_id: i32,
+    pub reason: String,
+    pub detail: Option<String>,
+    pub created_at: i64,
+}
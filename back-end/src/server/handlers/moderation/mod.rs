@@ -0,0 +1,4 @@
+pub mod content;
+pub mod logs;
+pub mod queue;
+pub mod user;
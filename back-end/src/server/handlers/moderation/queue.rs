@@ -0,0 +1,117 @@
+use crate::{
+    database::{Database, models::reports::TargetType},
+    error::HttpResult,
+    moderation::{self, ReportGroup},
+    server::auth::Moderator,
+};
+use axum::{Extension, Json, extract::Query};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+pub const PATH: &str = "/api/moderation/queue";
+
+// 对话预览的消息数
+const PREVIEW_DIALOGS: i64 = 10;
+
+#[axum::debug_handler]
+pub async fn handler(
+    _: Moderator,
+    Extension(database): Extension<Arc<Database>>,
+    Query(RequestParams { offset, limit }): Query<RequestParams>,
+) -> HttpResult<Json<ResponseData>> {
+    let offset = offset.unwrap_or(0).max(0) as usize;
+    let limit = limit.unwrap_or(15).max(0) as usize;
+
+    let groups = moderation::group_reports(database.list_pending_reports().await?);
+    let total = groups.len();
+
+    let mut items = Vec::new();
+
+    for group in groups.into_iter().skip(offset).take(limit) {
+        let preview = preview(&database, group.target_type, group.target_id).await?;
+        items.push(QueueItem { group, preview });
+    }
+
+    Ok(Json(ResponseData {
+        items,
+        total: total as i64,
+        has_more: offset + limit < total,
+    }))
+}
+
+// 被举报内容的摘要，内容已被删除时为空
+async fn preview(
+    database: &Database,
+    target_type: TargetType,
+    target_id: i32,
+) -> anyhow::Result<Option<Preview>> {
+    let preview = match target_type {
+        TargetType::Role => database.get_role(target_id).await.ok().map(|role| Preview {
+            user_id: role.user_id,
+            text: format!("{}\n{}\n{}", role.name, role.description, role.traits),
+            hidden: role.hidden,
+        }),
+        TargetType::Review => database
+            .get_role_review(target_id)
+            .await
+            .ok()
+            .map(|review| Preview {
+                user_id: review.user_id,
+                text: format!("{}分\n{}", review.rating, review.content),
+                hidden: review.hidden,
+            }),
+        TargetType::Conversation => match database.get_conversation_by_id(target_id).await? {
+            Some(conversation) => {
+                let dialogs = database
+                    .get_recent_dialogs_by_id(conversation.id, PREVIEW_DIALOGS)
+                    .await?;
+                let text = dialogs
+                    .into_iter()
+                    .map(|dialog| {
+                        let speaker = if dialog.is_user { "用户" } else { "角色" };
+                        format!("{}：{}", speaker, dialog.text)
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n");
+
+                Some(Preview {
+                    user_id: conversation.user_id,
+                    text,
+                    hidden: false,
+                })
+            }
+            None => None,
+        },
+        TargetType::User => None,
+    };
+
+    Ok(preview)
+}
+
+#[derive(Deserialize)]
+pub struct RequestParams {
+    pub offset: Option<i64>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Serialize)]
+pub struct ResponseData {
+    pub items: Vec<QueueItem>,
+    pub total: i64,
+    pub has_more: bool,
+}
+
+#[derive(Serialize)]
+pub struct QueueItem {
+    #[serde(flatten)]
+    pub group: ReportGroup,
+    pub preview: Option<Preview>,
+}
+
+#[derive(Serialize)]
+pub struct Preview {
+    // 内容的作者，对话为对话的用户
+    pub user_id: i32,
+    pub text: String,
+    pub hidden: bool,
+}
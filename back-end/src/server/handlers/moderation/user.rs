@@ -0,0 +1,93 @@
+use crate::{
+    database::{
+        Database,
+        models::{moderation_logs::ModerationAction, reports::TargetType, users::UserRole},
+    },
+    error::{HttpError, HttpResult},
+    moderation,
+    server::auth::Moderator,
+};
+use anyhow::anyhow;
+use axum::{Extension, Json};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+pub const PATH: &str = "/api/moderation/user";
+
+#[axum::debug_handler]
+pub async fn handler(
+    Moderator(moderator): Moderator,
+    Extension(database): Extension<Arc<Database>>,
+    Json(RequestParams {
+        user_id,
+        action,
+        days,
+        reason,
+    }): Json<RequestParams>,
+) -> HttpResult<Json<ResponseData>> {
+    if user_id == moderator.id {
+        return Err(anyhow!("不能处理自己的账号").into());
+    }
+
+    let user = database.get_user_by_id(user_id).await?;
+
+    // 只有管理员可以处理审核员和管理员
+    if user.role.can_moderate() && moderator.role != UserRole::Admin {
+        return Err(HttpError::Forbidden(anyhow!("无权限处理此用户")));
+    }
+
+    let reason = reason.trim();
+
+    let (suspended_until, detail) = match action {
+        ModerationAction::Warn => (user.suspended_until, None),
+        ModerationAction::Suspend => {
+            let until = moderation::suspension_end(Utc::now().timestamp_millis(), days);
+            database
+                .set_user_suspended_until(user_id, Some(until))
+                .await?;
+            (Some(until), Some(format!("suspended_until={}", until)))
+        }
+        ModerationAction::Reactivate => {
+            database.set_user_suspended_until(user_id, None).await?;
+            (None, None)
+        }
+        _ => return Err(anyhow!("内容请使用内容审核接口").into()),
+    };
+
+    database
+        .add_moderation_log(
+            moderator.id,
+            action,
+            TargetType::User,
+            user_id,
+            reason,
+            detail,
+        )
+        .await?;
+
+    Ok(Json(ResponseData {
+        user_id,
+        action,
+        suspended_until,
+    }))
+}
+
+#[derive(Deserialize)]
+pub struct RequestParams {
+    pub user_id: i32,
+    // warn、suspend或reactivate
+    pub action: ModerationAction,
+    // 封禁天数，为空时永久封禁
+    pub days: Option<i64>,
+    #[serde(default)]
+    pub reason: String,
+}
+
+#[derive(Serialize)]
+pub struct ResponseData {
+    pub user_id: i32,
+    pub action: ModerationAction,
+    // 封禁到期时间，毫秒，未封禁时为空
+    pub suspended_until: Option<i64>,
+}
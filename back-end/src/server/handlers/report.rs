@@ -0,0 +1,110 @@
+use crate::{
+    database::{Database, models::reports::TargetType},
+    error::{HttpError, HttpResult},
+    server::auth::Auth,
+};
+use anyhow::anyhow;
+use axum::{Extension, Json};
+use axum_auth::AuthBearer;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+pub const PATH: &str = "/api/report";
+
+// 举报理由的最大长度（字符）
+const MAX_REASON_CHARS: usize = 200;
+
+#[axum::debug_handler]
+pub async fn handler(
+    Extension(auth): Extension<Auth>,
+    Extension(database): Extension<Arc<Database>>,
+    AuthBearer(token): AuthBearer,
+    Json(RequestParams {
+        target_type,
+        target_id,
+        reason,
+    }): Json<RequestParams>,
+) -> HttpResult<Json<ResponseData>> {
+    let user = auth.verify(&token).await.map_err(HttpError::Unauthorized)?;
+
+    let reason = reason.trim();
+
+    if reason.is_empty() {
+        return Err(anyhow!("请填写举报理由").into());
+    }
+
+    if reason.chars().count() > MAX_REASON_CHARS {
+        return Err(anyhow!("举报理由不能超过{}个字", MAX_REASON_CHARS).into());
+    }
+
+    let hidden = match target_type {
+        TargetType::Role => {
+            let role = database.get_role(target_id).await?;
+
+            if !role.is_visible_to(Some(user.id)) {
+                return Err(HttpError::Forbidden(anyhow!("无权限查看此角色")));
+            }
+
+            if role.user_id == user.id {
+                return Err(anyhow!("不能举报自己的角色").into());
+            }
+
+            database
+                .add_report(user.id, target_type, target_id, reason)
+                .await?;
+
+            false
+        }
+        TargetType::Review => {
+            let review = database.get_role_review(target_id).await?;
+
+            if review.user_id == user.id {
+                return Err(anyhow!("不能举报自己的评价").into());
+            }
+
+            database
+                .report_role_review(target_id, user.id, reason)
+                .await?
+        }
+        // 对话不能分享，只能举报自己的对话中角色的回复
+        TargetType::Conversation => {
+            let conversation = database
+                .get_conversation_by_id(target_id)
+                .await?
+                .ok_or_else(|| anyhow!("Conversation not found"))?;
+
+            if conversation.user_id != user.id {
+                return Err(HttpError::Forbidden(anyhow!("无权限查看此对话")));
+            }
+
+            database
+                .add_report(user.id, target_type, target_id, reason)
+                .await?;
+
+            false
+        }
+        TargetType::User => return Err(anyhow!("不能举报用户").into()),
+    };
+
+    Ok(Json(ResponseData {
+        target_type,
+        target_id,
+        hidden,
+    }))
+}
+
+#[derive(Deserialize)]
+pub struct RequestParams {
+    // role、review或conversation
+    pub target_type: TargetType,
+    pub target_id: i32,
+    pub reason: String,
+}
+
+#[derive(Serialize)]
+pub struct ResponseData {
+    pub target_type: TargetType,
+    pub target_id: i32,
+    // 评价被多人举报后自动隐藏
+    pub hidden: bool,
+}
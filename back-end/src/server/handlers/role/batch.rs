@@ -0,0 +1,184 @@
+use crate::{
+    agents::{RoleBatches, role_batches::BatchData},
+    database::{
+        Database,
+        models::{role_batches, role_jobs::Status},
+    },
+    error::{HttpError, HttpResult},
+    import,
+    server::auth::Auth,
+};
+use anyhow::anyhow;
+use axum::{
+    Extension, Json,
+    body::Bytes,
+    extract::{Path, Query},
+    http::header,
+};
+use axum_auth::AuthBearer;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+pub const NEW_PATH: &str = "/api/role/batch/new";
+pub const STATUS_PATH: &str = "/api/role/batch/{batch_id}";
+pub const REPORT_PATH: &str = "/api/role/batch/{batch_id}/report";
+
+// 单个批量任务最多包含的名字数
+const MAX_BATCH_SIZE: usize = 100;
+
+#[axum::debug_handler]
+pub async fn new_handler(
+    Extension(auth): Extension<Auth>,
+    Extension(database): Extension<Arc<Database>>,
+    Extension(role_batches): Extension<Arc<RoleBatches>>,
+    AuthBearer(token): AuthBearer,
+    body: Bytes,
+) -> HttpResult<Json<ResponseData>> {
+    let user = auth.verify(&token).await.map_err(HttpError::Unauthorized)?;
+
+    let entries = import::parse_role_list(&String::from_utf8_lossy(&body))?;
+
+    if entries.len() > MAX_BATCH_SIZE {
+        return Err(anyhow!("每批最多{}个名字", MAX_BATCH_SIZE).into());
+    }
+
+    // 每个用户同时只能运行一个批量任务
+    if database.has_unfinished_role_batch(user.id).await? {
+        return Err(anyhow!("已有正在进行的批量任务，请等待完成后再提交").into());
+    }
+
+    let batch_id = database.add_role_batch(user.id, &entries).await?;
+
+    role_batches.start(batch_id).await?;
+
+    Ok(Json(ResponseData {
+        batch_id,
+        total: entries.len(),
+    }))
+}
+
+#[axum::debug_handler]
+pub async fn status_handler(
+    Extension(auth): Extension<Auth>,
+    Extension(database): Extension<Arc<Database>>,
+    AuthBearer(token): AuthBearer,
+    Path(batch_id): Path<i32>,
+) -> HttpResult<Json<BatchData>> {
+    let batch = get_owned_batch(&auth, &database, &token, batch_id).await?;
+    let items = database.list_role_batch_items(batch_id).await?;
+
+    Ok(Json(BatchData::new(&batch, &items)))
+}
+
+// 任务结束后下载逐项报告
+#[axum::debug_handler]
+pub async fn report_handler(
+    Extension(auth): Extension<Auth>,
+    Extension(database): Extension<Arc<Database>>,
+    AuthBearer(token): AuthBearer,
+    Path(batch_id): Path<i32>,
+    Query(ReportParams { format }): Query<ReportParams>,
+) -> HttpResult<([(header::HeaderName, String); 2], Vec<u8>)> {
+    let batch = get_owned_batch(&auth, &database, &token, batch_id).await?;
+
+    if matches!(batch.status, Status::Pending | Status::Running) {
+        return Err(anyhow!("批量任务尚未完成").into());
+    }
+
+    let items = database.list_role_batch_items(batch_id).await?;
+    let data = BatchData::new(&batch, &items);
+
+    let (content_type, extension, body) = match format {
+        ReportFormat::Json => (
+            "application/json",
+            "json",
+            serde_json::to_string_pretty(&data)?.into_bytes(),
+        ),
+        ReportFormat::Csv => ("text/csv; charset=utf-8", "csv", to_csv(&data).into_bytes()),
+    };
+
+    let file_name = format!("role_batch_{}.{}", batch_id, extension);
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, content_type.to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", file_name),
+            ),
+        ],
+        body,
+    ))
+}
+
+async fn get_owned_batch(
+    auth: &Auth,
+    database: &Database,
+    token: &str,
+    batch_id: i32,
+) -> HttpResult<role_batches::Model> {
+    let user = auth.verify(token).await.map_err(HttpError::Unauthorized)?;
+
+    let batch = database.get_role_batch(batch_id).await?;
+
+    if batch.user_id != user.id {
+        return Err(HttpError::Forbidden(anyhow!("无权限访问此任务")));
+    }
+
+    Ok(batch)
+}
+
+fn to_csv(data: &BatchData) -> String {
+    let mut csv = String::from("position,name,status,role_id,tags,error\n");
+
+    for item in &data.items {
+        let fields = [
+            item.position.to_string(),
+            item.name.clone(),
+            item.status.to_string(),
+            item.role_id.map(|id| id.to_string()).unwrap_or_default(),
+            item.tags.join(";"),
+            item.error.clone().unwrap_or_default(),
+        ];
+
+        let line = fields
+            .iter()
+            .map(|field| escape_csv(field))
+            .collect::<Vec<_>>()
+            .join(",");
+
+        csv.push_str(&line);
+        csv.push('\n');
+    }
+
+    csv
+}
+
+fn escape_csv(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ReportParams {
+    #[serde(default)]
+    pub format: ReportFormat,
+}
+
+#[derive(Default, Deserialize)]
+pub enum ReportFormat {
+    #[default]
+    #[serde(rename = "csv")]
+    Csv,
+    #[serde(rename = "json")]
+    Json,
+}
+
+#[derive(Serialize)]
+pub struct ResponseData {
+    pub batch_id: i32,
+    pub total: usize,
+}
@@ -0,0 +1,25 @@
+use crate::database::models::roles::Category;
+use axum::Json;
+use serde::Serialize;
+
+pub const PATH: &str = "/api/role/categories";
+
+// 固定的分类列表，供前端展示筛选项
+#[axum::debug_handler]
+pub async fn handler() -> Json<Vec<ResponseItem>> {
+    Json(
+        Category::ALL
+            .iter()
+            .map(|category| ResponseItem {
+                value: *category,
+                label: category.label(),
+            })
+            .collect(),
+    )
+}
+
+#[derive(Serialize)]
+pub struct ResponseItem {
+    pub value: Category,
+    pub label: &'static str,
+}
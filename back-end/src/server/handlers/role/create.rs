@@ -1,11 +1,18 @@
 use crate::{
     database::{
         Database,
-        models::roles::{AgeGroup, Gender},
+        models::roles::{
+            AgeGroup, Category, ExampleDialogue, Gender, MAX_EXAMPLE_DIALOGUES, MAX_GREETINGS,
+            RoleIdentity, Visibility, validate_tags,
+        },
     },
-    error::HttpResult,
+    error::{HttpError, HttpResult},
+    knowledge::ImageAttribution,
+    server::auth::Auth,
 };
+use anyhow::anyhow;
 use axum::{Extension, Json};
+use axum_auth::AuthBearer;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
@@ -13,9 +20,10 @@ pub const PATH: &str = "/api/role/create";
 
 #[axum::debug_handler]
 pub async fn handler(
+    Extension(auth): Extension<Auth>,
     Extension(database): Extension<Arc<Database>>,
+    AuthBearer(token): AuthBearer,
     Json(RequestParams {
-        user_id,
         name,
         description,
         traits,
@@ -23,11 +31,30 @@ pub async fn handler(
         gender,
         age_group,
         voice_type,
+        image_attribution,
+        example_dialogues,
+        greetings,
+        identity,
+        visibility,
+        category,
+        tags,
     }): Json<RequestParams>,
 ) -> HttpResult<Json<ResponseData>> {
+    let user = auth.verify(&token).await.map_err(HttpError::Unauthorized)?;
+
+    if example_dialogues.len() > MAX_EXAMPLE_DIALOGUES {
+        return Err(anyhow!("最多{}组示例对话", MAX_EXAMPLE_DIALOGUES).into());
+    }
+
+    if greetings.len() > MAX_GREETINGS {
+        return Err(anyhow!("最多{}条开场白", MAX_GREETINGS).into());
+    }
+
+    let tags = validate_tags(&tags)?;
+
     let role_id = database
         .add_role(
-            user_id,
+            user.id,
             &name,
             &description,
             &traits,
@@ -35,15 +62,35 @@ pub async fn handler(
             gender,
             age_group,
             &voice_type,
+            &example_dialogues,
+            &greetings,
+            visibility,
         )
         .await?;
 
+    if let Some(image_attribution) = image_attribution {
+        database
+            .set_role_image_attribution(role_id, Some(&serde_json::to_string(&image_attribution)?))
+            .await?;
+    }
+
+    if !tags.is_empty() {
+        database.set_role_tags(role_id, &tags).await?;
+    }
+
+    if category.is_some() {
+        database.set_role_category(role_id, category).await?;
+    }
+
+    if !identity.is_empty() {
+        database.set_role_identity(role_id, &identity).await?;
+    }
+
     Ok(Json(ResponseData { role_id }))
 }
 
 #[derive(Deserialize)]
 pub struct RequestParams {
-    pub user_id: i32,
     pub name: String,
     pub description: String,
     pub traits: String,
@@ -51,6 +98,22 @@ pub struct RequestParams {
     pub gender: Gender,
     pub age_group: AgeGroup,
     pub voice_type: String,
+    // 头像取自维基百科时的署名和许可协议
+    pub image_attribution: Option<ImageAttribution>,
+    #[serde(default)]
+    pub example_dialogues: Vec<ExampleDialogue>,
+    // 新对话的开场白，有多条时随机选用
+    #[serde(default)]
+    pub greetings: Vec<String>,
+    // 自动生成时返回的规范标题和维基条目
+    #[serde(flatten)]
+    pub identity: RoleIdentity,
+    // 默认公开
+    #[serde(default)]
+    pub visibility: Visibility,
+    pub category: Option<Category>,
+    #[serde(default)]
+    pub tags: Vec<String>,
 }
 
 #[derive(Serialize)]
@@ -1,4 +1,17 @@
-use crate::{database::Database, error::HttpResult};
+use crate::{
+    database::{
+        Database,
+        models::{
+            role_reactions::ReactionKind,
+            roles::{self, Category, ExampleDialogue, RoleIdentity, Visibility},
+        },
+    },
+    error::{HttpError, HttpResult},
+    knowledge::ImageAttribution,
+    server::auth::Viewer,
+    stats::RoleStats,
+};
+use anyhow::anyhow;
 use axum::{Extension, Json, extract::Query};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
@@ -7,17 +20,70 @@ pub const PATH: &str = "/api/role/details";
 
 #[axum::debug_handler]
 pub async fn handler(
+    viewer: Viewer,
     Extension(database): Extension<Arc<Database>>,
     Query(RequestData { role_id }): Query<RequestData>,
 ) -> HttpResult<Json<ResponseData>> {
+    let user_id = viewer.id();
     let role = database.get_role(role_id).await?;
 
+    if !role.is_visible_to(user_id) {
+        return Err(HttpError::Forbidden(anyhow!("无权限查看此角色")));
+    }
+
+    let example_dialogues = role.example_dialogues();
+    let greetings = role.greetings();
+    let identity = role.identity();
+    let tags = database.get_role_tags(role_id).await?;
+    let stats = database.get_role_stats(role_id).await?;
+    let reactions = match user_id {
+        Some(user_id) => database.get_user_reactions(user_id, role_id).await?,
+        None => Vec::new(),
+    };
+
+    // 原角色对查看者不可见时不显示署名
+    let parent = match role.parent_id {
+        Some(parent_id) => database
+            .get_role(parent_id)
+            .await
+            .ok()
+            .filter(|parent| parent.is_visible_to(user_id))
+            .map(RoleSummary::from),
+        None => None,
+    };
+
+    // 只列出未被隐藏的公开分叉，以及查看者自己的分叉
+    let forks = database
+        .list_role_forks(role_id)
+        .await?
+        .into_iter()
+        .filter(|fork| {
+            (fork.visibility == Visibility::Public && !fork.hidden) || user_id == Some(fork.user_id)
+        })
+        .map(RoleSummary::from)
+        .collect();
+
     Ok(Json(ResponseData {
         role_id: role.id,
         name: role.name,
         description: role.description,
         traits: role.traits,
         image_url: role.image,
+        image_attribution: role
+            .image_attribution
+            .and_then(|json| serde_json::from_str(&json).ok()),
+        version: role.version,
+        example_dialogues,
+        greetings,
+        identity,
+        visibility: role.visibility,
+        category: role.category,
+        tags,
+        stats,
+        reactions,
+        allow_fork: role.allow_fork,
+        parent,
+        forks,
     }))
 }
 
@@ -33,4 +99,39 @@ pub struct ResponseData {
     pub description: String,
     pub traits: String,
     pub image_url: String,
+    pub image_attribution: Option<ImageAttribution>,
+    pub version: i32,
+    pub example_dialogues: Vec<ExampleDialogue>,
+    pub greetings: Vec<String>,
+    #[serde(flatten)]
+    pub identity: RoleIdentity,
+    pub visibility: Visibility,
+    pub category: Option<Category>,
+    pub tags: Vec<String>,
+    pub stats: RoleStats,
+    // 查看者对该角色的收藏和点赞
+    pub reactions: Vec<ReactionKind>,
+    pub allow_fork: bool,
+    // 分叉自的角色
+    pub parent: Option<RoleSummary>,
+    pub forks: Vec<RoleSummary>,
+}
+
+#[derive(Serialize)]
+pub struct RoleSummary {
+    pub role_id: i32,
+    pub user_id: i32,
+    pub name: String,
+    pub image_url: String,
+}
+
+impl From<roles::Model> for RoleSummary {
+    fn from(role: roles::Model) -> Self {
+        Self {
+            role_id: role.id,
+            user_id: role.user_id,
+            name: role.name,
+            image_url: role.image,
+        }
+    }
 }
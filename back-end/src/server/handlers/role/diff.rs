@@ -0,0 +1,87 @@
+use crate::{
+    database::{Database, models::roles::ExampleDialogue},
+    diff,
+    error::{HttpError, HttpResult},
+    server::auth::Viewer,
+};
+use anyhow::anyhow;
+use axum::{Extension, Json, extract::Query};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+pub const PATH: &str = "/api/role/diff";
+
+#[axum::debug_handler]
+pub async fn handler(
+    viewer: Viewer,
+    Extension(database): Extension<Arc<Database>>,
+    Query(RequestParams { role_id, from, to }): Query<RequestParams>,
+) -> HttpResult<Json<ResponseData>> {
+    if !database.get_role(role_id).await?.is_visible_to(viewer.id()) {
+        return Err(HttpError::Forbidden(anyhow!("无权限查看此角色")));
+    }
+
+    let old = database.get_role_version(role_id, from).await?;
+    let new = database.get_role_version(role_id, to).await?;
+
+    let examples = (
+        ExampleDialogue::to_text(&old.example_dialogues()),
+        ExampleDialogue::to_text(&new.example_dialogues()),
+    );
+
+    let greetings = (old.greetings().join("\n"), new.greetings().join("\n"));
+
+    let fields = [
+        ("name", old.name, new.name),
+        ("description", old.description, new.description),
+        ("traits", old.traits, new.traits),
+        ("image", old.image, new.image),
+        ("gender", old.gender.to_string(), new.gender.to_string()),
+        (
+            "age_group",
+            old.age_group.to_string(),
+            new.age_group.to_string(),
+        ),
+        ("voice_type", old.voice_type, new.voice_type),
+        ("example_dialogues", examples.0, examples.1),
+        ("greetings", greetings.0, greetings.1),
+    ];
+
+    // 只返回有变化的字段
+    let changes = fields
+        .into_iter()
+        .filter(|(_, old, new)| old != new)
+        .map(|(field, old, new)| FieldDiff {
+            field: field.to_string(),
+            lines: diff::diff_lines(&old, &new),
+        })
+        .collect();
+
+    Ok(Json(ResponseData {
+        role_id,
+        from,
+        to,
+        changes,
+    }))
+}
+
+#[derive(Deserialize)]
+pub struct RequestParams {
+    pub role_id: i32,
+    pub from: i32,
+    pub to: i32,
+}
+
+#[derive(Serialize)]
+pub struct ResponseData {
+    pub role_id: i32,
+    pub from: i32,
+    pub to: i32,
+    pub changes: Vec<FieldDiff>,
+}
+
+#[derive(Serialize)]
+pub struct FieldDiff {
+    pub field: String,
+    pub lines: Vec<diff::Line>,
+}
@@ -0,0 +1,51 @@
+use crate::{
+    database::{
+        Database,
+        models::roles::{RoleIdentity, Visibility},
+    },
+    error::HttpResult,
+};
+use anyhow::Result;
+use axum::{Extension, Json, extract::Query};
+use serde::Serialize;
+use std::sync::Arc;
+
+pub const PATH: &str = "/api/role/duplicates";
+
+// 创建角色前查询同一人物的已有角色，前端可提示改用或复刻
+#[axum::debug_handler]
+pub async fn handler(
+    Extension(database): Extension<Arc<Database>>,
+    Query(identity): Query<RoleIdentity>,
+) -> HttpResult<Json<Vec<ResponseItem>>> {
+    Ok(Json(find_duplicates(&database, &identity).await?))
+}
+
+pub async fn find_duplicates(
+    database: &Database,
+    identity: &RoleIdentity,
+) -> Result<Vec<ResponseItem>> {
+    let roles = database.find_roles_by_identity(identity).await?;
+
+    // 只提示未被隐藏的公开角色
+    Ok(roles
+        .into_iter()
+        .filter(|role| role.visibility == Visibility::Public && !role.hidden)
+        .map(|role| ResponseItem {
+            role_id: role.id,
+            user_id: role.user_id,
+            name: role.name,
+            description: role.description,
+            image_url: role.image,
+        })
+        .collect())
+}
+
+#[derive(Serialize)]
+pub struct ResponseItem {
+    pub role_id: i32,
+    pub user_id: i32,
+    pub name: String,
+    pub description: String,
+    pub image_url: String,
+}
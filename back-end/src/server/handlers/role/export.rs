@@ -0,0 +1,97 @@
+use crate::{
+    card,
+    database::Database,
+    error::{HttpError, HttpResult},
+    server::auth::Viewer,
+    storage::StorageClient,
+};
+use anyhow::anyhow;
+use axum::{Extension, extract::Query, http::header};
+use serde::Deserialize;
+use std::sync::Arc;
+
+pub const PATH: &str = "/api/role/export";
+
+// 头像的大小上限
+const MAX_AVATAR_BYTES: usize = 10 * 1024 * 1024;
+
+#[axum::debug_handler]
+pub async fn handler(
+    viewer: Viewer,
+    Extension(database): Extension<Arc<Database>>,
+    Extension(storage_client): Extension<Arc<StorageClient>>,
+    Query(RequestParams { role_id, format }): Query<RequestParams>,
+) -> HttpResult<([(header::HeaderName, String); 2], Vec<u8>)> {
+    let role = database.get_role(role_id).await?;
+
+    if !role.is_visible_to(viewer.id()) {
+        return Err(HttpError::Forbidden(anyhow!("无权限导出此角色")));
+    }
+
+    let creator = database.get_user_by_id(role.user_id).await?;
+    let mut data = card::CardData::from_role(&role, &creator.username)?;
+    data.tags = database.get_role_tags(role_id).await?;
+
+    let (content_type, extension, body) = match format {
+        CardFormat::Json => (
+            "application/json",
+            "json",
+            card::to_json(&data)?.into_bytes(),
+        ),
+        CardFormat::Png => {
+            let image = fetch_png(&storage_client, &role.image).await;
+
+            ("image/png", "png", card::to_png(&data, &image)?)
+        }
+    };
+
+    let file_name = format!("role_{}.{}", role.id, extension);
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, content_type.to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", file_name),
+            ),
+        ],
+        body,
+    ))
+}
+
+// 头像不在存储空间中、不是PNG或下载失败时使用占位图片
+async fn fetch_png(storage_client: &StorageClient, url: &str) -> Vec<u8> {
+    if url.is_empty() {
+        return card::png::placeholder();
+    }
+
+    let image = match storage_client.fetch_object(url, MAX_AVATAR_BYTES).await {
+        Ok((_, image)) => Some(image),
+        Err(e) => {
+            tracing::warn!("Failed to fetch avatar {}: {}", url, e);
+            None
+        }
+    };
+
+    match image {
+        Some(image) if card::png::is_png(&image) => image,
+        _ => {
+            tracing::warn!("Avatar is not a PNG image, using placeholder: {}", url);
+            card::png::placeholder()
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct RequestParams {
+    pub role_id: i32,
+    pub format: CardFormat,
+}
+
+#[derive(Deserialize)]
+pub enum CardFormat {
+    #[serde(rename = "json")]
+    Json,
+    #[serde(rename = "png")]
+    Png,
+}
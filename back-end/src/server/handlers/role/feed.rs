@@ -0,0 +1,84 @@
+use crate::{
+    agents::{Recommender, recommender::Reason},
+    database::models::roles::{AgeGroup, Category, Gender},
+    error::{HttpError, HttpResult},
+    server::auth::Auth,
+};
+use axum::{Extension, Json, extract::Query};
+use axum_auth::AuthBearer;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+pub const PATH: &str = "/api/role/for-you";
+
+#[axum::debug_handler]
+pub async fn handler(
+    Extension(auth): Extension<Auth>,
+    Extension(recommender): Extension<Arc<Recommender>>,
+    AuthBearer(token): AuthBearer,
+    Query(RequestParams { offset, limit }): Query<RequestParams>,
+) -> HttpResult<Json<ResponseData>> {
+    let user = auth.verify(&token).await.map_err(HttpError::Unauthorized)?;
+
+    let offset = offset.unwrap_or(0).max(0) as usize;
+    let limit = limit.unwrap_or(15).max(0) as usize;
+
+    let feed = recommender.feed(user.id).await?;
+    let total = feed.len();
+
+    let items = feed
+        .into_iter()
+        .skip(offset)
+        .take(limit)
+        .map(|recommendation| {
+            let role = recommendation.role;
+
+            ResponseItem {
+                role_id: role.id,
+                user_id: role.user_id,
+                name: role.name,
+                description: role.description,
+                image_url: role.image,
+                gender: role.gender,
+                age_group: role.age_group,
+                category: role.category,
+                explanation: recommendation.reason.explain(),
+                reason: recommendation.reason,
+            }
+        })
+        .collect();
+
+    Ok(Json(ResponseData {
+        items,
+        total: total as i64,
+        has_more: offset + limit < total,
+    }))
+}
+
+#[derive(Deserialize)]
+pub struct RequestParams {
+    pub offset: Option<i64>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Serialize)]
+pub struct ResponseData {
+    pub items: Vec<ResponseItem>,
+    pub total: i64,
+    pub has_more: bool,
+}
+
+#[derive(Serialize)]
+pub struct ResponseItem {
+    pub role_id: i32,
+    pub user_id: i32,
+    pub name: String,
+    pub description: String,
+    pub image_url: String,
+    pub gender: Gender,
+    pub age_group: AgeGroup,
+    pub category: Option<Category>,
+    pub reason: Reason,
+    // 如"因为你和爱因斯坦聊过"
+    pub explanation: String,
+}
@@ -0,0 +1,93 @@
+use crate::{
+    database::{Database, models::roles::Visibility},
+    error::{HttpError, HttpResult},
+    server::auth::Auth,
+};
+use anyhow::anyhow;
+use axum::{Extension, Json};
+use axum_auth::AuthBearer;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+pub const FORK_PATH: &str = "/api/role/fork";
+pub const ALLOW_PATH: &str = "/api/role/allow-fork";
+
+#[axum::debug_handler]
+pub async fn fork_handler(
+    Extension(auth): Extension<Auth>,
+    Extension(database): Extension<Arc<Database>>,
+    AuthBearer(token): AuthBearer,
+    Json(ForkParams { role_id }): Json<ForkParams>,
+) -> HttpResult<Json<ForkResponse>> {
+    let user = auth.verify(&token).await.map_err(HttpError::Unauthorized)?;
+
+    let role = database.get_role(role_id).await?;
+
+    // 创建者可以复制自己的任何角色
+    if role.user_id != user.id {
+        if role.visibility != Visibility::Public || role.hidden {
+            return Err(HttpError::Forbidden(anyhow!("只能分叉公开角色")));
+        }
+
+        if !role.allow_fork {
+            return Err(HttpError::Forbidden(anyhow!("作者不允许分叉此角色")));
+        }
+    }
+
+    let forked_id = database.fork_role(&role, user.id).await?;
+
+    Ok(Json(ForkResponse {
+        role_id: forked_id,
+        parent_id: role_id,
+    }))
+}
+
+#[axum::debug_handler]
+pub async fn allow_handler(
+    Extension(auth): Extension<Auth>,
+    Extension(database): Extension<Arc<Database>>,
+    AuthBearer(token): AuthBearer,
+    Json(AllowParams {
+        role_id,
+        allow_fork,
+    }): Json<AllowParams>,
+) -> HttpResult<Json<AllowResponse>> {
+    let user = auth.verify(&token).await.map_err(HttpError::Unauthorized)?;
+
+    let role = database.get_role(role_id).await?;
+
+    if role.user_id != user.id {
+        return Err(HttpError::Forbidden(anyhow!("无权限修改此角色")));
+    }
+
+    database.set_role_allow_fork(role_id, allow_fork).await?;
+
+    Ok(Json(AllowResponse {
+        role_id,
+        allow_fork,
+    }))
+}
+
+#[derive(Deserialize)]
+pub struct ForkParams {
+    pub role_id: i32,
+}
+
+#[derive(Serialize)]
+pub struct ForkResponse {
+    // 新角色，仅自己可见
+    pub role_id: i32,
+    pub parent_id: i32,
+}
+
+#[derive(Deserialize)]
+pub struct AllowParams {
+    pub role_id: i32,
+    pub allow_fork: bool,
+}
+
+#[derive(Serialize)]
+pub struct AllowResponse {
+    pub role_id: i32,
+    pub allow_fork: bool,
+}
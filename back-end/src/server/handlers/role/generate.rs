@@ -1,7 +1,15 @@
 use crate::{
-    agents::{RoleBuilder, role_builder::RoleBuilt},
-    database::models::roles::{AgeGroup, Gender},
+    agents::{
+        RoleBuilder,
+        role_builder::{Avatar, RoleBuilt},
+    },
+    database::{
+        Database,
+        models::roles::{AgeGroup, Category, ExampleDialogue, Gender, RoleIdentity},
+    },
     error::HttpResult,
+    knowledge::ImageAttribution,
+    server::handlers::role::duplicates::{self, find_duplicates},
 };
 use axum::{Extension, Json};
 use serde::{Deserialize, Serialize};
@@ -11,6 +19,7 @@ pub const PATH: &str = "/api/role/auto-fill";
 
 #[axum::debug_handler]
 pub async fn handler(
+    Extension(database): Extension<Arc<Database>>,
     Extension(role_builder): Extension<Arc<RoleBuilder>>,
     Json(RequestParams {
         name,
@@ -40,16 +49,39 @@ pub async fn handler(
         gender,
         age_group,
         voice_type,
+        quotes,
+        avatar,
+        example_dialogues,
+        greetings,
+        identity,
+        category,
+        tags,
     } = role_builder
         .build(&name, &description, &traits, &gender, &age_group, Some(sid))
         .await?;
 
+    let (image, image_attribution) = match avatar {
+        Some(Avatar { url, attribution }) => (Some(url), Some(attribution)),
+        None => (None, None),
+    };
+
+    let duplicates = find_duplicates(&database, &identity).await?;
+
     Ok(Json(ResponseData {
         description,
         traits,
         gender,
         age_group,
         voice_type,
+        quotes,
+        image,
+        image_attribution,
+        example_dialogues,
+        greetings,
+        identity,
+        duplicates,
+        category,
+        tags,
     }))
 }
 
@@ -70,4 +102,18 @@ pub struct ResponseData {
     pub gender: Gender,
     pub age_group: AgeGroup,
     pub voice_type: String,
+    pub quotes: Vec<String>,
+    // 维基百科条目首图，创建角色时作为头像和署名传回
+    pub image: Option<String>,
+    pub image_attribution: Option<ImageAttribution>,
+    pub example_dialogues: Vec<ExampleDialogue>,
+    pub greetings: Vec<String>,
+    // 规范标题和维基条目，创建角色时传回
+    #[serde(flatten)]
+    pub identity: RoleIdentity,
+    // 同一人物的已有角色
+    pub duplicates: Vec<duplicates::ResponseItem>,
+    // 建议的分类和标签
+    pub category: Option<Category>,
+    pub tags: Vec<String>,
 }
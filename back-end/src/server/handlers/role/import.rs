@@ -0,0 +1,133 @@
+use crate::{
+    agents::RoleBuilder,
+    card,
+    database::{
+        Database,
+        models::roles::{AgeGroup, Gender, Visibility},
+    },
+    error::{HttpError, HttpResult},
+    server::auth::Viewer,
+    storage::StorageClient,
+};
+use anyhow::anyhow;
+use axum::{Extension, Json, body::Bytes, extract::Query};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+pub const PATH: &str = "/api/role/import";
+
+#[axum::debug_handler]
+pub async fn handler(
+    viewer: Viewer,
+    Extension(database): Extension<Arc<Database>>,
+    Extension(storage_client): Extension<Arc<StorageClient>>,
+    Extension(role_builder): Extension<Arc<RoleBuilder>>,
+    Query(RequestParams {
+        gender,
+        age_group,
+        voice_type,
+        visibility,
+    }): Query<RequestParams>,
+    body: Bytes,
+) -> HttpResult<Json<ResponseData>> {
+    // 导入需要登录
+    let Viewer(Some(user)) = viewer else {
+        return Err(HttpError::Unauthorized(anyhow!("请先登录")));
+    };
+
+    let card::ParsedCard { data, avatar } = card::parse(&body)?;
+
+    // 本平台导出的卡片直接还原角色设定
+    let extension = data.role_extension();
+
+    let (description, traits, example_dialogues) = match &extension {
+        Some(extension) => (
+            extension.description.clone(),
+            extension.traits.clone(),
+            extension.example_dialogues.clone(),
+        ),
+        None => (
+            data.role_description(),
+            data.role_traits(),
+            data.role_examples(),
+        ),
+    };
+
+    let gender = gender.or_else(|| extension.as_ref().map(|e| e.gender.clone()));
+    let age_group = age_group.or_else(|| extension.as_ref().map(|e| e.age_group.clone()));
+    let extension_category = extension.as_ref().and_then(|e| e.category);
+    let voice_type = voice_type.or_else(|| extension.map(|e| e.voice_type));
+
+    let (gender, age_group) = match (gender, age_group) {
+        (Some(gender), Some(age_group)) => (gender, age_group),
+        (gender, age_group) => {
+            let (inferred_gender, inferred_age_group) = role_builder
+                .infer_gender_and_age_group(&description, &traits)
+                .await?;
+
+            (
+                gender.unwrap_or(inferred_gender),
+                age_group.unwrap_or(inferred_age_group),
+            )
+        }
+    };
+
+    let voice_type = match voice_type {
+        Some(voice_type) => voice_type,
+        None => role_builder.select_voice_type(&traits, None).await?,
+    };
+
+    // 推断完成后再上传头像，推断失败不会留下无主文件
+    let avatar = match avatar {
+        Some(avatar) => {
+            let name = format!("avatar_{}.png", uuid::Uuid::new_v4());
+            let object_info = storage_client.upload_object(&name, avatar).await?;
+
+            storage_client.get_object_url(&object_info.key)
+        }
+        None => String::new(),
+    };
+
+    let role_id = database
+        .add_role(
+            user.id,
+            &data.name,
+            &description,
+            &traits,
+            &avatar,
+            gender,
+            age_group,
+            &voice_type,
+            &example_dialogues,
+            &data.role_greetings(),
+            visibility,
+        )
+        .await?;
+
+    database.set_role_tags(role_id, &data.role_tags()).await?;
+
+    if let Some(category) = extension_category {
+        database.set_role_category(role_id, Some(category)).await?;
+    }
+
+    Ok(Json(ResponseData {
+        role_id,
+        name: data.name,
+    }))
+}
+
+#[derive(Deserialize)]
+pub struct RequestParams {
+    pub gender: Option<Gender>,      // 可选：不提供则由AI判断
+    pub age_group: Option<AgeGroup>, // 可选：不提供则由AI判断
+    pub voice_type: Option<String>,  // 可选：不提供则由AI选择
+    // 默认公开
+    #[serde(default)]
+    pub visibility: Visibility,
+}
+
+#[derive(Serialize)]
+pub struct ResponseData {
+    pub role_id: i32,
+    pub name: String,
+}
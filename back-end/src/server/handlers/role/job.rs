@@ -0,0 +1,131 @@
+use crate::{
+    agents::{RoleJobs, role_jobs::JobData},
+    database::{
+        Database,
+        models::{
+            role_jobs,
+            roles::{AgeGroup, Gender},
+        },
+    },
+    error::{HttpError, HttpResult},
+    server::auth::Auth,
+};
+use anyhow::anyhow;
+use axum::{Extension, Json, extract::Path};
+use axum_auth::AuthBearer;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+pub const NEW_PATH: &str = "/api/role/job/new";
+pub const STATUS_PATH: &str = "/api/role/job/{job_id}";
+pub const CANCEL_PATH: &str = "/api/role/job/{job_id}/cancel";
+pub const RESUME_PATH: &str = "/api/role/job/{job_id}/resume";
+
+#[axum::debug_handler]
+pub async fn new_handler(
+    Extension(auth): Extension<Auth>,
+    Extension(database): Extension<Arc<Database>>,
+    Extension(role_jobs): Extension<Arc<RoleJobs>>,
+    AuthBearer(token): AuthBearer,
+    Json(RequestParams {
+        name,
+        description,
+        traits,
+        gender,
+        age_group,
+    }): Json<RequestParams>,
+) -> HttpResult<Json<ResponseData>> {
+    let user = auth.verify(&token).await.map_err(HttpError::Unauthorized)?;
+
+    let job_id = database
+        .add_role_job(
+            user.id,
+            &name,
+            &description.unwrap_or_default(),
+            &traits.unwrap_or_default(),
+            gender,
+            age_group,
+        )
+        .await?;
+
+    role_jobs.start(job_id).await?;
+
+    Ok(Json(ResponseData { job_id }))
+}
+
+#[axum::debug_handler]
+pub async fn status_handler(
+    Extension(auth): Extension<Auth>,
+    Extension(database): Extension<Arc<Database>>,
+    AuthBearer(token): AuthBearer,
+    Path(job_id): Path<i32>,
+) -> HttpResult<Json<JobData>> {
+    let job = get_owned_job(&auth, &database, &token, job_id).await?;
+
+    Ok(Json(JobData::from(&job)))
+}
+
+#[axum::debug_handler]
+pub async fn cancel_handler(
+    Extension(auth): Extension<Auth>,
+    Extension(database): Extension<Arc<Database>>,
+    Extension(role_jobs): Extension<Arc<RoleJobs>>,
+    AuthBearer(token): AuthBearer,
+    Path(job_id): Path<i32>,
+) -> HttpResult<Json<JobData>> {
+    get_owned_job(&auth, &database, &token, job_id).await?;
+
+    role_jobs.cancel(job_id).await?;
+
+    let job = database.get_role_job(job_id).await?;
+
+    Ok(Json(JobData::from(&job)))
+}
+
+#[axum::debug_handler]
+pub async fn resume_handler(
+    Extension(auth): Extension<Auth>,
+    Extension(database): Extension<Arc<Database>>,
+    Extension(role_jobs): Extension<Arc<RoleJobs>>,
+    AuthBearer(token): AuthBearer,
+    Path(job_id): Path<i32>,
+) -> HttpResult<Json<JobData>> {
+    get_owned_job(&auth, &database, &token, job_id).await?;
+
+    role_jobs.start(job_id).await?;
+
+    let job = database.get_role_job(job_id).await?;
+
+    Ok(Json(JobData::from(&job)))
+}
+
+async fn get_owned_job(
+    auth: &Auth,
+    database: &Database,
+    token: &str,
+    job_id: i32,
+) -> HttpResult<role_jobs::Model> {
+    let user = auth.verify(token).await.map_err(HttpError::Unauthorized)?;
+
+    let job = database.get_role_job(job_id).await?;
+
+    if job.user_id != user.id {
+        return Err(HttpError::Forbidden(anyhow!("无权限访问此任务")));
+    }
+
+    Ok(job)
+}
+
+#[derive(Deserialize)]
+pub struct RequestParams {
+    pub name: String,
+    pub description: Option<String>,
+    pub traits: Option<String>,
+    pub gender: Option<Gender>,
+    pub age_group: Option<AgeGroup>,
+}
+
+#[derive(Serialize)]
+pub struct ResponseData {
+    pub job_id: i32,
+}
@@ -1,9 +1,11 @@
 use crate::{
     database::{
         Database,
-        models::roles::{AgeGroup, Gender},
+        models::roles::{AgeGroup, Category, Gender},
     },
     error::HttpResult,
+    search::{Facets, RoleFilter},
+    stats::RoleStats,
 };
 use axum::{Extension, Json, extract::Query};
 use serde::{Deserialize, Serialize};
@@ -16,10 +18,26 @@ pub async fn handler(
     Extension(database): Extension<Arc<Database>>,
     Query(params): Query<RequestParams>,
 ) -> HttpResult<Json<PaginatedResponse<ResponseItem>>> {
+    let filter = RoleFilter {
+        tag: params.tag,
+        category: params.category,
+        gender: params.gender,
+        age_group: params.age_group,
+    };
+
     let roles = database
-        .list_roles_paginated(params.offset.unwrap_or(0), params.limit.unwrap_or(15))
+        .list_roles_paginated(
+            params.offset.unwrap_or(0),
+            params.limit.unwrap_or(15),
+            &filter,
+        )
         .await?;
 
+    let ids: Vec<i32> = roles.items.iter().map(|role| role.id).collect();
+    let mut tags = database.get_roles_tags(&ids).await?;
+    let mut stats = database.get_roles_stats(&ids).await?;
+    let facets = database.role_facets(&filter).await?;
+
     let mut response_data = Vec::new();
 
     for role in &roles.items {
@@ -32,6 +50,9 @@ pub async fn handler(
             image_url: role.image.clone(),
             gender: role.gender.clone(),
             age_group: role.age_group.clone(),
+            category: role.category,
+            tags: tags.remove(&role.id).unwrap_or_default(),
+            stats: stats.remove(&role.id).unwrap_or_default(),
         });
     }
 
@@ -39,6 +60,7 @@ pub async fn handler(
         items: response_data,
         total: roles.total,
         has_more: roles.has_more,
+        facets,
     }))
 }
 
@@ -46,6 +68,11 @@ pub async fn handler(
 pub struct RequestParams {
     pub offset: Option<i64>,
     pub limit: Option<i64>,
+    // 以下为筛选条件，可省略
+    pub tag: Option<String>,
+    pub category: Option<Category>,
+    pub gender: Option<Gender>,
+    pub age_group: Option<AgeGroup>,
 }
 
 #[derive(Serialize)]
@@ -53,6 +80,8 @@ pub struct PaginatedResponse<T> {
     pub items: Vec<T>,
     pub total: i64,
     pub has_more: bool,
+    // 符合筛选条件的全部角色的分面统计
+    pub facets: Facets,
 }
 
 #[derive(Serialize)]
@@ -65,4 +94,7 @@ pub struct ResponseItem {
     pub image_url: String,
     pub gender: Gender,
     pub age_group: AgeGroup,
+    pub category: Option<Category>,
+    pub tags: Vec<String>,
+    pub stats: RoleStats,
 }
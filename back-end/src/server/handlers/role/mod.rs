@@ -1,5 +1,23 @@
+pub mod batch;
+pub mod categories;
 pub mod create;
 pub mod details;
+pub mod diff;
+pub mod duplicates;
+pub mod export;
+pub mod feed;
+pub mod fork;
 pub mod generate;
+pub mod import;
+pub mod job;
 pub mod list;
+pub mod original;
+pub mod reaction;
+pub mod review;
+pub mod rollback;
 pub mod search;
+pub mod semantic;
+pub mod trending;
+pub mod update;
+pub mod versions;
+pub mod visibility;
@@ -0,0 +1,87 @@
+use crate::{
+    agents::{
+        RoleBuilder,
+        role_builder::{OriginalCharacter, RoleBuilt},
+    },
+    database::models::roles::{AgeGroup, Category, ExampleDialogue, Gender},
+    error::HttpResult,
+};
+use axum::{Extension, Json};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+pub const PATH: &str = "/api/role/auto-fill/original";
+
+#[axum::debug_handler]
+pub async fn handler(
+    Extension(role_builder): Extension<Arc<RoleBuilder>>,
+    Json(RequestParams {
+        name,
+        gender,
+        age_group,
+        original,
+        sid,
+    }): Json<RequestParams>,
+) -> HttpResult<Json<ResponseData>> {
+    let gender = match gender {
+        Some(gender) => gender.to_string(),
+        None => String::new(),
+    };
+
+    let age_group = match age_group {
+        Some(age_group) => age_group.to_string(),
+        None => String::new(),
+    };
+
+    let RoleBuilt {
+        description,
+        traits,
+        gender,
+        age_group,
+        voice_type,
+        example_dialogues,
+        greetings,
+        category,
+        tags,
+        ..
+    } = role_builder
+        .build_original(&name, &original, &gender, &age_group, Some(sid))
+        .await?;
+
+    Ok(Json(ResponseData {
+        description,
+        traits,
+        gender,
+        age_group,
+        voice_type,
+        example_dialogues,
+        greetings,
+        category,
+        tags,
+    }))
+}
+
+#[derive(Deserialize)]
+pub struct RequestParams {
+    pub name: String,
+    pub gender: Option<Gender>,
+    pub age_group: Option<AgeGroup>,
+    // 背景故事、世界观、人物关系、目标、秘密、说话习惯
+    #[serde(flatten)]
+    pub original: OriginalCharacter,
+    pub sid: String,
+}
+
+#[derive(Serialize)]
+pub struct ResponseData {
+    pub description: String,
+    pub traits: String,
+    pub gender: Gender,
+    pub age_group: AgeGroup,
+    pub voice_type: String,
+    pub example_dialogues: Vec<ExampleDialogue>,
+    pub greetings: Vec<String>,
+    // 建议的分类和标签
+    pub category: Option<Category>,
+    pub tags: Vec<String>,
+}
@@ -0,0 +1,59 @@
+use crate::{
+    database::{Database, models::role_reactions::ReactionKind},
+    error::{HttpError, HttpResult},
+    server::auth::Auth,
+    stats::RoleStats,
+};
+use anyhow::anyhow;
+use axum::{Extension, Json};
+use axum_auth::AuthBearer;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+pub const PATH: &str = "/api/role/reaction";
+
+#[axum::debug_handler]
+pub async fn handler(
+    Extension(auth): Extension<Auth>,
+    Extension(database): Extension<Arc<Database>>,
+    AuthBearer(token): AuthBearer,
+    Json(RequestParams {
+        role_id,
+        kind,
+        active,
+    }): Json<RequestParams>,
+) -> HttpResult<Json<ResponseData>> {
+    let user = auth.verify(&token).await.map_err(HttpError::Unauthorized)?;
+
+    let role = database.get_role(role_id).await?;
+
+    if !role.is_visible_to(Some(user.id)) {
+        return Err(HttpError::Forbidden(anyhow!("无权限查看此角色")));
+    }
+
+    database
+        .set_role_reaction(user.id, role_id, kind, active)
+        .await?;
+
+    Ok(Json(ResponseData {
+        role_id,
+        stats: database.get_role_stats(role_id).await?,
+        reactions: database.get_user_reactions(user.id, role_id).await?,
+    }))
+}
+
+#[derive(Deserialize)]
+pub struct RequestParams {
+    pub role_id: i32,
+    pub kind: ReactionKind,
+    // true为收藏/点赞，false为取消
+    pub active: bool,
+}
+
+#[derive(Serialize)]
+pub struct ResponseData {
+    pub role_id: i32,
+    pub stats: RoleStats,
+    // 当前用户对该角色的收藏和点赞
+    pub reactions: Vec<ReactionKind>,
+}
@@ -0,0 +1,241 @@
+use crate::{
+    database::{Database, models::role_reviews},
+    error::{HttpError, HttpResult},
+    server::auth::{Auth, Viewer},
+    stats::Rating,
+};
+use anyhow::anyhow;
+use axum::{
+    Extension, Json,
+    extract::{Path, Query},
+};
+use axum_auth::AuthBearer;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+pub const SAVE_PATH: &str = "/api/role/review";
+pub const LIST_PATH: &str = "/api/role/{role_id}/reviews";
+pub const REPLY_PATH: &str = "/api/role/review/reply";
+pub const REPORT_PATH: &str = "/api/role/review/report";
+
+// 与角色至少对话这么多轮（角色回复数）才能评价
+const MIN_REPLIES: i64 = 5;
+// 评价、回复和举报理由的最大长度（字符）
+const MAX_CONTENT_CHARS: usize = 500;
+const MAX_REASON_CHARS: usize = 200;
+
+#[axum::debug_handler]
+pub async fn save_handler(
+    Extension(auth): Extension<Auth>,
+    Extension(database): Extension<Arc<Database>>,
+    AuthBearer(token): AuthBearer,
+    Json(SaveParams {
+        role_id,
+        rating,
+        content,
+    }): Json<SaveParams>,
+) -> HttpResult<Json<ReviewData>> {
+    let user = auth.verify(&token).await.map_err(HttpError::Unauthorized)?;
+
+    if !(1..=5).contains(&rating) {
+        return Err(anyhow!("评分须为1~5").into());
+    }
+
+    let content = content.trim();
+
+    if content.chars().count() > MAX_CONTENT_CHARS {
+        return Err(anyhow!("评价不能超过{}个字", MAX_CONTENT_CHARS).into());
+    }
+
+    let role = database.get_role(role_id).await?;
+
+    if !role.is_visible_to(Some(user.id)) {
+        return Err(HttpError::Forbidden(anyhow!("无权限查看此角色")));
+    }
+
+    if role.user_id == user.id {
+        return Err(HttpError::Forbidden(anyhow!("不能评价自己的角色")));
+    }
+
+    if database.count_role_replies(user.id, role_id).await? < MIN_REPLIES {
+        return Err(HttpError::Forbidden(anyhow!(
+            "与角色至少对话{}轮才能评价",
+            MIN_REPLIES
+        )));
+    }
+
+    let review = database
+        .save_role_review(user.id, role_id, rating, content)
+        .await?;
+
+    Ok(Json(ReviewData::new(review, user.username, user.image)))
+}
+
+#[axum::debug_handler]
+pub async fn list_handler(
+    viewer: Viewer,
+    Extension(database): Extension<Arc<Database>>,
+    Path(role_id): Path<i32>,
+    Query(ListParams { offset, limit }): Query<ListParams>,
+) -> HttpResult<Json<ListResponse>> {
+    let role = database.get_role(role_id).await?;
+
+    if !role.is_visible_to(viewer.id()) {
+        return Err(HttpError::Forbidden(anyhow!("无权限查看此角色")));
+    }
+
+    let reviews = database
+        .list_role_reviews_paginated(role_id, offset.unwrap_or(0), limit.unwrap_or(15))
+        .await?;
+    let rating = database.get_role_rating(role_id).await?;
+
+    let mut items = Vec::new();
+
+    for review in reviews.items {
+        let reviewer = database.get_user_by_id(review.user_id).await?;
+        items.push(ReviewData::new(review, reviewer.username, reviewer.image));
+    }
+
+    Ok(Json(ListResponse {
+        average: rating.average(),
+        rating,
+        items,
+        total: reviews.total,
+        has_more: reviews.has_more,
+    }))
+}
+
+#[axum::debug_handler]
+pub async fn reply_handler(
+    Extension(auth): Extension<Auth>,
+    Extension(database): Extension<Arc<Database>>,
+    AuthBearer(token): AuthBearer,
+    Json(ReplyParams { review_id, reply }): Json<ReplyParams>,
+) -> HttpResult<()> {
+    let user = auth.verify(&token).await.map_err(HttpError::Unauthorized)?;
+
+    let review = database.get_role_review(review_id).await?;
+    let role = database.get_role(review.role_id).await?;
+
+    if role.user_id != user.id {
+        return Err(HttpError::Forbidden(anyhow!("只有角色作者可以回复")));
+    }
+
+    let reply = reply.trim();
+
+    if reply.chars().count() > MAX_CONTENT_CHARS {
+        return Err(anyhow!("回复不能超过{}个字", MAX_CONTENT_CHARS).into());
+    }
+
+    database
+        .reply_role_review(review_id, Some(reply).filter(|reply| !reply.is_empty()))
+        .await?;
+
+    Ok(())
+}
+
+#[axum::debug_handler]
+pub async fn report_handler(
+    Extension(auth): Extension<Auth>,
+    Extension(database): Extension<Arc<Database>>,
+    AuthBearer(token): AuthBearer,
+    Json(ReportParams { review_id, reason }): Json<ReportParams>,
+) -> HttpResult<Json<ReportResponse>> {
+    let user = auth.verify(&token).await.map_err(HttpError::Unauthorized)?;
+
+    let reason = reason.trim();
+
+    if reason.chars().count() > MAX_REASON_CHARS {
+        return Err(anyhow!("举报理由不能超过{}个字", MAX_REASON_CHARS).into());
+    }
+
+    let review = database.get_role_review(review_id).await?;
+
+    if review.user_id == user.id {
+        return Err(anyhow!("不能举报自己的评价").into());
+    }
+
+    let hidden = database
+        .report_role_review(review_id, user.id, reason)
+        .await?;
+
+    Ok(Json(ReportResponse { review_id, hidden }))
+}
+
+#[derive(Deserialize)]
+pub struct SaveParams {
+    pub role_id: i32,
+    // 1~5
+    pub rating: i32,
+    #[serde(default)]
+    pub content: String,
+}
+
+#[derive(Deserialize)]
+pub struct ListParams {
+    pub offset: Option<i64>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Deserialize)]
+pub struct ReplyParams {
+    pub review_id: i32,
+    // 为空时删除回复
+    pub reply: String,
+}
+
+#[derive(Deserialize)]
+pub struct ReportParams {
+    pub review_id: i32,
+    pub reason: String,
+}
+
+#[derive(Serialize)]
+pub struct ListResponse {
+    // 评分分布和平均分，不含被隐藏的评价
+    pub rating: Rating,
+    pub average: Option<f32>,
+    pub items: Vec<ReviewData>,
+    pub total: i64,
+    pub has_more: bool,
+}
+
+#[derive(Serialize)]
+pub struct ReportResponse {
+    pub review_id: i32,
+    // 举报人数达到阈值后评价被隐藏
+    pub hidden: bool,
+}
+
+#[derive(Serialize)]
+pub struct ReviewData {
+    pub review_id: i32,
+    pub role_id: i32,
+    pub user_id: i32,
+    pub username: String,
+    pub avatar: String,
+    pub rating: i32,
+    pub content: String,
+    pub reply: Option<String>,
+    pub replied_at: Option<i64>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+impl ReviewData {
+    fn new(review: role_reviews::Model, username: String, avatar: String) -> Self {
+        Self {
+            review_id: review.id,
+            role_id: review.role_id,
+            user_id: review.user_id,
+            username,
+            avatar,
+            rating: review.rating,
+            content: review.content,
+            reply: review.reply,
+            replied_at: review.replied_at,
+            created_at: review.created_at,
+            updated_at: review.updated_at,
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use crate::{
+    database::Database,
+    error::{HttpError, HttpResult},
+    server::auth::Auth,
+};
+use anyhow::anyhow;
+use axum::{Extension, Json};
+use axum_auth::AuthBearer;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+pub const PATH: &str = "/api/role/rollback";
+
+#[axum::debug_handler]
+pub async fn handler(
+    Extension(auth): Extension<Auth>,
+    Extension(database): Extension<Arc<Database>>,
+    AuthBearer(token): AuthBearer,
+    Json(RequestParams { role_id, version }): Json<RequestParams>,
+) -> HttpResult<Json<ResponseData>> {
+    let user = auth.verify(&token).await.map_err(HttpError::Unauthorized)?;
+
+    let role = database.get_role(role_id).await?;
+
+    if role.user_id != user.id {
+        return Err(HttpError::Forbidden(anyhow!("无权限修改此角色")));
+    }
+
+    // 回滚以旧版本内容生成新版本，不删除历史
+    let target = database.get_role_version(role_id, version).await?;
+    let example_dialogues = target.example_dialogues();
+    let greetings = target.greetings();
+
+    let version = database
+        .update_role(
+            role_id,
+            &target.name,
+            &target.description,
+            &target.traits,
+            &target.image,
+            target.gender,
+            target.age_group,
+            &target.voice_type,
+            &example_dialogues,
+            &greetings,
+        )
+        .await?;
+
+    Ok(Json(ResponseData { role_id, version }))
+}
+
+#[derive(Deserialize)]
+pub struct RequestParams {
+    pub role_id: i32,
+    pub version: i32, // 回滚到的版本
+}
+
+#[derive(Serialize)]
+pub struct ResponseData {
+    pub role_id: i32,
+    pub version: i32, // 回滚后生成的新版本
+}
@@ -1,13 +1,14 @@
 use crate::{
     database::{
         Database,
-        models::roles::{AgeGroup, Gender},
+        models::roles::{self, AgeGroup, Category, Gender},
     },
     error::HttpResult,
+    search::{Facets, RoleFilter},
 };
 use axum::{Extension, Json, extract::Query};
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
 pub const PATH: &str = "/api/role/search";
 
@@ -15,19 +16,63 @@ pub const PATH: &str = "/api/role/search";
 pub async fn handler(
     Extension(database): Extension<Arc<Database>>,
     Query(params): Query<RequestParams>,
-) -> HttpResult<Json<Vec<ResponseItem>>> {
+) -> HttpResult<Json<ResponseData>> {
     let keyword = params.q.trim();
 
     if keyword.is_empty() {
-        return Ok(Json(vec![]));
+        return Ok(Json(ResponseData::default()));
     }
 
-    let roles = database.search_roles(keyword).await?;
+    let filter = RoleFilter {
+        tag: params.tag,
+        category: params.category,
+        gender: params.gender,
+        age_group: params.age_group,
+    };
 
-    let mut response_data = Vec::new();
+    // 按相关度排列的全部结果，只含分面和分组需要的列
+    let hits = database.search_roles(keyword, &filter).await?;
+    let facets = database.count_role_facets(&hits).await?;
 
-    for role in roles {
-        response_data.push(ResponseItem {
+    // 同一人物的角色合并到第一个出现的角色下，分组后再分页
+    let mut groups: Vec<Vec<i32>> = Vec::new();
+    let mut group_index: HashMap<String, usize> = HashMap::new();
+
+    for hit in &hits {
+        match hit.identity().key().filter(|_| params.group) {
+            Some(key) => match group_index.get(&key) {
+                Some(index) => groups[*index].push(hit.id),
+                None => {
+                    group_index.insert(key, groups.len());
+                    groups.push(vec![hit.id]);
+                }
+            },
+            None => groups.push(vec![hit.id]),
+        }
+    }
+
+    let total = groups.len() as i64;
+    let offset = params.offset.unwrap_or(0).max(0);
+    let limit = params.limit.unwrap_or(15).max(0);
+    let groups: Vec<Vec<i32>> = groups
+        .into_iter()
+        .skip(offset as usize)
+        .take(limit as usize)
+        .collect();
+    let has_more = offset + (groups.len() as i64) < total;
+
+    // 只读取当前页的完整角色和标签
+    let ids: Vec<i32> = groups.iter().flatten().copied().collect();
+    let mut roles: HashMap<i32, roles::Model> = database
+        .find_roles_ranked(&ids, &filter)
+        .await?
+        .into_iter()
+        .map(|role| (role.id, role))
+        .collect();
+    let mut tags = database.get_roles_tags(&ids).await?;
+
+    let mut to_item = |role_id: i32| {
+        roles.remove(&role_id).map(|role| ResponseItem {
             role_id: role.id,
             name: role.name,
             description: role.description,
@@ -35,15 +80,56 @@ pub async fn handler(
             image_url: role.image,
             gender: role.gender,
             age_group: role.age_group,
-        });
+            category: role.category,
+            tags: tags.remove(&role.id).unwrap_or_default(),
+            canonical_title: role.canonical_title,
+            variants: Vec::new(),
+        })
+    };
+
+    let mut items: Vec<ResponseItem> = Vec::new();
+
+    for role_ids in &groups {
+        let Some(mut item) = to_item(role_ids[0]) else {
+            continue;
+        };
+        item.variants = role_ids[1..]
+            .iter()
+            .filter_map(|role_id| to_item(*role_id))
+            .collect();
+        items.push(item);
     }
 
-    Ok(Json(response_data))
+    Ok(Json(ResponseData {
+        has_more,
+        items,
+        total,
+        facets,
+    }))
 }
 
 #[derive(Deserialize)]
 pub struct RequestParams {
     pub q: String, // 搜索关键词
+    // 是否按人物身份分组
+    #[serde(default)]
+    pub group: bool,
+    pub offset: Option<i64>,
+    pub limit: Option<i64>,
+    // 以下为筛选条件，可省略
+    pub tag: Option<String>,
+    pub category: Option<Category>,
+    pub gender: Option<Gender>,
+    pub age_group: Option<AgeGroup>,
+}
+
+#[derive(Default, Serialize)]
+pub struct ResponseData {
+    pub items: Vec<ResponseItem>,
+    pub total: i64,
+    pub has_more: bool,
+    // 全部搜索结果的分面统计
+    pub facets: Facets,
 }
 
 #[derive(Serialize)]
@@ -55,4 +141,10 @@ pub struct ResponseItem {
     pub image_url: String,
     pub gender: Gender,
     pub age_group: AgeGroup,
+    pub category: Option<Category>,
+    pub tags: Vec<String>,
+    pub canonical_title: Option<String>,
+    // 分组时同一人物的其他角色
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub variants: Vec<ResponseItem>,
 }
@@ -0,0 +1,140 @@
+use crate::{
+    agents::RoleEmbeddings,
+    database::{
+        Database,
+        models::roles::{self, AgeGroup, Category, Gender},
+    },
+    error::{HttpError, HttpResult},
+    search::RoleFilter,
+    server::auth::Viewer,
+};
+use anyhow::anyhow;
+use axum::{
+    Extension, Json,
+    extract::{Path, Query},
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, sync::Arc};
+
+pub const SEARCH_PATH: &str = "/api/role/search/semantic";
+pub const SIMILAR_PATH: &str = "/api/role/{role_id}/similar";
+
+const DEFAULT_LIMIT: usize = 10;
+const MAX_LIMIT: usize = 50;
+// 不公开和私有角色会被过滤掉，多取一些候选
+const CANDIDATE_FACTOR: usize = 4;
+
+#[axum::debug_handler]
+pub async fn search_handler(
+    Extension(database): Extension<Arc<Database>>,
+    Extension(role_embeddings): Extension<Arc<RoleEmbeddings>>,
+    Query(SearchParams { q, limit }): Query<SearchParams>,
+) -> HttpResult<Json<Vec<ResponseItem>>> {
+    if !role_embeddings.is_enabled() {
+        return Err(anyhow!("未配置向量模型，语义搜索不可用").into());
+    }
+
+    let query = q.trim();
+
+    if query.is_empty() {
+        return Ok(Json(Vec::new()));
+    }
+
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT);
+    let nearest = role_embeddings
+        .search(query, limit * CANDIDATE_FACTOR)
+        .await?;
+
+    Ok(Json(public_roles(&database, nearest, limit).await?))
+}
+
+#[axum::debug_handler]
+pub async fn similar_handler(
+    viewer: Viewer,
+    Extension(database): Extension<Arc<Database>>,
+    Extension(role_embeddings): Extension<Arc<RoleEmbeddings>>,
+    Path(role_id): Path<i32>,
+    Query(SimilarParams { limit }): Query<SimilarParams>,
+) -> HttpResult<Json<Vec<ResponseItem>>> {
+    if !role_embeddings.is_enabled() {
+        return Err(anyhow!("未配置向量模型，语义搜索不可用").into());
+    }
+
+    let role = database.get_role(role_id).await?;
+
+    if !role.is_visible_to(viewer.id()) {
+        return Err(HttpError::Forbidden(anyhow!("无权限查看此角色")));
+    }
+
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT);
+    let nearest = role_embeddings
+        .similar(&role, limit * CANDIDATE_FACTOR)
+        .await?;
+
+    Ok(Json(public_roles(&database, nearest, limit).await?))
+}
+
+// 只返回公开角色，保持相似度顺序
+async fn public_roles(
+    database: &Database,
+    nearest: Vec<(i32, f32)>,
+    limit: usize,
+) -> anyhow::Result<Vec<ResponseItem>> {
+    let role_ids: Vec<i32> = nearest.iter().map(|(role_id, _)| *role_id).collect();
+    let scores: HashMap<i32, f32> = nearest.into_iter().collect();
+
+    let roles = database
+        .find_roles_ranked(&role_ids, &RoleFilter::default())
+        .await?;
+
+    Ok(roles
+        .into_iter()
+        .take(limit)
+        .map(|role| {
+            let score = scores[&role.id];
+            ResponseItem::new(role, score)
+        })
+        .collect())
+}
+
+#[derive(Deserialize)]
+pub struct SearchParams {
+    // 自然语言描述，如"能幽默地教我物理的人"
+    pub q: String,
+    pub limit: Option<usize>,
+}
+
+#[derive(Deserialize)]
+pub struct SimilarParams {
+    pub limit: Option<usize>,
+}
+
+#[derive(Serialize)]
+pub struct ResponseItem {
+    pub role_id: i32,
+    pub user_id: i32,
+    pub name: String,
+    pub description: String,
+    pub image_url: String,
+    pub gender: Gender,
+    pub age_group: AgeGroup,
+    pub category: Option<Category>,
+    // 余弦相似度
+    pub score: f32,
+}
+
+impl ResponseItem {
+    fn new(role: roles::Model, score: f32) -> Self {
+        Self {
+            role_id: role.id,
+            user_id: role.user_id,
+            name: role.name,
+            description: role.description,
+            image_url: role.image,
+            gender: role.gender,
+            age_group: role.age_group,
+            category: role.category,
+            score,
+        }
+    }
+}
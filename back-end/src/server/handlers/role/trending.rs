@@ -0,0 +1,87 @@
+use crate::{
+    database::{
+        Database,
+        models::roles::{AgeGroup, Category, Gender},
+    },
+    error::HttpResult,
+    search::RoleFilter,
+    stats::RoleStats,
+};
+use axum::{Extension, Json, extract::Query};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, sync::Arc};
+
+pub const PATH: &str = "/api/role/trending";
+
+#[axum::debug_handler]
+pub async fn handler(
+    Extension(database): Extension<Arc<Database>>,
+    Query(RequestParams { offset, limit }): Query<RequestParams>,
+) -> HttpResult<Json<ResponseData>> {
+    let offset = offset.unwrap_or(0).max(0) as usize;
+    let limit = limit.unwrap_or(15).max(0) as usize;
+
+    let trending = database.trending_roles().await?;
+    let role_ids: Vec<i32> = trending.iter().map(|(role_id, _)| *role_id).collect();
+    let scores: HashMap<i32, f32> = trending.into_iter().collect();
+
+    // 只保留公开角色
+    let roles = database
+        .find_roles_ranked(&role_ids, &RoleFilter::default())
+        .await?;
+    let total = roles.len();
+    let roles: Vec<_> = roles.into_iter().skip(offset).take(limit).collect();
+
+    let ids: Vec<i32> = roles.iter().map(|role| role.id).collect();
+    let mut stats = database.get_roles_stats(&ids).await?;
+
+    let items = roles
+        .into_iter()
+        .map(|role| ResponseItem {
+            role_id: role.id,
+            user_id: role.user_id,
+            score: scores[&role.id],
+            stats: stats.remove(&role.id).unwrap_or_default(),
+            name: role.name,
+            description: role.description,
+            image_url: role.image,
+            gender: role.gender,
+            age_group: role.age_group,
+            category: role.category,
+        })
+        .collect();
+
+    Ok(Json(ResponseData {
+        items,
+        total: total as i64,
+        has_more: offset + limit < total,
+    }))
+}
+
+#[derive(Deserialize)]
+pub struct RequestParams {
+    pub offset: Option<i64>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Serialize)]
+pub struct ResponseData {
+    pub items: Vec<ResponseItem>,
+    pub total: i64,
+    pub has_more: bool,
+}
+
+#[derive(Serialize)]
+pub struct ResponseItem {
+    pub role_id: i32,
+    pub user_id: i32,
+    pub name: String,
+    pub description: String,
+    pub image_url: String,
+    pub gender: Gender,
+    pub age_group: AgeGroup,
+    pub category: Option<Category>,
+    pub stats: RoleStats,
+    // 随时间衰减的热度
+    pub score: f32,
+}
@@ -0,0 +1,110 @@
+use crate::{
+    database::{
+        Database,
+        models::roles::{
+            AgeGroup, Category, ExampleDialogue, Gender, MAX_EXAMPLE_DIALOGUES, MAX_GREETINGS,
+            validate_tags,
+        },
+    },
+    error::{HttpError, HttpResult},
+    server::auth::Auth,
+};
+use anyhow::anyhow;
+use axum::{Extension, Json};
+use axum_auth::AuthBearer;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+pub const PATH: &str = "/api/role/update";
+
+#[axum::debug_handler]
+pub async fn handler(
+    Extension(auth): Extension<Auth>,
+    Extension(database): Extension<Arc<Database>>,
+    AuthBearer(token): AuthBearer,
+    Json(RequestParams {
+        role_id,
+        name,
+        description,
+        traits,
+        avatar,
+        gender,
+        age_group,
+        voice_type,
+        example_dialogues,
+        greetings,
+        category,
+        tags,
+    }): Json<RequestParams>,
+) -> HttpResult<Json<ResponseData>> {
+    let user = auth.verify(&token).await.map_err(HttpError::Unauthorized)?;
+
+    let role = database.get_role(role_id).await?;
+
+    if role.user_id != user.id {
+        return Err(HttpError::Forbidden(anyhow!("无权限修改此角色")));
+    }
+
+    let example_dialogues = example_dialogues.unwrap_or_else(|| role.example_dialogues());
+
+    if example_dialogues.len() > MAX_EXAMPLE_DIALOGUES {
+        return Err(anyhow!("最多{}组示例对话", MAX_EXAMPLE_DIALOGUES).into());
+    }
+
+    let greetings = greetings.unwrap_or_else(|| role.greetings());
+
+    if greetings.len() > MAX_GREETINGS {
+        return Err(anyhow!("最多{}条开场白", MAX_GREETINGS).into());
+    }
+
+    let tags = tags.map(|tags| validate_tags(&tags)).transpose()?;
+
+    // 未提供的字段保持不变
+    let version = database
+        .update_role(
+            role_id,
+            &name.unwrap_or(role.name),
+            &description.unwrap_or(role.description),
+            &traits.unwrap_or(role.traits),
+            &avatar.unwrap_or(role.image),
+            gender.unwrap_or(role.gender),
+            age_group.unwrap_or(role.age_group),
+            &voice_type.unwrap_or(role.voice_type),
+            &example_dialogues,
+            &greetings,
+        )
+        .await?;
+
+    // 分类和标签不属于角色设定，不产生新版本
+    if let Some(tags) = tags {
+        database.set_role_tags(role_id, &tags).await?;
+    }
+
+    if let Some(category) = category {
+        database.set_role_category(role_id, Some(category)).await?;
+    }
+
+    Ok(Json(ResponseData { role_id, version }))
+}
+
+#[derive(Deserialize)]
+pub struct RequestParams {
+    pub role_id: i32,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub traits: Option<String>,
+    pub avatar: Option<String>,
+    pub gender: Option<Gender>,
+    pub age_group: Option<AgeGroup>,
+    pub voice_type: Option<String>,
+    pub example_dialogues: Option<Vec<ExampleDialogue>>,
+    pub greetings: Option<Vec<String>>,
+    pub category: Option<Category>,
+    pub tags: Option<Vec<String>>,
+}
+
+#[derive(Serialize)]
+pub struct ResponseData {
+    pub role_id: i32,
+    pub version: i32,
+}
@@ -0,0 +1,79 @@
+use crate::{
+    database::{
+        Database,
+        models::roles::{AgeGroup, ExampleDialogue, Gender},
+    },
+    error::{HttpError, HttpResult},
+    server::auth::Viewer,
+};
+use anyhow::anyhow;
+use axum::{Extension, Json, extract::Query};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+pub const PATH: &str = "/api/role/versions";
+
+#[axum::debug_handler]
+pub async fn handler(
+    viewer: Viewer,
+    Extension(database): Extension<Arc<Database>>,
+    Query(RequestParams { role_id }): Query<RequestParams>,
+) -> HttpResult<Json<ResponseData>> {
+    let role = database.get_role(role_id).await?;
+
+    if !role.is_visible_to(viewer.id()) {
+        return Err(HttpError::Forbidden(anyhow!("无权限查看此角色")));
+    }
+
+    let versions = database
+        .list_role_versions(role_id)
+        .await?
+        .into_iter()
+        .map(|version| VersionItem {
+            example_dialogues: version.example_dialogues(),
+            greetings: version.greetings(),
+            version: version.version,
+            name: version.name,
+            description: version.description,
+            traits: version.traits,
+            image_url: version.image,
+            gender: version.gender,
+            age_group: version.age_group,
+            voice_type: version.voice_type,
+            created_at: version.created_at,
+        })
+        .collect();
+
+    Ok(Json(ResponseData {
+        role_id,
+        current_version: role.version,
+        versions,
+    }))
+}
+
+#[derive(Deserialize)]
+pub struct RequestParams {
+    pub role_id: i32,
+}
+
+#[derive(Serialize)]
+pub struct ResponseData {
+    pub role_id: i32,
+    pub current_version: i32,
+    pub versions: Vec<VersionItem>,
+}
+
+#[derive(Serialize)]
+pub struct VersionItem {
+    pub version: i32,
+    pub name: String,
+    pub description: String,
+    pub traits: String,
+    pub image_url: String,
+    pub gender: Gender,
+    pub age_group: AgeGroup,
+    pub voice_type: String,
+    pub example_dialogues: Vec<ExampleDialogue>,
+    pub greetings: Vec<String>,
+    pub created_at: i64, // 毫秒，迁移前的旧版本为0
+}
@@ -0,0 +1,52 @@
+use crate::{
+    database::{Database, models::roles::Visibility},
+    error::{HttpError, HttpResult},
+    server::auth::Auth,
+};
+use anyhow::anyhow;
+use axum::{Extension, Json};
+use axum_auth::AuthBearer;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+pub const PATH: &str = "/api/role/visibility";
+
+#[axum::debug_handler]
+pub async fn handler(
+    Extension(auth): Extension<Auth>,
+    Extension(database): Extension<Arc<Database>>,
+    AuthBearer(token): AuthBearer,
+    Json(RequestParams {
+        role_id,
+        visibility,
+    }): Json<RequestParams>,
+) -> HttpResult<Json<ResponseData>> {
+    let user = auth.verify(&token).await.map_err(HttpError::Unauthorized)?;
+
+    let role = database.get_role(role_id).await?;
+
+    if role.user_id != user.id {
+        return Err(HttpError::Forbidden(anyhow!("无权限修改此角色")));
+    }
+
+    database
+        .set_role_visibility(role_id, visibility.clone())
+        .await?;
+
+    Ok(Json(ResponseData {
+        role_id,
+        visibility,
+    }))
+}
+
+#[derive(Deserialize)]
+pub struct RequestParams {
+    pub role_id: i32,
+    pub visibility: Visibility,
+}
+
+#[derive(Serialize)]
+pub struct ResponseData {
+    pub role_id: i32,
+    pub visibility: Visibility,
+}
@@ -1,15 +1,26 @@
-use crate::{database::Database, error::HttpResult};
+use crate::{
+    database::Database,
+    error::{HttpError, HttpResult},
+    server::auth::Auth,
+};
 use axum::{Extension, Json};
-use serde::{Deserialize, Serialize};
+use axum_auth::AuthBearer;
+use serde::Serialize;
 use std::sync::Arc;
 
 pub const PATH: &str = "/api/user/debates/delete";
 
 #[axum::debug_handler]
 pub async fn handler(
+    Extension(auth): Extension<Auth>,
     Extension(database): Extension<Arc<Database>>,
-    Json(RequestParams { user_id }): Json<RequestParams>,
+    AuthBearer(token): AuthBearer,
 ) -> HttpResult<Json<ResponseData>> {
+    let user_id = auth
+        .verify(&token)
+        .await
+        .map_err(HttpError::Unauthorized)?
+        .id;
     let deleted_count = database.delete_all_user_debates(user_id).await?;
 
     Ok(Json(ResponseData {
@@ -19,11 +30,6 @@ pub async fn handler(
     }))
 }
 
-#[derive(Deserialize)]
-pub struct RequestParams {
-    pub user_id: i32,
-}
-
 #[derive(Serialize)]
 pub struct ResponseData {
     pub success: bool,
@@ -0,0 +1,67 @@
+use crate::{
+    database::{
+        Database,
+        models::{role_reactions::ReactionKind, roles::Visibility},
+    },
+    error::{HttpError, HttpResult},
+    server::auth::Auth,
+    stats::RoleStats,
+};
+use axum::{Extension, Json};
+use axum_auth::AuthBearer;
+use serde::Serialize;
+use std::sync::Arc;
+
+pub const PATH: &str = "/api/user/favourites";
+
+#[axum::debug_handler]
+pub async fn handler(
+    Extension(auth): Extension<Auth>,
+    Extension(database): Extension<Arc<Database>>,
+    AuthBearer(token): AuthBearer,
+) -> HttpResult<Json<Vec<FavouriteData>>> {
+    let user = auth.verify(&token).await.map_err(HttpError::Unauthorized)?;
+
+    let favourites = database
+        .list_user_reactions(user.id, ReactionKind::Favourite)
+        .await?;
+    let mut roles = Vec::new();
+
+    for favourite in &favourites {
+        let role = database.get_role(favourite.role_id).await?;
+
+        // 收藏后被设为私有的角色不再显示
+        if role.is_visible_to(Some(user.id)) {
+            roles.push((role, favourite.created_at));
+        }
+    }
+
+    let ids: Vec<i32> = roles.iter().map(|(role, _)| role.id).collect();
+    let mut stats = database.get_roles_stats(&ids).await?;
+
+    let favourite_data = roles
+        .into_iter()
+        .map(|(role, favourited_at)| FavouriteData {
+            id: role.id,
+            favourited_at,
+            stats: stats.remove(&role.id).unwrap_or_default(),
+            name: role.name,
+            description: role.description,
+            image: role.image,
+            visibility: role.visibility,
+        })
+        .collect();
+
+    Ok(Json(favourite_data))
+}
+
+#[derive(Serialize)]
+pub struct FavouriteData {
+    id: i32,
+    name: String,
+    description: String,
+    image: String,
+    visibility: Visibility,
+    stats: RoleStats,
+    favourited_at: i64,
+}
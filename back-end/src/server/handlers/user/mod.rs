@@ -1,5 +1,7 @@
 pub mod avatar;
 pub mod conversations;
 pub mod debates;
+pub mod favourites;
 pub mod profile;
 pub mod roles;
+pub mod warnings;
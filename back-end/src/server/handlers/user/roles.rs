@@ -1,16 +1,19 @@
 use crate::{
-    database::Database,
+    database::{Database, models::roles::Visibility},
     error::{HttpError, HttpResult},
     server::auth::Auth,
+    stats::{self, RoleStats, TRENDING_WINDOW_DAYS},
 };
 use anyhow::anyhow;
 use axum::{Extension, Json, extract::Path};
 use axum_auth::AuthBearer;
+use chrono::Utc;
 use serde::Serialize;
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
 pub const DELETE_PATH: &str = "/api/user/role/delete/{role_id}";
 pub const LIST_PATH: &str = "/api/user/roles";
+pub const STATS_PATH: &str = "/api/user/roles/stats";
 
 #[axum::debug_handler]
 pub async fn list_handler(
@@ -30,12 +33,55 @@ pub async fn list_handler(
             description: role.description,
             image: role.image,
             traits: role.traits,
+            visibility: role.visibility,
+            hidden: role.hidden,
         })
         .collect();
 
     Ok(Json(role_data))
 }
 
+// 创建者查看自己角色的累计使用量、热度和最近30天每天的使用量
+#[axum::debug_handler]
+pub async fn stats_handler(
+    Extension(auth): Extension<Auth>,
+    Extension(database): Extension<Arc<Database>>,
+    AuthBearer(token): AuthBearer,
+) -> HttpResult<Json<Vec<RoleStatsData>>> {
+    let user = auth.verify(&token).await.map_err(HttpError::Unauthorized)?;
+
+    let roles = database.get_user_roles(user.id).await?;
+    let ids: Vec<i32> = roles.iter().map(|role| role.id).collect();
+
+    let mut role_stats = database.get_roles_stats(&ids).await?;
+    let scores: HashMap<i32, f32> = database.trending_roles().await?.into_iter().collect();
+
+    let since = stats::day_of(Utc::now().timestamp_millis()) - TRENDING_WINDOW_DAYS;
+    let mut daily: HashMap<i32, Vec<DailyStats>> = HashMap::new();
+
+    for stats in database.list_role_daily_stats(&ids, since).await? {
+        daily.entry(stats.role_id).or_default().push(DailyStats {
+            day: stats.day,
+            conversations: stats.conversations,
+            messages: stats.messages,
+            debates: stats.debates,
+        });
+    }
+
+    let stats_data = roles
+        .into_iter()
+        .map(|role| RoleStatsData {
+            id: role.id,
+            name: role.name,
+            stats: role_stats.remove(&role.id).unwrap_or_default(),
+            score: scores.get(&role.id).copied().unwrap_or(0.0),
+            daily: daily.remove(&role.id).unwrap_or_default(),
+        })
+        .collect();
+
+    Ok(Json(stats_data))
+}
+
 #[axum::debug_handler]
 pub async fn delete_handler(
     Extension(auth): Extension<Auth>,
@@ -63,4 +109,27 @@ pub struct RoleData {
     description: String,
     image: String,
     traits: String,
+    visibility: Visibility,
+    // 被审核隐藏的角色只有创建者能看到
+    hidden: bool,
+}
+
+#[derive(Serialize)]
+pub struct RoleStatsData {
+    id: i32,
+    name: String,
+    stats: RoleStats,
+    // 随时间衰减的热度
+    score: f32,
+    // 有使用记录的天，按天排列
+    daily: Vec<DailyStats>,
+}
+
+#[derive(Serialize)]
+pub struct DailyStats {
+    // 自1970-01-01起的天数（UTC）
+    day: i32,
+    conversations: i32,
+    messages: i32,
+    debates: i32,
 }
@@ -0,0 +1,41 @@
+use crate::{
+    database::Database,
+    error::{HttpError, HttpResult},
+    server::auth::Auth,
+};
+use axum::{Extension, Json};
+use axum_auth::AuthBearer;
+use serde::Serialize;
+use std::sync::Arc;
+
+pub const PATH: &str = "/api/user/warnings";
+
+#[axum::debug_handler]
+pub async fn handler(
+    Extension(auth): Extension<Auth>,
+    Extension(database): Extension<Arc<Database>>,
+    AuthBearer(token): AuthBearer,
+) -> HttpResult<Json<Vec<WarningData>>> {
+    let user = auth.verify(&token).await.map_err(HttpError::Unauthorized)?;
+
+    // 不返回审核员信息
+    let warnings = database
+        .list_user_warnings(user.id)
+        .await?
+        .into_iter()
+        .map(|warning| WarningData {
+            id: warning.id,
+            reason: warning.reason,
+            created_at: warning.created_at,
+        })
+        .collect();
+
+    Ok(Json(warnings))
+}
+
+#[derive(Serialize)]
+pub struct WarningData {
+    id: i32,
+    reason: String,
+    created_at: i64,
+}
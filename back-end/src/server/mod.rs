@@ -1,7 +1,11 @@
 use crate::{
-    agents::{AI, Debater, Reciter, Recorder, RoleBuilder, Summarizer},
+    agents::{
+        AI, Debater, Embedder, Reciter, Recommender, Recorder, RoleBatches, RoleBuilder,
+        RoleEmbeddings, RoleJobs, Summarizer,
+    },
     database::Database,
     env::ENV,
+    knowledge::Knowledge,
     storage::StorageClient,
     trace::trace_middleware,
 };
@@ -45,6 +49,8 @@ pub async fn run() {
     .await
     .unwrap();
     database.init().await.unwrap();
+    database.build_search_index().await.unwrap();
+    database.grant_admins(&env.admin_usernames).await.unwrap();
 
     let (socketio_layer, socketio) = SocketIo::builder()
         .ping_interval(Duration::from_secs(3))
@@ -61,29 +67,62 @@ pub async fn run() {
     let database = Arc::new(database);
 
     let reciter = Reciter::new(storage_client.clone(), &env.qiniu_ai_api_key);
-    let role_builder = RoleBuilder::new(ai.clone(), Some(socketio.clone()), reciter.clone());
+    let knowledge = Knowledge::from_config(&env.wiki_endpoints).unwrap();
+    let role_builder = RoleBuilder::new(
+        ai.clone(),
+        Some(socketio.clone()),
+        reciter.clone(),
+        knowledge,
+        storage_client.clone(),
+    );
     let debater = Debater::new(ai.clone(), database.clone());
 
     let role_builder = Arc::new(role_builder);
+    let role_jobs = Arc::new(RoleJobs::new(
+        database.clone(),
+        role_builder.clone(),
+        Some(socketio.clone()),
+    ));
+    role_jobs.resume_unfinished().await.unwrap();
+    let role_batches = Arc::new(RoleBatches::new(
+        database.clone(),
+        role_builder.clone(),
+        Some(socketio.clone()),
+    ));
+    role_batches.resume_unfinished().await.unwrap();
+    // 没有配置向量模型时关闭语义搜索和相似角色，推荐只用热门角色
+    let embedder = (!env.qiniu_embedding_model.is_empty())
+        .then(|| Embedder::new(&env.qiniu_ai_api_key, &env.qiniu_embedding_model));
+    if embedder.is_none() {
+        tracing::warn!("QINIU_EMBEDDING_MODEL is not set, semantic search is disabled");
+    }
+    let role_embeddings = Arc::new(RoleEmbeddings::new(database.clone(), embedder));
+    role_embeddings.start();
+    let recommender = Arc::new(Recommender::new(database.clone(), role_embeddings.clone()));
     let socketio = Arc::new(socketio);
     let auth = auth::Auth::new(database.clone());
+    let auth_s = auth.clone();
 
     let recorder = Recorder::new(&env.qiniu_ai_api_key);
     let database_s = database.clone();
     let summarizer = Arc::new(Summarizer::new(ai.clone(), database.clone()));
+    let summarizer_s = summarizer.clone();
     let reciter_s = reciter.clone();
     let ai = Arc::new(ai);
+    let ai_s = ai.clone();
     socketio.ns("/", |s: SocketRef| {
         sockets::connect(&s);
         s.on_disconnect(sockets::disconnect);
         s.on(sockets::join::EVENT, sockets::join::handler);
+        s.on(sockets::join_user::EVENT, sockets::join_user::handler);
         s.on(sockets::message::EVENT, sockets::message::handler);
         s.on(sockets::voice::EVENT, sockets::voice::handler);
         s.extensions.insert(database_s);
-        s.extensions.insert(ai);
+        s.extensions.insert(ai_s);
         s.extensions.insert(reciter_s);
         s.extensions.insert(recorder);
-        s.extensions.insert(summarizer);
+        s.extensions.insert(summarizer_s);
+        s.extensions.insert(auth_s);
     });
 
     let router = Router::new()
@@ -110,6 +149,74 @@ pub async fn run() {
             handlers::role::generate::PATH,
             post(handlers::role::generate::handler),
         )
+        .route(
+            handlers::role::categories::PATH,
+            get(handlers::role::categories::handler),
+        )
+        .route(
+            handlers::role::duplicates::PATH,
+            get(handlers::role::duplicates::handler),
+        )
+        .route(
+            handlers::role::original::PATH,
+            post(handlers::role::original::handler),
+        )
+        .route(
+            handlers::role::job::NEW_PATH,
+            post(handlers::role::job::new_handler),
+        )
+        .route(
+            handlers::role::job::STATUS_PATH,
+            get(handlers::role::job::status_handler),
+        )
+        .route(
+            handlers::role::job::CANCEL_PATH,
+            post(handlers::role::job::cancel_handler),
+        )
+        .route(
+            handlers::role::job::RESUME_PATH,
+            post(handlers::role::job::resume_handler),
+        )
+        .route(
+            handlers::role::batch::NEW_PATH,
+            post(handlers::role::batch::new_handler),
+        )
+        .route(
+            handlers::role::batch::STATUS_PATH,
+            get(handlers::role::batch::status_handler),
+        )
+        .route(
+            handlers::role::batch::REPORT_PATH,
+            get(handlers::role::batch::report_handler),
+        )
+        .route(
+            handlers::role::update::PATH,
+            post(handlers::role::update::handler),
+        )
+        .route(
+            handlers::role::visibility::PATH,
+            post(handlers::role::visibility::handler),
+        )
+        .route(
+            handlers::role::versions::PATH,
+            get(handlers::role::versions::handler),
+        )
+        .route(
+            handlers::role::diff::PATH,
+            get(handlers::role::diff::handler),
+        )
+        .route(
+            handlers::role::rollback::PATH,
+            post(handlers::role::rollback::handler),
+        )
+        .route(
+            handlers::role::export::PATH,
+            get(handlers::role::export::handler),
+        )
+        .route(
+            handlers::role::import::PATH,
+            post(handlers::role::import::handler),
+        )
         .route(
             handlers::role::details::PATH,
             get(handlers::role::details::handler),
@@ -118,10 +225,54 @@ pub async fn run() {
             handlers::role::list::PATH,
             get(handlers::role::list::handler),
         )
+        .route(
+            handlers::role::feed::PATH,
+            get(handlers::role::feed::handler),
+        )
+        .route(
+            handlers::role::fork::FORK_PATH,
+            post(handlers::role::fork::fork_handler),
+        )
+        .route(
+            handlers::role::fork::ALLOW_PATH,
+            post(handlers::role::fork::allow_handler),
+        )
+        .route(
+            handlers::role::review::SAVE_PATH,
+            post(handlers::role::review::save_handler),
+        )
+        .route(
+            handlers::role::review::LIST_PATH,
+            get(handlers::role::review::list_handler),
+        )
+        .route(
+            handlers::role::review::REPLY_PATH,
+            post(handlers::role::review::reply_handler),
+        )
+        .route(
+            handlers::role::review::REPORT_PATH,
+            post(handlers::role::review::report_handler),
+        )
+        .route(
+            handlers::role::trending::PATH,
+            get(handlers::role::trending::handler),
+        )
+        .route(
+            handlers::role::reaction::PATH,
+            post(handlers::role::reaction::handler),
+        )
         .route(
             handlers::role::search::PATH,
             get(handlers::role::search::handler),
         )
+        .route(
+            handlers::role::semantic::SEARCH_PATH,
+            get(handlers::role::semantic::search_handler),
+        )
+        .route(
+            handlers::role::semantic::SIMILAR_PATH,
+            get(handlers::role::semantic::similar_handler),
+        )
         .route(
             handlers::conversation::new::PATH,
             post(handlers::conversation::new::handler),
@@ -138,6 +289,22 @@ pub async fn run() {
             handlers::conversation::delete::PATH,
             post(handlers::conversation::delete::handler),
         )
+        .route(
+            handlers::conversation::fork::PATH,
+            post(handlers::conversation::fork::handler),
+        )
+        .route(
+            handlers::conversation::tree::PATH,
+            get(handlers::conversation::tree::handler),
+        )
+        .route(
+            handlers::conversation::export::PATH,
+            get(handlers::conversation::export::handler),
+        )
+        .route(
+            handlers::conversation::import::PATH,
+            post(handlers::conversation::import::handler),
+        )
         .route(
             handlers::user::avatar::PATH,
             post(handlers::user::avatar::handler),
@@ -154,6 +321,14 @@ pub async fn run() {
             handlers::user::roles::LIST_PATH,
             get(handlers::user::roles::list_handler),
         )
+        .route(
+            handlers::user::roles::STATS_PATH,
+            get(handlers::user::roles::stats_handler),
+        )
+        .route(
+            handlers::user::favourites::PATH,
+            get(handlers::user::favourites::handler),
+        )
         .route(
             handlers::user::roles::DELETE_PATH,
             delete(handlers::user::roles::delete_handler),
@@ -178,18 +353,89 @@ pub async fn run() {
             handlers::debate::delete::PATH,
             post(handlers::debate::delete::handler),
         )
+        .route(
+            handlers::debate::export::PATH,
+            get(handlers::debate::export::handler),
+        )
         .route(
             handlers::user::debates::PATH,
             post(handlers::user::debates::handler),
         )
+        .route(
+            handlers::lorebook::new::PATH,
+            post(handlers::lorebook::new::handler),
+        )
+        .route(
+            handlers::lorebook::list::PATH,
+            get(handlers::lorebook::list::handler),
+        )
+        .route(
+            handlers::lorebook::delete::PATH,
+            post(handlers::lorebook::delete::handler),
+        )
+        .route(
+            handlers::lorebook::save_entry::PATH,
+            post(handlers::lorebook::save_entry::handler),
+        )
+        .route(
+            handlers::lorebook::delete_entry::PATH,
+            post(handlers::lorebook::delete_entry::handler),
+        )
+        .route(handlers::report::PATH, post(handlers::report::handler))
+        .route(
+            handlers::user::warnings::PATH,
+            get(handlers::user::warnings::handler),
+        )
+        .route(
+            handlers::moderation::queue::PATH,
+            get(handlers::moderation::queue::handler),
+        )
+        .route(
+            handlers::moderation::content::PATH,
+            post(handlers::moderation::content::handler),
+        )
+        .route(
+            handlers::moderation::user::PATH,
+            post(handlers::moderation::user::handler),
+        )
+        .route(
+            handlers::moderation::logs::PATH,
+            get(handlers::moderation::logs::handler),
+        )
+        .route(
+            handlers::admin::users::LIST_PATH,
+            get(handlers::admin::users::list_handler),
+        )
+        .route(
+            handlers::admin::users::ROLE_PATH,
+            post(handlers::admin::users::role_handler),
+        )
+        .route(
+            handlers::admin::roles::TRANSFER_PATH,
+            post(handlers::admin::roles::transfer_handler),
+        )
+        .route(
+            handlers::admin::roles::DELETE_PATH,
+            post(handlers::admin::roles::delete_handler),
+        )
+        .route(
+            handlers::admin::stats::PATH,
+            get(handlers::admin::stats::handler),
+        )
         .layer(middleware::from_fn(trace_middleware))
         .layer(DefaultBodyLimit::max(50 * 1024 * 1024))
         .layer(Extension(storage_client))
         .layer(Extension(database))
         .layer(Extension(auth))
         .layer(Extension(role_builder))
+        .layer(Extension(role_jobs))
+        .layer(Extension(role_batches))
+        .layer(Extension(role_embeddings))
+        .layer(Extension(recommender))
         .layer(Extension(reciter))
+        .layer(Extension(ai))
         .layer(Extension(debater))
+        .layer(Extension(summarizer))
         .layer(socketio_layer)
         .layer(Extension(socketio));
 
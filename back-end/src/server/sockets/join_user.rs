@@ -0,0 +1,27 @@
+use crate::{agents::role_jobs::user_room, server::auth::Auth};
+use serde::Deserialize;
+use socketioxide::extract::{Data, Extension, SocketRef};
+
+pub const EVENT: &str = "join_user";
+
+// 验证令牌后加入用户房间，用于接收后台任务的推送
+pub async fn handler(
+    socket: SocketRef,
+    Extension(auth): Extension<Auth>,
+    Data(JoinUserData { token }): Data<JoinUserData>,
+) {
+    match auth.verify(&token).await {
+        Ok(user) => {
+            let room = user_room(user.id);
+            socket.join(room.clone());
+
+            tracing::info!("client {} joined room {}", socket.id, room);
+        }
+        Err(e) => tracing::warn!("client {} failed to join user room: {}", socket.id, e),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct JoinUserData {
+    pub token: String,
+}
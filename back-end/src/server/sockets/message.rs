@@ -4,8 +4,10 @@ use crate::{
         reciter::{Reciter, remove_brackets},
     },
     database::Database,
+    lorebook,
+    server::auth::Auth,
 };
-use anyhow::Result;
+use anyhow::{Result, anyhow};
 use serde::{Deserialize, Serialize};
 use socketioxide::extract::{Data, Extension, SocketRef};
 use std::sync::Arc;
@@ -14,32 +16,50 @@ pub const EVENT: &str = "message";
 
 pub async fn handler(
     socket: SocketRef,
+    auth: Extension<Auth>,
     ai: Extension<Arc<AI>>,
     database: Extension<Arc<Database>>,
     reciter: Extension<Reciter>,
     summarizer: Extension<Arc<Summarizer>>,
     data: Data<MessageData>,
 ) {
-    if let Err(e) = handler_inner(socket, ai, database, reciter, summarizer, data).await {
+    if let Err(e) = handler_inner(socket, auth, ai, database, reciter, summarizer, data).await {
         tracing::error!("socket message handler error: {}", e);
     }
 }
 
 pub async fn handler_inner(
     socket: SocketRef,
+    Extension(auth): Extension<Auth>,
     Extension(ai): Extension<Arc<AI>>,
     Extension(database): Extension<Arc<Database>>,
     Extension(reciter): Extension<Reciter>,
     Extension(summarizer): Extension<Arc<Summarizer>>,
     Data(MessageData {
-        user_id,
+        token,
         role_id,
+        conversation_id,
         timestamp,
         text,
     }): Data<MessageData>,
 ) -> Result<()> {
+    // 用户由令牌确定，被封禁的用户不能继续对话
+    let user_id = auth.verify(&token).await?.id;
+
+    let conversation_id = database
+        .resolve_conversation(user_id, role_id, conversation_id)
+        .await?
+        .id;
+
+    // 角色在对话创建后被设为私有或被隐藏时不能继续对话
+    let role = database.get_role(role_id).await?;
+
+    if !role.is_visible_to(Some(user_id)) {
+        return Err(anyhow!("Permission denied"));
+    }
+
     database
-        .add_dialog(user_id, role_id, true, timestamp, &text, None)
+        .add_dialog_by_id(conversation_id, true, timestamp, &text, None, None)
         .await?;
 
     socket.emit(
@@ -51,10 +71,15 @@ pub async fn handler_inner(
         },
     )?;
 
-    let role = database.get_role(role_id).await?;
-    let history = database.get_history(user_id, role_id).await?;
+    let history = database.get_history_by_id(conversation_id).await?;
+
+    let dialog_count = database.get_dialog_count_by_id(conversation_id).await?;
+    let system = role.prompt_with_examples(dialog_count);
+
+    let (system, history) =
+        lorebook::build_context(&database, &role, conversation_id, &system, &history).await?;
 
-    let answer = ai.chat_once(&role.prompt(), &text, Some(&history)).await?;
+    let answer = ai.chat_once(&system, &text, Some(&history)).await?;
 
     let cleaned_answer = remove_brackets(&answer);
     let audio_data = reciter.tts(&cleaned_answer, &role.voice_type).await?;
@@ -62,13 +87,13 @@ pub async fn handler_inner(
 
     let timestamp = chrono::Utc::now().timestamp_millis();
     database
-        .add_dialog(
-            user_id,
-            role_id,
+        .add_dialog_by_id(
+            conversation_id,
             false,
             timestamp,
             &answer,
             Some(voice_url.clone()),
+            Some(role.version),
         )
         .await?;
 
@@ -76,13 +101,14 @@ pub async fn handler_inner(
         "message",
         &EmitData {
             role_id,
+            conversation_id,
             timestamp,
             text: answer,
             voice_url,
         },
     )?;
 
-    if let Err(e) = summarizer.check_and_trigger(user_id, role_id).await {
+    if let Err(e) = summarizer.check_and_trigger(conversation_id).await {
         tracing::warn!("Summarizer trigger failed: {}", e);
     }
 
@@ -91,8 +117,9 @@ pub async fn handler_inner(
 
 #[derive(Deserialize)]
 pub struct MessageData {
-    pub user_id: i32,
+    pub token: String,
     pub role_id: i32,
+    pub conversation_id: Option<i32>,
     pub timestamp: i64,
     pub text: String,
 }
@@ -100,6 +127,7 @@ pub struct MessageData {
 #[derive(Serialize)]
 pub struct EmitData {
     pub role_id: i32,
+    pub conversation_id: i32,
     pub timestamp: i64,
     pub text: String,
     pub voice_url: String,
@@ -1,4 +1,5 @@
 pub mod join;
+pub mod join_user;
 pub mod message;
 pub mod voice;
 
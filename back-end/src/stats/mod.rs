@@ -0,0 +1,267 @@
+use crate::database::models::{
+    role_daily_stats,
+    role_reactions::{self, ReactionKind},
+};
+use serde::Serialize;
+use std::collections::HashMap;
+
+// 热度每3天减半
+const HALF_LIFE_DAYS: f32 = 3.0;
+// 热度只统计最近30天
+pub const TRENDING_WINDOW_DAYS: i32 = 30;
+
+// 各项使用量在热度中的权重
+const CONVERSATION_WEIGHT: f32 = 3.0;
+const MESSAGE_WEIGHT: f32 = 0.1;
+const DEBATE_WEIGHT: f32 = 2.0;
+const FAVOURITE_WEIGHT: f32 = 5.0;
+const LIKE_WEIGHT: f32 = 2.0;
+
+// 评分少时向3分收缩，相当于预先有5个3分
+const PRIOR_RATING: f32 = 3.0;
+const PRIOR_COUNT: f32 = 5.0;
+// 评分对搜索相关度的最大影响，5分加30%，1分减30%
+const RATING_BOOST: f32 = 0.3;
+
+pub const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+
+// 全站的用户、角色、对话和辩论数
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+pub struct GlobalStats {
+    pub users: i64,
+    pub roles: i64,
+    pub conversations: i64,
+    // 所有对话的消息数，包括分支
+    pub dialogs: i64,
+    pub debates: i64,
+}
+
+// 角色的累计使用量和评分
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+pub struct RoleStats {
+    pub conversations: i64,
+    pub messages: i64,
+    pub debates: i64,
+    // 独立用户数
+    pub users: i64,
+    pub favourites: i64,
+    pub likes: i64,
+    // 评价数和平均分，没有评价时为空
+    pub ratings: i64,
+    pub rating: Option<f32>,
+}
+
+// 角色的评分分布，counts[0]为1分的个数
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize)]
+pub struct Rating {
+    pub counts: [i64; 5],
+}
+
+impl RoleStats {
+    pub fn add(&mut self, daily: &role_daily_stats::Model) {
+        self.conversations += daily.conversations as i64;
+        self.messages += daily.messages as i64;
+        self.debates += daily.debates as i64;
+    }
+
+    pub fn add_reactions(&mut self, kind: ReactionKind, count: i64) {
+        match kind {
+            ReactionKind::Favourite => self.favourites += count,
+            ReactionKind::Like => self.likes += count,
+        }
+    }
+
+    pub fn set_rating(&mut self, rating: &Rating) {
+        self.ratings = rating.count();
+        self.rating = rating.average();
+    }
+}
+
+impl Rating {
+    // 超出1~5的评分忽略
+    pub fn add(&mut self, rating: i32) {
+        if (1..=5).contains(&rating) {
+            self.counts[rating as usize - 1] += 1;
+        }
+    }
+
+    pub fn count(&self) -> i64 {
+        self.counts.iter().sum()
+    }
+
+    fn total(&self) -> i64 {
+        self.counts
+            .iter()
+            .enumerate()
+            .map(|(i, count)| (i as i64 + 1) * count)
+            .sum()
+    }
+
+    pub fn average(&self) -> Option<f32> {
+        let count = self.count();
+
+        (count > 0).then(|| self.total() as f32 / count as f32)
+    }
+
+    // 贝叶斯平均，避免一两个评价左右排名
+    fn bayesian_average(&self) -> f32 {
+        (PRIOR_RATING * PRIOR_COUNT + self.total() as f32) / (PRIOR_COUNT + self.count() as f32)
+    }
+
+    // 搜索相关度的系数，没有评价或3分时为1
+    pub fn boost(&self) -> f32 {
+        1.0 + RATING_BOOST * (self.bayesian_average() - PRIOR_RATING) / 2.0
+    }
+}
+
+// 相关度乘以评分系数后重新排列，返回角色ID
+pub fn rank_with_ratings(scores: Vec<(i32, f32)>, ratings: &HashMap<i32, Rating>) -> Vec<i32> {
+    let mut scores: Vec<(i32, f32)> = scores
+        .into_iter()
+        .map(|(role_id, score)| {
+            let boost = ratings.get(&role_id).map(Rating::boost).unwrap_or(1.0);
+            (role_id, score * boost)
+        })
+        .collect();
+    scores.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+    scores.into_iter().map(|(role_id, _)| role_id).collect()
+}
+
+// 一次记录的使用量增量
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Usage {
+    pub conversations: i32,
+    pub messages: i32,
+    pub debates: i32,
+}
+
+// 毫秒时间戳所在的天，自1970-01-01起（UTC）
+pub fn day_of(timestamp: i64) -> i32 {
+    timestamp.div_euclid(DAY_MS) as i32
+}
+
+fn decay(today: i32, day: i32) -> f32 {
+    0.5f32.powf((today - day).max(0) as f32 / HALF_LIFE_DAYS)
+}
+
+// 按天加权求和后随时间衰减，返回按热度从高到低排列的(角色ID, 热度)
+pub fn trending_scores(
+    daily: &[role_daily_stats::Model],
+    reactions: &[role_reactions::Model],
+    today: i32,
+) -> Vec<(i32, f32)> {
+    let mut scores: HashMap<i32, f32> = HashMap::new();
+
+    for stats in daily {
+        let usage = CONVERSATION_WEIGHT * stats.conversations as f32
+            + MESSAGE_WEIGHT * stats.messages as f32
+            + DEBATE_WEIGHT * stats.debates as f32;
+
+        *scores.entry(stats.role_id).or_default() += usage * decay(today, stats.day);
+    }
+
+    for reaction in reactions {
+        let weight = match reaction.kind {
+            ReactionKind::Favourite => FAVOURITE_WEIGHT,
+            ReactionKind::Like => LIKE_WEIGHT,
+        };
+
+        *scores.entry(reaction.role_id).or_default() +=
+            weight * decay(today, day_of(reaction.created_at));
+    }
+
+    let mut scores: Vec<(i32, f32)> = scores
+        .into_iter()
+        .filter(|(_, score)| *score > 0.0)
+        .collect();
+    scores.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+    scores
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn daily(role_id: i32, day: i32, conversations: i32, messages: i32) -> role_daily_stats::Model {
+        role_daily_stats::Model {
+            role_id,
+            day,
+            conversations,
+            messages,
+            debates: 0,
+        }
+    }
+
+    #[test]
+    fn test_day_of() {
+        assert_eq!(day_of(0), 0);
+        assert_eq!(day_of(DAY_MS - 1), 0);
+        assert_eq!(day_of(DAY_MS), 1);
+        assert_eq!(day_of(-1), -1);
+    }
+
+    #[test]
+    fn test_trending_scores() {
+        let today = 100;
+        let stats = [
+            // 很久以前的大量使用
+            daily(1, today - 20, 30, 300),
+            // 最近的少量使用
+            daily(2, today, 3, 20),
+            daily(2, today - 1, 2, 10),
+            daily(3, today - 2, 0, 0),
+        ];
+        let reactions = [role_reactions::Model {
+            user_id: 1,
+            role_id: 3,
+            kind: ReactionKind::Favourite,
+            created_at: today as i64 * DAY_MS,
+        }];
+
+        let scores = trending_scores(&stats, &reactions, today);
+        let ids: Vec<i32> = scores.iter().map(|(role_id, _)| *role_id).collect();
+
+        assert_eq!(ids, vec![2, 3, 1]);
+        assert!(trending_scores(&[], &[], today).is_empty());
+    }
+
+    fn rating(ratings: &[i32]) -> Rating {
+        let mut rating = Rating::default();
+
+        for value in ratings {
+            rating.add(*value);
+        }
+
+        rating
+    }
+
+    #[test]
+    fn test_rating() {
+        let empty = Rating::default();
+        assert_eq!(empty.average(), None);
+        assert_eq!(empty.boost(), 1.0);
+
+        let rating = rating(&[5, 4, 0, 6]);
+        assert_eq!(rating.count(), 2);
+        assert_eq!(rating.average(), Some(4.5));
+
+        // 评价多的5分高于只有一个的5分
+        assert!(self::rating(&[5; 20]).boost() > self::rating(&[5]).boost());
+        assert!(self::rating(&[1; 20]).boost() < 1.0);
+        assert!(self::rating(&[5; 1000]).boost() <= 1.0 + RATING_BOOST);
+    }
+
+    #[test]
+    fn test_rank_with_ratings() {
+        let scores = vec![(1, 1.0), (2, 0.9), (3, 0.5)];
+        let ratings = HashMap::from([(1, rating(&[1; 10])), (2, rating(&[5; 10]))]);
+
+        assert_eq!(
+            rank_with_ratings(scores.clone(), &HashMap::new()),
+            vec![1, 2, 3]
+        );
+        assert_eq!(rank_with_ratings(scores, &ratings), vec![2, 1, 3]);
+    }
+}
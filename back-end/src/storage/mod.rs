@@ -10,11 +10,15 @@ use qiniu_sdk::{
 use serde::Deserialize;
 use std::time::Duration;
 
+// 下载存储空间中文件的超时时间
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
 pub struct StorageClient {
     credential: Credential,
     client: Client,
     bucket_name: &'static str,
     domain: String,
+    http_client: reqwest::Client,
 }
 
 impl StorageClient {
@@ -24,11 +28,19 @@ impl StorageClient {
         let credential = Credential::new(access_key, secret_key);
         let client = Client::default();
 
+        // 不跟随重定向，避免被引到其他地址
+        let http_client = reqwest::Client::builder()
+            .timeout(FETCH_TIMEOUT)
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .unwrap();
+
         Self {
             credential,
             client,
             bucket_name: BUCKET_NAME,
             domain: String::new(),
+            http_client,
         }
     }
 
@@ -139,6 +151,55 @@ impl StorageClient {
     pub fn get_object_url(&self, name: &str) -> String {
         format!("http://{}/{}", self.domain, name)
     }
+
+    pub fn is_object_url(&self, url: &str) -> bool {
+        match reqwest::Url::parse(url) {
+            Ok(url) => {
+                matches!(url.scheme(), "http" | "https")
+                    && url.port().is_none()
+                    && url.host_str() == Some(self.domain.as_str())
+            }
+            Err(_) => false,
+        }
+    }
+
+    // 只下载存储空间中的文件，其他地址一律拒绝，避免服务端请求内网；超过大小限制时返回错误
+    pub async fn fetch_object(
+        &self,
+        url: &str,
+        max_bytes: usize,
+    ) -> Result<(Option<String>, Vec<u8>)> {
+        if !self.is_object_url(url) {
+            return Err(anyhow!("Not a storage object: {}", url));
+        }
+
+        let mut res = self.http_client.get(url).send().await?.error_for_status()?;
+
+        if res
+            .content_length()
+            .is_some_and(|length| length > max_bytes as u64)
+        {
+            return Err(anyhow!("Object too large: {}", url));
+        }
+
+        let content_type = res
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.to_string());
+
+        let mut data = Vec::new();
+
+        while let Some(chunk) = res.chunk().await? {
+            if data.len() + chunk.len() > max_bytes {
+                return Err(anyhow!("Object too large: {}", url));
+            }
+
+            data.extend_from_slice(&chunk);
+        }
+
+        Ok((content_type, data))
+    }
 }
 
 #[derive(Deserialize)]
@@ -153,6 +214,20 @@ mod tests {
     use super::*;
     use crate::env::tests::get_env;
 
+    #[test]
+    fn test_is_object_url() {
+        let mut storage_client = StorageClient::new("access_key", "secret_key");
+        storage_client.domain = "cdn.example.com".to_string();
+
+        assert!(storage_client.is_object_url("http://cdn.example.com/avatar.png"));
+        assert!(storage_client.is_object_url("https://cdn.example.com/voice/1.mp3"));
+        assert!(!storage_client.is_object_url("http://cdn.example.com:8080/avatar.png"));
+        assert!(!storage_client.is_object_url("http://cdn.example.com.evil.com/avatar.png"));
+        assert!(!storage_client.is_object_url("http://127.0.0.1/avatar.png"));
+        assert!(!storage_client.is_object_url("file:///etc/passwd"));
+        assert!(!storage_client.is_object_url("not a url"));
+    }
+
     #[tokio::test]
     async fn test_storage_client() {
         let env = get_env();